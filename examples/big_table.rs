@@ -92,7 +92,13 @@ impl<RowData: Data, CR: CellRender<RowData>, TableData: IndexedData<Item = RowDa
         RemapSpec::default()
     }
 
-    fn remap_items(&self, _table_data: &TableData, _remap_spec: &RemapSpec) -> Remap {
+    fn remap_items(
+        &self,
+        _table_data: &TableData,
+        _remap_spec: &RemapSpec,
+        _prior: &Remap,
+        _env: &Env,
+    ) -> Remap {
         Remap::Pristine
     }
 }
@@ -105,6 +111,10 @@ where
     fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<TableData::Item>>> {
         None
     }
+
+    fn can_edit(&self, _ctx: &CellCtx) -> bool {
+        false
+    }
 }
 
 fn build_root_widget() -> impl Widget<LogIdxTable> {
@@ -0,0 +1,41 @@
+use druid::{AppLauncher, Widget, WindowDesc};
+use druid_table::numbers_table::{FormulaCell, FormulaRow, FormulaTable};
+use druid_table::{Table, TableBuilder};
+
+const SOURCE_COLUMNS: usize = 3;
+
+fn build_root_widget() -> impl Widget<FormulaTable> {
+    let mut table_builder = TableBuilder::<FormulaRow, FormulaTable>::new();
+    for col in 0..SOURCE_COLUMNS {
+        table_builder.add_column(format!("Quarter {}", col + 1), FormulaCell::new(col));
+    }
+    let table_builder = table_builder.with_column("Total", FormulaCell::new(SOURCE_COLUMNS));
+
+    let measures = table_builder.build_measures();
+    Table::new_in_scope(table_builder.build_args(), measures)
+}
+
+pub fn main() {
+    // describe the main window
+    let main_window = WindowDesc::new(build_root_widget())
+        .title("Formula table")
+        .window_size((500.0, 300.0));
+
+    // Double click a quarter to edit it, and its row's total follows
+    let rows = (0..6)
+        .map(|row| {
+            let mut inputs: Vec<String> = (0..SOURCE_COLUMNS)
+                .map(|col| ((row + 1) * 10 + col).to_string())
+                .collect();
+            inputs.push(format!("=SUM(R{0}C0:R{0}C{1})", row, SOURCE_COLUMNS - 1));
+            inputs
+        })
+        .collect();
+    let initial_state = FormulaTable::new(rows);
+
+    // start the application
+    AppLauncher::with_window(main_window)
+        .log_to_console()
+        .launch(initial_state)
+        .expect("Failed to launch application");
+}
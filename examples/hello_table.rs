@@ -1,8 +1,9 @@
 use std::fmt::Debug;
 
 use druid_table::{
-    column, AxisMeasurementType, CellCtx, CellRender, CellRenderExt, DataCompare, EditorFactory,
-    ShowHeadings, SortDirection, Table, TableAxis, TableBuilder, TextCell,
+    column, AxisMeasurementType, CellCtx, CellRender, CellRenderExt, ChoiceCell, DataCompare,
+    EditorFactory, NumberCell, ShowHeadings, SortDirection, Table, TableAxis, TableBuilder,
+    TextCell,
 };
 
 use druid::im::{vector, Vector};
@@ -22,12 +23,21 @@ use std::f64::consts::PI;
 
 const WINDOW_TITLE: LocalizedString<HelloState> = LocalizedString::new("Hello Table!");
 
+#[derive(Clone, Copy, Data, PartialEq, Debug)]
+enum Status {
+    Todo,
+    Doing,
+    Done,
+}
+
 #[derive(Clone, Data, Lens, Debug)]
 struct HelloRow {
     lang: String,
     greeting: String,
     westernised: String,
     who_knows: f64,
+    vs_average: f64,
+    status: Status,
 }
 
 impl HelloRow {
@@ -42,6 +52,14 @@ impl HelloRow {
             greeting: greeting.into(),
             westernised: westernised.into(),
             who_knows: percent / 100.,
+            vs_average: percent - 70.,
+            status: if percent > 90. {
+                Status::Done
+            } else if percent > 50. {
+                Status::Doing
+            } else {
+                Status::Todo
+            },
         }
     }
 }
@@ -98,6 +116,10 @@ impl EditorFactory<f64> for PieCell {
     fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<f64>>> {
         None
     }
+
+    fn can_edit(&self, _ctx: &CellCtx) -> bool {
+        false
+    }
 }
 
 fn build_main_widget() -> impl Widget<HelloState> {
@@ -209,7 +231,13 @@ fn build_table(settings: Settings) -> impl Widget<Vector<HelloRow>> {
         )
         .headings(settings.show_headings)
         .border(settings.border_thickness)
-        .with_column("Language", TextCell::new().lens(HelloRow::lang))
+        .frozen_columns(1)
+        .with_column(
+            "Language",
+            TextCell::new()
+                .compare_with(|a: &String, b: &String| a.to_lowercase().cmp(&b.to_lowercase()))
+                .lens(HelloRow::lang),
+        )
         .with_column(
             "Greeting",
             TextCell::new().font_size(17.).lens(HelloRow::greeting),
@@ -222,6 +250,29 @@ fn build_table(settings: Settings) -> impl Widget<Vector<HelloRow>> {
             column("Who knows?", PieCell {}.lens(HelloRow::who_knows))
                 .sort(SortDirection::Ascending),
         )
+        .with_column(
+            "Vs. average",
+            NumberCell::new()
+                .decimals(1)
+                .suffix("%")
+                .with_bg(|v: &f64, _: &Env| {
+                    Some(if *v < 0. {
+                        Color::rgb8(0xFF, 0xB0, 0xB0)
+                    } else {
+                        Color::rgb8(0xB0, 0xFF, 0xB0)
+                    })
+                })
+                .lens(HelloRow::vs_average),
+        )
+        .with_column(
+            "Status",
+            ChoiceCell::new(vec![
+                ("To do".to_string(), Status::Todo),
+                ("Doing".to_string(), Status::Doing),
+                ("Done".to_string(), Status::Done),
+            ])
+            .lens(HelloRow::status),
+        )
         .with_column(
             "Greeting 2 with very long column name",
             TextCell::new()
@@ -1,10 +1,9 @@
 use crate::config::{DEFAULT_COL_HEADER_HEIGHT, DEFAULT_ROW_HEADER_WIDTH};
 use crate::{AxisMeasurementType, Remap};
 use druid::{Cursor, Data, Point, Rect, Size};
-use float_ord::FloatOrd;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
 use std::iter::Map;
@@ -113,12 +112,11 @@ impl From<LogIdx> for usize {
     }
 }
 
+// Ascending visual indices, reversible with .rev() and strided with .step_by()
+pub type VisIdxIter = Map<RangeInclusive<usize>, fn(usize) -> VisIdx>;
+
 impl VisIdx {
-    // Todo work out how to support custom range
-    pub fn range_inc_iter(
-        from_inc: VisIdx,
-        to_inc: VisIdx,
-    ) -> Map<RangeInclusive<usize>, fn(usize) -> VisIdx> {
+    pub fn range_inc_iter(from_inc: VisIdx, to_inc: VisIdx) -> VisIdxIter {
         ((from_inc.0)..=(to_inc.0)).map(VisIdx)
     }
 
@@ -151,12 +149,15 @@ impl Sub<VisOffset> for VisIdx {
 #[derive(Debug, Clone)]
 pub struct AxisMeasure {
     inner: AxisMeasureInner,
-    version: u64,
+    version: Cell<u64>, // Bumped on any change, including fits from layout
+    // Laid out from the far end, eg columns right to left, across at least the fitted length
+    reversed: bool,
+    fit_length: Cell<f64>,
 }
 
 impl Data for AxisMeasure {
     fn same(&self, other: &Self) -> bool {
-        self.version == other.version
+        self.version.get() == other.version.get()
     }
 }
 
@@ -171,7 +172,9 @@ impl AxisMeasure {
                     StoredAxisMeasure::new(pixels_per_unit),
                 ))),
             },
-            version: 0,
+            version: Cell::new(0),
+            reversed: false,
+            fit_length: Cell::new(0.),
         }
     }
 }
@@ -185,6 +188,10 @@ enum AxisMeasureInner {
 use AxisMeasureInner::*;
 
 impl AxisMeasure {
+    fn bump(&self) {
+        self.version.set(self.version.get() + 1);
+    }
+
     fn border(&self) -> f64 {
         match &self.inner {
             Fixed(f) => f.border,
@@ -197,11 +204,38 @@ impl AxisMeasure {
             Fixed(f) => f.set_axis_properties(border, len, remap),
             Stored(s) => s.borrow_mut().set_axis_properties(border, len, remap),
         } {
-            self.version += 1
+            self.bump();
+        }
+    }
+
+    pub fn set_reversed(&mut self, reversed: bool) {
+        if self.reversed != reversed {
+            self.reversed = reversed;
+            self.bump();
+        }
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    // The length a reversed measure is mirrored across, so it hugs the far end
+    fn span(&self) -> f64 {
+        self.unmirrored_total().max(self.fit_length.get())
+    }
+
+    // A pixel as measured from the near end, and back again
+    fn mirror(&self, pixel: f64) -> f64 {
+        if self.reversed {
+            self.span() - pixel
+        } else {
+            pixel
         }
     }
 
     pub fn vis_range_from_pixels(&self, p0: f64, p1: f64) -> (VisIdx, VisIdx) {
+        // The far pixel holds the lower index when reversed
+        let (p0, p1) = if self.reversed { (p1, p0) } else { (p0, p1) };
         let start = self.vis_idx_from_pixel(p0).unwrap_or(VisIdx(0));
         let end = self
             .vis_idx_from_pixel(p1)
@@ -210,6 +244,14 @@ impl AxisMeasure {
     }
 
     pub fn total_pixel_length(&self) -> f64 {
+        if self.reversed {
+            self.span()
+        } else {
+            self.unmirrored_total()
+        }
+    }
+
+    fn unmirrored_total(&self) -> f64 {
         match &self.inner {
             Fixed(f) => f.total_pixel_length(),
             Stored(s) => s.borrow().total_pixel_length(),
@@ -217,19 +259,24 @@ impl AxisMeasure {
     }
 
     fn last_vis_idx(&self) -> VisIdx {
-        let len = match &self.inner {
+        VisIdx(self.vis_len() - 1)
+    }
+
+    // How many items are laid out, ie shown
+    pub fn vis_len(&self) -> usize {
+        match &self.inner {
             Fixed(f) => f.len,
             Stored(s) => s.borrow().vis_pix_lengths.len(),
-        };
-        VisIdx(len - 1)
+        }
     }
 
     pub(crate) fn pixel_near_border(&self, pixel: f64) -> Option<VisIdx> {
-        let idx = self.vis_idx_from_pixel(pixel)?;
-        let idx_border_middle = self.first_pixel_from_vis(idx).unwrap_or(0.) - self.border() / 2.;
+        let pixel = self.mirror(pixel);
+        let idx = self.unmirrored_vis_idx(pixel)?;
+        let idx_border_middle = self.unmirrored_first_pixel(idx).unwrap_or(0.) - self.border() / 2.;
         let next_border_middle = self
-            .first_pixel_from_vis(idx + VisOffset(1))
-            .unwrap_or_else(|| self.total_pixel_length())
+            .unmirrored_first_pixel(idx + VisOffset(1))
+            .unwrap_or_else(|| self.unmirrored_total())
             - self.border() / 2.;
         if f64::abs(pixel - idx_border_middle) < MOUSE_MOVE_EPSILON {
             Some(idx)
@@ -241,13 +288,27 @@ impl AxisMeasure {
     }
 
     pub fn vis_idx_from_pixel(&self, pixel: f64) -> Option<VisIdx> {
+        self.unmirrored_vis_idx(self.mirror(pixel))
+    }
+
+    fn unmirrored_vis_idx(&self, pixel: f64) -> Option<VisIdx> {
         match &self.inner {
             Fixed(f) => f.vis_idx_from_pixel(pixel),
             Stored(s) => s.borrow().vis_idx_from_pixel(pixel),
         }
     }
 
+    // The near edge as drawn, which is the left even when reversed
     pub fn first_pixel_from_vis(&self, idx: VisIdx) -> Option<f64> {
+        let first = self.unmirrored_first_pixel(idx)?;
+        if self.reversed {
+            Some(self.span() - first - self.pixels_length_for_vis(idx)?)
+        } else {
+            Some(first)
+        }
+    }
+
+    fn unmirrored_first_pixel(&self, idx: VisIdx) -> Option<f64> {
         match &self.inner {
             Fixed(f) => f.first_pixel_from_vis(idx),
             Stored(s) => s.borrow().first_pixel_from_vis(idx),
@@ -261,6 +322,22 @@ impl AxisMeasure {
         }
     }
 
+    // Length of an item regardless of where it is currently shown
+    pub fn pixels_length_for_log(&self, idx: LogIdx) -> Option<f64> {
+        match &self.inner {
+            Fixed(f) => f.pixels_length_for_log(idx),
+            Stored(s) => s.borrow().pixels_length_for_log(idx),
+        }
+    }
+
+    // The length items have until they are resized
+    pub fn default_length(&self) -> f64 {
+        match &self.inner {
+            Fixed(f) => f.pixels_per_unit,
+            Stored(s) => s.borrow().default_pixels,
+        }
+    }
+
     pub fn can_resize(&self, idx: VisIdx) -> bool {
         match &self.inner {
             Fixed(f) => f.can_resize(idx),
@@ -268,13 +345,85 @@ impl AxisMeasure {
         }
     }
 
+    // When reversed, the far edge being dragged is the left one
     pub fn set_far_pixel_for_vis(&mut self, idx: VisIdx, pixel: f64) {
+        let pixel = self.mirror(pixel);
         // Check if changed
         if match &mut self.inner {
             Fixed(f) => f.set_far_pixel_for_vis(idx, pixel),
             Stored(s) => s.borrow_mut().set_far_pixel_for_vis(idx, pixel),
         } {
-            self.version += 1;
+            self.bump();
+        }
+    }
+
+    pub fn set_pixel_length_for_vis(&mut self, idx: VisIdx, length: f64) {
+        if match &mut self.inner {
+            Fixed(f) => f.set_pixel_length_for_vis(idx, length),
+            Stored(s) => s.borrow_mut().set_pixel_length_for_vis(idx, length),
+        } {
+            self.bump();
+        }
+    }
+
+    pub fn set_pixel_length_for_log(&mut self, idx: LogIdx, length: f64) {
+        if match &mut self.inner {
+            Fixed(f) => f.set_pixel_length_for_log(idx, length),
+            Stored(s) => s.borrow_mut().set_pixel_length_for_log(idx, length),
+        } {
+            self.bump();
+        }
+    }
+
+    // (min, max) pixel lengths by logical index, applied to later resizes
+    pub fn set_log_limits(&mut self, limits: Vec<(f64, f64)>) {
+        if match &mut self.inner {
+            Fixed(f) => f.set_log_limits(limits),
+            Stored(s) => s.borrow_mut().set_log_limits(limits),
+        } {
+            self.bump();
+        }
+    }
+
+    // Lengths by logical index that items start at. Items not resized yet follow a new one.
+    pub fn set_log_initial(&mut self, lengths: Vec<Option<f64>>) {
+        if match &mut self.inner {
+            Fixed(f) => f.set_log_initial(lengths),
+            Stored(s) => s.borrow_mut().set_log_initial(lengths),
+        } {
+            self.bump();
+        }
+    }
+
+    // Flex weights by logical index, zero for items that keep their own length
+    pub fn set_log_flex(&mut self, weights: Vec<f64>) {
+        if match &mut self.inner {
+            Fixed(f) => f.set_log_flex(weights),
+            Stored(s) => s.borrow_mut().set_log_flex(weights),
+        } {
+            self.bump();
+        }
+    }
+
+    // Shares out whatever the other items leave of `available` by flex weight.
+    // Called from layout, where we only have shared access to the measures
+    pub(crate) fn fit_flex(&self, available: f64) {
+        self.fit_length.set(available);
+        if let Stored(s) = &self.inner {
+            if s.borrow_mut().fit_flex(available) {
+                self.bump();
+            }
+        }
+    }
+
+    // Scales every item, within its limits, so they take up all of `available` in the
+    // proportions they have now. Called from layout like fit_flex.
+    pub(crate) fn fit_all(&self, available: f64) {
+        self.fit_length.set(available);
+        if let Stored(s) = &self.inner {
+            if s.borrow_mut().fit_all(available) {
+                self.bump();
+            }
         }
     }
 
@@ -282,6 +431,22 @@ impl AxisMeasure {
         self.first_pixel_from_vis(idx)
             .and_then(|p| self.pixels_length_for_vis(idx).map(|l| p + l))
     }
+
+    // The pixels covering first..=last, whichever way round they are laid out. A last item
+    // past the end runs to the end of them all.
+    pub(crate) fn pixel_range(&self, first: VisIdx, last: VisIdx) -> Option<(f64, f64)> {
+        if self.reversed {
+            let start = self
+                .first_pixel_from_vis(last)
+                .unwrap_or_else(|| self.span() - self.unmirrored_total());
+            Some((start, self.far_pixel_from_vis(first)?))
+        } else {
+            let end = self
+                .far_pixel_from_vis(last)
+                .unwrap_or_else(|| self.total_pixel_length());
+            Some((self.first_pixel_from_vis(first)?, end))
+        }
+    }
 }
 
 trait AxisMeasureT: Debug {
@@ -292,10 +457,18 @@ trait AxisMeasureT: Debug {
 
     fn first_pixel_from_vis(&self, idx: VisIdx) -> Option<f64>;
     fn pixels_length_for_vis(&self, idx: VisIdx) -> Option<f64>;
+    fn pixels_length_for_log(&self, idx: LogIdx) -> Option<f64>;
     fn can_resize(&self, idx: VisIdx) -> bool;
 
     fn set_axis_properties(&mut self, border: f64, len: usize, remap: &Remap) -> bool;
     fn set_far_pixel_for_vis(&mut self, idx: VisIdx, pixel: f64) -> bool;
+    fn set_pixel_length_for_vis(&mut self, idx: VisIdx, length: f64) -> bool;
+    fn set_pixel_length_for_log(&mut self, idx: LogIdx, length: f64) -> bool;
+    fn set_log_limits(&mut self, limits: Vec<(f64, f64)>) -> bool;
+    fn set_log_initial(&mut self, lengths: Vec<Option<f64>>) -> bool;
+    fn set_log_flex(&mut self, weights: Vec<f64>) -> bool;
+    fn fit_flex(&mut self, available: f64) -> bool;
+    fn fit_all(&mut self, available: f64) -> bool;
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -355,32 +528,73 @@ impl AxisMeasureT for FixedAxisMeasure {
         }
     }
 
+    fn pixels_length_for_log(&self, idx: LogIdx) -> Option<f64> {
+        if idx.0 < self.len {
+            Some(self.pixels_per_unit)
+        } else {
+            None
+        }
+    }
+
     fn can_resize(&self, _idx: VisIdx) -> bool {
         false
     }
 
-    fn set_axis_properties(&mut self, border: f64, len: usize, _remap: &Remap) -> bool {
+    fn set_axis_properties(&mut self, border: f64, len: usize, remap: &Remap) -> bool {
+        // Every item is the same, so only the number shown matters
+        let len = remap.vis_len(len);
         let changed = border != self.border || len != self.len;
 
         self.border = border;
         self.len = len;
-        // We don't care about remap as every item is the same... I think.
-        // TODO: Maybe we should care about the length?
         changed
     }
 
     fn set_far_pixel_for_vis(&mut self, _idx: VisIdx, _pixel: f64) -> bool {
         false
     }
+
+    fn set_pixel_length_for_vis(&mut self, _idx: VisIdx, _length: f64) -> bool {
+        false
+    }
+
+    fn set_pixel_length_for_log(&mut self, _idx: LogIdx, _length: f64) -> bool {
+        false
+    }
+
+    fn set_log_limits(&mut self, _limits: Vec<(f64, f64)>) -> bool {
+        false
+    }
+
+    fn set_log_initial(&mut self, _lengths: Vec<Option<f64>>) -> bool {
+        false
+    }
+
+    fn set_log_flex(&mut self, _weights: Vec<f64>) -> bool {
+        false
+    }
+
+    fn fit_flex(&mut self, _available: f64) -> bool {
+        false
+    }
+
+    fn fit_all(&mut self, _available: f64) -> bool {
+        false
+    }
 }
 
 #[derive(Clone)]
 pub struct StoredAxisMeasure {
     remap: Remap,
     log_pix_lengths: Vec<f64>,
+    log_limits: Vec<(f64, f64)>,
+    log_flex: Vec<f64>,
+    given_flex: Vec<f64>, // As last set, before items were resized out of flexing
+    given_initial: Vec<Option<f64>>,
+    resized: HashSet<LogIdx>, // Sized explicitly, so no longer at their initial length
     vis_pix_lengths: Vec<f64>,
-    first_pixels: BTreeMap<VisIdx, f64>, // TODO newtypes
-    pixels_to_vis: BTreeMap<FloatOrd<f64>, VisIdx>,
+    // Running total of lengths and borders before each visual index
+    first_pixels: Vec<f64>,
     default_pixels: f64,
     border: f64,
     total_pixel_length: f64,
@@ -389,23 +603,17 @@ pub struct StoredAxisMeasure {
 impl Debug for StoredAxisMeasure {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         let fp = &self.first_pixels;
-        let pti = &self.pixels_to_vis;
         fmt.debug_struct("StoredAxisMeasure")
             .field("log_pix_lengths", &self.log_pix_lengths)
+            .field("log_limits", &self.log_limits)
+            .field("log_flex", &self.log_flex)
             .field("vis_pix_lengths", &self.vis_pix_lengths)
             .field("default_pixels", &self.default_pixels)
             .field("border", &self.border)
             .field("total_pixel_length", &self.total_pixel_length)
             .field(
                 "first_pixels",
-                debug_fn!(|f| f.debug_map().entries(fp.iter()).finish()),
-            )
-            .field(
-                "pixels_to_index",
-                debug_fn!(|f| f
-                    .debug_map()
-                    .entries(pti.iter().map(|(k, v)| (k.0, v)))
-                    .finish()),
+                debug_fn!(|f| f.debug_map().entries(fp.iter().enumerate()).finish()),
             )
             .finish()
     }
@@ -416,15 +624,46 @@ impl StoredAxisMeasure {
         StoredAxisMeasure {
             remap: Remap::new(),
             log_pix_lengths: Default::default(),
+            log_limits: Default::default(),
+            log_flex: Default::default(),
+            given_flex: Default::default(),
+            given_initial: Default::default(),
+            resized: Default::default(),
             vis_pix_lengths: Default::default(),
             first_pixels: Default::default(),
-            pixels_to_vis: Default::default(),
             default_pixels,
             border: 0.,
             total_pixel_length: 0.,
         }
     }
 
+    fn clamp(&self, log_idx: LogIdx, length: f64) -> f64 {
+        let (min, max) = self
+            .log_limits
+            .get(log_idx.0)
+            .copied()
+            .unwrap_or((0., f64::INFINITY));
+        length.min(max).max(min).max(0.)
+    }
+
+    fn initial_length(&self, log_idx: LogIdx) -> f64 {
+        let initial = self.given_initial.get(log_idx.0).copied().flatten();
+        self.clamp(log_idx, initial.unwrap_or(self.default_pixels))
+    }
+
+    // Keeps resized items within their limits, and the rest at their initial length
+    fn apply_limits(&mut self) {
+        for idx in 0..self.log_pix_lengths.len() {
+            let log_idx = LogIdx(idx);
+            self.log_pix_lengths[idx] = if self.resized.contains(&log_idx) {
+                self.clamp(log_idx, self.log_pix_lengths[idx])
+            } else {
+                self.initial_length(log_idx)
+            };
+        }
+        self.build_maps();
+    }
+
     fn build_maps(&mut self) {
         let mut cur = 0.;
         self.vis_pix_lengths.clear();
@@ -438,15 +677,15 @@ impl StoredAxisMeasure {
             ) {
                 if let Some(log_idx) = self.remap.get_log_idx(vis_idx) {
                     self.vis_pix_lengths.push(self.log_pix_lengths[log_idx.0]);
+                } else if self.remap.group_header_at(vis_idx).is_some() {
+                    self.vis_pix_lengths.push(self.default_pixels);
                 }
             }
         }
 
         self.first_pixels.clear();
-        self.pixels_to_vis.clear();
-        for (idx, pixels) in self.vis_pix_lengths.iter().enumerate() {
-            self.first_pixels.insert(VisIdx(idx), cur);
-            self.pixels_to_vis.insert(FloatOrd(cur), VisIdx(idx));
+        for pixels in &self.vis_pix_lengths {
+            self.first_pixels.push(cur);
             cur += pixels + self.border;
         }
         self.total_pixel_length = cur;
@@ -463,20 +702,23 @@ impl AxisMeasureT for StoredAxisMeasure {
     }
 
     fn vis_idx_from_pixel(&self, pixel: f64) -> Option<VisIdx> {
-        self.pixels_to_vis
-            .range(..=FloatOrd(pixel))
-            .next_back()
-            .map(|(_, v)| *v)
+        // Binary search for the last item starting at or before the pixel
+        let starts_before = self.first_pixels.partition_point(|first| *first <= pixel);
+        starts_before.checked_sub(1).map(VisIdx)
     }
 
     fn first_pixel_from_vis(&self, idx: VisIdx) -> Option<f64> {
-        self.first_pixels.get(&idx).copied()
+        self.first_pixels.get(idx.0).copied()
     }
 
     fn pixels_length_for_vis(&self, idx: VisIdx) -> Option<f64> {
         self.vis_pix_lengths.get(idx.0).copied()
     }
 
+    fn pixels_length_for_log(&self, idx: LogIdx) -> Option<f64> {
+        self.log_pix_lengths.get(idx.0).copied()
+    }
+
     fn can_resize(&self, _idx: VisIdx) -> bool {
         true
     }
@@ -488,9 +730,14 @@ impl AxisMeasureT for StoredAxisMeasure {
         let old_len = self.log_pix_lengths.len();
 
         match old_len.cmp(&len) {
-            Ordering::Greater => self.log_pix_lengths.truncate(len),
+            Ordering::Greater => {
+                self.log_pix_lengths.truncate(len);
+                self.resized.retain(|log_idx| log_idx.0 < len);
+            }
             Ordering::Less => {
-                let extra = vec![self.default_pixels; len - old_len];
+                let extra: Vec<f64> = (old_len..len)
+                    .map(|idx| self.initial_length(LogIdx(idx)))
+                    .collect();
                 self.log_pix_lengths.extend_from_slice(&extra[..]);
                 assert_eq!(self.log_pix_lengths.len(), len);
             }
@@ -502,38 +749,158 @@ impl AxisMeasureT for StoredAxisMeasure {
     }
 
     fn set_far_pixel_for_vis(&mut self, vis_idx: VisIdx, pixel: f64) -> bool {
-        let length = f64::max(0., pixel - *self.first_pixels.get(&vis_idx).unwrap_or(&0.));
+        let length = pixel - *self.first_pixels.get(vis_idx.0).unwrap_or(&0.);
         // Todo Option
+        self.set_pixel_length_for_vis(vis_idx, length)
+    }
+
+    fn set_pixel_length_for_vis(&mut self, vis_idx: VisIdx, length: f64) -> bool {
         if let Some(log_idx) = self.remap.get_log_idx(vis_idx) {
-            if let Some(place) = self.log_pix_lengths.get_mut(log_idx.0) {
-                if *place != length {
-                    *place = length;
-                    self.build_maps(); // TODO : modify efficiently instead of rebuilding
-                    return true;
-                }
+            self.set_pixel_length_for_log(log_idx, length)
+        } else {
+            false
+        }
+    }
+
+    fn set_pixel_length_for_log(&mut self, log_idx: LogIdx, length: f64) -> bool {
+        let length = self.clamp(log_idx, length);
+        // Once sized explicitly, an item stops flexing
+        if let Some(weight) = self.log_flex.get_mut(log_idx.0) {
+            *weight = 0.;
+        }
+        self.resized.insert(log_idx);
+        if let Some(place) = self.log_pix_lengths.get_mut(log_idx.0) {
+            if *place != length {
+                *place = length;
+                self.build_maps(); // TODO : modify efficiently instead of rebuilding
+                return true;
             }
         }
         false
     }
+
+    fn set_log_limits(&mut self, limits: Vec<(f64, f64)>) -> bool {
+        if limits == self.log_limits {
+            return false;
+        }
+        self.log_limits = limits;
+        self.apply_limits();
+        true
+    }
+
+    fn set_log_initial(&mut self, lengths: Vec<Option<f64>>) -> bool {
+        if lengths == self.given_initial {
+            return false;
+        }
+        self.given_initial = lengths;
+        self.apply_limits();
+        true
+    }
+
+    fn set_log_flex(&mut self, weights: Vec<f64>) -> bool {
+        if weights == self.given_flex {
+            return false;
+        }
+        // Items resized since keep their length, unless they are given a new weight
+        let flex = weights.iter().enumerate().map(|(idx, weight)| {
+            match (self.given_flex.get(idx), self.log_flex.get(idx)) {
+                (Some(given), Some(current)) if given == weight => *current,
+                _ => *weight,
+            }
+        });
+        self.log_flex = flex.collect();
+        self.given_flex = weights;
+        true
+    }
+
+    fn fit_flex(&mut self, available: f64) -> bool {
+        let weights = self.log_flex.clone();
+        self.share_out(available, &weights)
+    }
+
+    fn fit_all(&mut self, available: f64) -> bool {
+        // Already filling it, give or take rounding, so repeated layouts don't creep
+        if (self.total_pixel_length - available).abs() < 0.5 {
+            return false;
+        }
+        let weights = self.log_pix_lengths.clone();
+        self.share_out(available, &weights)
+    }
 }
 
-#[cfg(not)]
+impl StoredAxisMeasure {
+    // Shares out whatever the items without a weight leave of `available` among those with
+    // one, in proportion to it. Hidden items take no part.
+    fn share_out(&mut self, available: f64, weights: &[f64]) -> bool {
+        let remap = &self.remap;
+        let shown: Vec<usize> = (0..self.log_pix_lengths.len())
+            .filter(|idx| remap.get_vis_idx(LogIdx(*idx)).is_some())
+            .collect();
+        let weight = |idx: usize| weights.get(idx).copied().unwrap_or(0.).max(0.);
+        let mut flexing: Vec<usize> = shown.iter().copied().filter(|i| weight(*i) > 0.).collect();
+        if flexing.is_empty() {
+            return false;
+        }
+
+        let fixed: f64 = shown
+            .iter()
+            .filter(|idx| weight(**idx) <= 0.)
+            .map(|idx| self.log_pix_lengths[*idx])
+            .sum();
+        let mut remaining = available - fixed - self.border * shown.len() as f64;
+        let mut lengths = self.log_pix_lengths.clone();
+
+        // Items pushed out of their limits take the limit and drop out, then the rest is
+        // shared again. If even the minimums don't fit, everything ends up at its minimum
+        // and the table scrolls.
+        loop {
+            let total_weight: f64 = flexing.iter().map(|idx| weight(*idx)).sum();
+            let share = remaining.max(0.) / total_weight;
+            let before = flexing.len();
+            flexing.retain(|idx| {
+                let wanted = share * weight(*idx);
+                let clamped = self.clamp(LogIdx(*idx), wanted);
+                lengths[*idx] = clamped;
+                if clamped != wanted {
+                    remaining -= clamped;
+                    false
+                } else {
+                    true
+                }
+            });
+            if flexing.is_empty() || flexing.len() == before {
+                break;
+            }
+        }
+
+        if lengths == self.log_pix_lengths {
+            false
+        } else {
+            self.log_pix_lengths = lengths;
+            self.build_maps();
+            true
+        }
+    }
+}
+
+#[cfg(test)]
 mod test {
-    use crate::axis_measure::{AxisMeasureT, VisIdx};
-    use crate::{FixedAxisMeasure, Remap, StoredAxisMeasure};
+    use crate::axis_measure::{AxisMeasure, AxisMeasureT, LogIdx, VisIdx};
+    use crate::{AxisMeasurementType, Remap, StoredAxisMeasure};
     use float_ord::FloatOrd;
     use std::collections::HashSet;
-    use std::fmt::Debug;
 
     #[test]
     fn fixed_axis() {
-        let mut ax = FixedAxisMeasure::new(99.0);
+        let mut ax = AxisMeasure::new(AxisMeasurementType::Uniform, 99.0);
 
         test_equal_sized(&mut ax);
-        assert_eq!(ax.set_far_pixel_for_vis(VisIdx(12), 34.), 99.);
+        assert!(!ax.can_resize(VisIdx(2)));
+        ax.set_far_pixel_for_vis(VisIdx(2), 34.);
+        assert_eq!(ax.pixels_length_for_vis(VisIdx(2)), Some(99.));
     }
 
-    fn test_equal_sized<AX: AxisMeasureT + Debug>(ax: &mut AX) {
+    fn test_equal_sized(ax: &mut AxisMeasure) {
         ax.set_axis_properties(1.0, 4, &Remap::Pristine);
         println!("Axis:{:#?}", ax);
         assert_eq!(ax.total_pixel_length(), 400.);
@@ -571,11 +938,88 @@ mod test {
 
     #[test]
     fn stored_axis() {
-        let mut ax = StoredAxisMeasure::new(99.);
+        let mut ax = AxisMeasure::new(AxisMeasurementType::Individual, 99.);
         test_equal_sized(&mut ax);
 
-        assert_eq!(ax.set_pixel_length_for_vis(VisIdx(2), 49.), 49.);
-        assert_eq!(ax.set_far_pixel_for_vis(VisIdx(1), 109.), 9.);
+        ax.set_pixel_length_for_vis(VisIdx(2), 49.);
+        assert_eq!(ax.pixels_length_for_vis(VisIdx(2)), Some(49.));
+        ax.set_far_pixel_for_vis(VisIdx(1), 109.);
+        assert_eq!(ax.pixels_length_for_vis(VisIdx(1)), Some(9.));
         assert_eq!(ax.total_pixel_length(), 260.0)
     }
+
+    #[test]
+    fn reversed_axis_hugs_the_far_end() {
+        let mut ax = AxisMeasure::new(AxisMeasurementType::Individual, 100.);
+        ax.set_axis_properties(0., 3, &Remap::Pristine);
+        ax.set_reversed(true);
+        ax.fit_flex(400.);
+
+        assert_eq!(ax.total_pixel_length(), 400.);
+        assert_eq!(ax.first_pixel_from_vis(VisIdx(0)), Some(300.));
+        assert_eq!(ax.first_pixel_from_vis(VisIdx(2)), Some(100.));
+        assert_eq!(ax.vis_idx_from_pixel(350.), Some(VisIdx(0)));
+        assert_eq!(ax.vis_idx_from_pixel(150.), Some(VisIdx(2)));
+        assert_eq!(ax.vis_range_from_pixels(0., 250.), (VisIdx(1), VisIdx(2)));
+        assert_eq!(ax.pixel_range(VisIdx(0), VisIdx(1)), Some((200., 400.)));
+    }
+
+    #[test]
+    fn stored_lookup_over_many_rows() {
+        let rows = 100_000;
+        let mut ax = StoredAxisMeasure::new(20.);
+        ax.set_axis_properties(1., rows, &Remap::Pristine);
+        ax.set_pixel_length_for_log(LogIdx(10), 79.);
+
+        assert_eq!(ax.vis_idx_from_pixel(-1.), None);
+        assert_eq!(ax.vis_idx_from_pixel(0.), Some(VisIdx(0)));
+        assert_eq!(ax.vis_idx_from_pixel(20.5), Some(VisIdx(0)));
+        assert_eq!(ax.vis_idx_from_pixel(210.), Some(VisIdx(10)));
+        assert_eq!(ax.vis_idx_from_pixel(289.), Some(VisIdx(10)));
+        assert_eq!(ax.vis_idx_from_pixel(290.), Some(VisIdx(11)));
+        assert_eq!(ax.total_pixel_length(), 21. * rows as f64 + 59.);
+        assert_eq!(
+            ax.vis_idx_from_pixel(ax.total_pixel_length() + 100.),
+            Some(VisIdx(rows - 1))
+        );
+
+        for idx in (0..rows).step_by(997) {
+            let first = ax.first_pixel_from_vis(VisIdx(idx)).unwrap();
+            assert_eq!(ax.vis_idx_from_pixel(first), Some(VisIdx(idx)));
+            assert_eq!(ax.vis_idx_from_pixel(first + 10.), Some(VisIdx(idx)));
+        }
+    }
+
+    #[test]
+    fn resized_items_stop_flexing_for_good() {
+        let mut ax = StoredAxisMeasure::new(50.);
+        ax.set_axis_properties(0., 2, &Remap::Pristine);
+        ax.set_log_flex(vec![1., 1.]);
+        ax.set_pixel_length_for_log(LogIdx(0), 80.);
+        // The same weights again, as on every remap of the columns
+        assert!(!ax.set_log_flex(vec![1., 1.]));
+        ax.fit_flex(300.);
+        assert_eq!(ax.pixels_length_for_log(LogIdx(0)), Some(80.));
+        assert_eq!(ax.pixels_length_for_log(LogIdx(1)), Some(220.));
+
+        assert!(ax.set_log_flex(vec![2., 1.]));
+        ax.fit_flex(300.);
+        assert_eq!(ax.pixels_length_for_log(LogIdx(0)), Some(200.));
+    }
+
+    #[test]
+    fn fitting_keeps_proportions_until_minimums_overflow() {
+        let mut ax = StoredAxisMeasure::new(50.);
+        ax.set_axis_properties(0., 3, &Remap::Pristine);
+        ax.set_pixel_length_for_log(LogIdx(0), 100.);
+        ax.fit_all(400.);
+        assert_eq!(ax.pixels_length_for_log(LogIdx(0)), Some(200.));
+        assert_eq!(ax.pixels_length_for_log(LogIdx(2)), Some(100.));
+        assert_eq!(ax.total_pixel_length(), 400.);
+
+        // Too narrow for them all, so they keep to their minimums and scroll
+        ax.set_log_limits(vec![(150., f64::INFINITY); 3]);
+        ax.fit_all(300.);
+        assert_eq!(ax.total_pixel_length(), 450.);
+    }
 }
@@ -2,14 +2,17 @@ use crate::columns::{
     CellDelegate, CellRenderExt, HeaderCell, ProvidedColumns, TableColumn, TextCell,
 };
 
-use crate::axis_measure::{AxisMeasure, AxisPair, LogIdx, TableAxis};
-use crate::config::TableConfig;
+use crate::axis_measure::{AxisMeasure, AxisPair, LogIdx, TableAxis, VisIdx};
+use crate::config::{Gridlines, TableConfig};
 use crate::data::{IndexedData, IndexedItems};
 use crate::headings::{HeadersFromIndices, SuppliedHeaders};
 use crate::table::TableArgs;
-use crate::{CellRender, HeaderBuild};
-use druid::{theme, Data, KeyOrValue};
+use crate::{CellRender, HeaderBuild, SelectionMode, TableSelection};
+use druid::{theme, Color, Data, KeyOrValue, Menu, Selector};
+use std::any::Any;
 use std::marker::PhantomData;
+use std::rc::Rc;
+use std::time::Duration;
 
 #[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
 #[derive(Default)]
@@ -91,6 +94,194 @@ impl<RowData: Data, TableData: IndexedData<Item = RowData, Idx = LogIdx>>
         self
     }
 
+    // Space kept clear between a cell's border and its content, on every side
+    pub fn cell_padding(mut self, padding: impl Into<KeyOrValue<f64>>) -> Self {
+        self.table_config.cell_padding = padding.into();
+        self
+    }
+
+    pub fn gridlines(mut self, gridlines: Gridlines) -> Self {
+        self.table_config.gridlines = gridlines;
+        self
+    }
+
+    pub fn selection_color(mut self, color: impl Into<KeyOrValue<Color>>) -> Self {
+        self.table_config.selection_color = color.into();
+        self
+    }
+
+    // From 0 for an outline alone to 1 to hide the selected cells
+    pub fn selection_opacity(mut self, opacity: impl Into<KeyOrValue<f64>>) -> Self {
+        self.table_config.selection_opacity = opacity.into();
+        self
+    }
+
+    pub fn slice_selection_color(mut self, color: impl Into<KeyOrValue<Color>>) -> Self {
+        self.table_config.slice_selection_color = color.into();
+        self
+    }
+
+    pub fn focus_color(mut self, color: impl Into<KeyOrValue<Color>>) -> Self {
+        self.table_config.focus_color = color.into();
+        self
+    }
+
+    pub fn focus_border_width(mut self, width: impl Into<KeyOrValue<f64>>) -> Self {
+        self.table_config.focus_border_width = Some(width.into());
+        self
+    }
+
+    pub fn sort_indicator_color(mut self, color: impl Into<KeyOrValue<Color>>) -> Self {
+        self.table_config.sort_indicator_color = color.into();
+        self
+    }
+
+    pub fn search_highlight(mut self, color: impl Into<KeyOrValue<Color>>) -> Self {
+        self.table_config.search_highlight = color.into();
+        self
+    }
+
+    pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.table_config.selection_mode = mode;
+        self
+    }
+
+    // Off makes the focus and fitted rows jump straight to where they end up
+    pub fn animate(mut self, animate: bool) -> Self {
+        self.table_config.animate = animate;
+        self
+    }
+
+    // How long to rest on a heading or clipped cell before its tooltip shows
+    pub fn tooltip_delay(mut self, delay: Duration) -> Self {
+        self.table_config.tooltip_delay = delay;
+        self
+    }
+
+    // Only rows that are measured individually can be resized, see AxisMeasurementType
+    pub fn min_row_height(mut self, height: impl Into<KeyOrValue<f64>>) -> Self {
+        self.table_config.min_row_height = height.into();
+        self
+    }
+
+    // Frozen columns are ignored right to left, see TableConfig::right_to_left
+    pub fn right_to_left(mut self, right_to_left: bool) -> Self {
+        self.table_config.right_to_left = right_to_left;
+        self
+    }
+
+    pub fn cache_text_layouts(mut self, cache: bool) -> Self {
+        self.table_config.cache_text_layouts = cache;
+        self
+    }
+
+    // Scale the columns to fill the table's width, keeping their proportions as it is
+    // resized, instead of scrolling. Needs individually measured columns.
+    pub fn fit_width(mut self, fit: bool) -> Self {
+        self.table_config.fit_width = fit;
+        self
+    }
+
+    // Not kept in view right to left yet, see TableConfig::frozen_columns
+    pub fn frozen_columns(mut self, count: usize) -> Self {
+        self.table_config.frozen_columns = count;
+        self
+    }
+
+    pub fn frozen_rows(mut self, count: usize) -> Self {
+        self.table_config.frozen_rows = count;
+        self
+    }
+
+    // Put a box in the corner between the headings that selects every row, or clears the
+    // selection once they all are. Best with SelectionMode::Row.
+    pub fn select_all_box(mut self, shown: bool) -> Self {
+        self.table_config.select_all_box = shown;
+        self
+    }
+
+    // Show row numbers, counted from 1 in the order shown, in the row headings. Clicking one
+    // selects its row.
+    pub fn row_numbers(mut self, shown: bool) -> Self {
+        self.table_config.row_numbers = shown;
+        self
+    }
+
+    // Jump to rows by typing the start of their value in the sorted column, or the first
+    // column shown when nothing is sorted. Typing the same letter again goes on to the next
+    // row starting with it.
+    pub fn type_ahead(mut self, on: bool) -> Self {
+        self.table_config.type_ahead = on;
+        self
+    }
+
+    // Keep the last row in view as rows are appended, eg for a log, while the view is at the
+    // bottom
+    pub fn stick_to_bottom(mut self, on: bool) -> Self {
+        self.table_config.stick_to_bottom = on;
+        self
+    }
+
+    // Copy the column headings as the first line when whole columns are copied
+    pub fn copy_headers(mut self, on: bool) -> Self {
+        self.table_config.copy_headers = on;
+        self
+    }
+
+    // The selector is submitted with the new selection each time it changes
+    pub fn on_selection(mut self, selector: Selector<TableSelection>) -> Self {
+        self.table_config.on_selection = Some(selector);
+        self
+    }
+
+    // The selector is submitted with the first and last rows on screen as scrolling or
+    // resizing changes them, at most every tenth of a second
+    pub fn on_visible_rows(mut self, selector: Selector<(VisIdx, VisIdx)>) -> Self {
+        self.table_config.on_visible_rows = Some(selector);
+        self
+    }
+
+    // The selector is submitted with the logical rows on screen that the data returns None for,
+    // eg to load a window of a large remote source as it scrolls into view
+    pub fn on_missing_rows(mut self, selector: Selector<Vec<LogIdx>>) -> Self {
+        self.table_config.on_missing_rows = Some(selector);
+        self
+    }
+
+    // Shown instead of the rows when there are none to show
+    pub fn empty_message(mut self, message: impl Into<String>) -> Self {
+        self.table_config.empty_message = Some(message.into());
+        self
+    }
+
+    // Right clicking a cell selects it, then shows the menu built for it. The menu's data is
+    // the application's root data, as for any other druid menu.
+    pub fn context_menu<AppData: Any>(
+        mut self,
+        make_menu: impl Fn(&AxisPair<LogIdx>) -> Menu<AppData> + 'static,
+    ) -> Self {
+        self.table_config.context_menu = Some(Rc::new(move |ctx, cell, pos| {
+            ctx.show_context_menu(make_menu(cell), pos)
+        }));
+        self
+    }
+
+    // Wrap long column headers onto more lines, growing the headings to fit the tallest
+    pub fn wrap_headers(mut self, wrap: bool) -> Self {
+        self.table_config.wrap_headers = wrap;
+        self.column_header_delegate = Box::new(HeaderCell::new(
+            TextCell::new().text_color(theme::TEXT_COLOR).wrap(wrap),
+        ));
+        self
+    }
+
+    // Let the user rename columns by double clicking their headings, eg for a spreadsheet
+    // whose headers are labels. Enter or clicking away keeps the name, Escape drops it.
+    pub fn editable_headers(mut self, editable: bool) -> Self {
+        self.table_config.editable_headers = editable;
+        self
+    }
+
     pub fn headings(mut self, show_headings: ShowHeadings) -> Self {
         self.show_headings = show_headings;
         self
@@ -135,12 +326,23 @@ impl<RowData: Data, TableData: IndexedData<Item = RowData, Idx = LogIdx>>
         )
     }
 
-    pub fn build_args(self) -> DefaultTableArgs<TableData> {
+    pub fn build_args(mut self) -> DefaultTableArgs<TableData> {
         let column_headers: Vec<String> = self
             .table_columns
             .iter()
             .map(|tc| tc.header.clone())
             .collect();
+        self.table_config.column_groups = self
+            .table_columns
+            .iter()
+            .map(|tc| tc.group.clone())
+            .collect();
+        self.table_config.header_tooltips = self
+            .table_columns
+            .iter()
+            .map(|tc| tc.header_tooltip.clone())
+            .collect();
+        self.table_config.fixed_sorts = self.table_columns.iter().map(|tc| tc.sort_fixed).collect();
 
         let row_build = if_opt!(
             self.show_headings.should_show(&TableAxis::Rows),
@@ -152,7 +354,7 @@ impl<RowData: Data, TableData: IndexedData<Item = RowData, Idx = LogIdx>>
         let col_build = if_opt!(
             self.show_headings.should_show(&TableAxis::Columns),
             HeaderBuild::new(
-                SuppliedHeaders::new(column_headers),
+                SuppliedHeaders::new(column_headers).renamable(),
                 self.column_header_delegate,
             )
         );
@@ -1,19 +1,30 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::time::{Duration, Instant};
 
+use druid::commands::CLOSE_WINDOW;
+use druid::im::Vector;
+use druid::kurbo::{Line, Vec2};
+use druid::piet::{FontFamily, PietText, Text, TextLayout, TextLayoutBuilder};
 use druid::widget::prelude::*;
 use druid::{
-    Affine, BoxConstraints, Command, Data, Env, Event, EventCtx, KbKey, LayoutCtx, LifeCycle,
-    LifeCycleCtx, PaintCtx, Point, Rect, Selector, Size, UpdateCtx, Widget, WidgetPod,
+    theme, Affine, Application, BoxConstraints, Command, Data, Env, Event, EventCtx, Insets, KbKey,
+    KeyEvent, LayoutCtx, LifeCycle, LifeCycleCtx, MouseEvent, PaintCtx, Point, Rect, Selector,
+    Size, TimerToken, UpdateCtx, Widget, WidgetPod,
 };
 
 use crate::axis_measure::{AxisMeasure, AxisPair, LogIdx, TableAxis, VisIdx, VisOffset};
 use crate::cells::Editing::Inactive;
-use crate::columns::{CellCtx, CellRender};
+use crate::columns::{CellCtx, CellRender, CACHE_TEXT_LAYOUTS, LOADING_PHASE, RIGHT_TO_LEFT};
 use crate::config::{ResolvedTableConfig, TableConfig};
-use crate::data::{IndexedData, Remapper};
+use crate::data::{FilterSpec, IndexedData, Remapper, SearchSpec};
+use crate::interp::HasInterp;
 use crate::render_ext::RenderContextExt;
-use crate::selection::{CellRect, SingleCell, TableSelection};
-use crate::table::TableState;
+use crate::selection::{CellDemap, CellRect, SingleCell, TableSelection};
+use crate::table::{TableGeometry, TableLayout, TableState};
+use crate::tooltip::{show_tooltip, Tooltip};
 use crate::{EditorFactory, IndexedItems, Remap};
 use druid_bindings::{BindableAccess, bindable_self_body};
 
@@ -23,13 +34,102 @@ where
     TableData::Item: Data,
 {
     fn number_of_columns_in_data(&self, data: &TableData) -> usize;
+
+    // (min, max) widths by logical column, empty if unconstrained
+    fn column_width_limits(&self, _env: &Env) -> Vec<(f64, f64)> {
+        Vec::new()
+    }
+
+    // Widths by logical column that columns start at until resized, empty for the default
+    fn column_initial_widths(&self, _env: &Env) -> Vec<Option<f64>> {
+        Vec::new()
+    }
+
+    // Flex weights by logical column, see TableColumn::flex
+    fn column_flex_weights(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    // How many visible columns the cell starting at this logical column covers, clamped to
+    // the last column. The cells it covers are not drawn, and clicking them selects it.
+    fn col_span(&self, _row: &TableData::Item, _col: LogIdx) -> usize {
+        1
+    }
+
+    // Whether any cell might span columns, so delegates overriding col_span should say so.
+    // When none do, painting and clicking only look at the columns in view, rather than every
+    // column up to them.
+    fn spans_columns(&self) -> bool {
+        false
+    }
+
+    // Whether runs of equal cells down a logical column show as one, see
+    // TableColumn::merge_equal
+    fn merges_equal(&self, _col: LogIdx) -> bool {
+        false
+    }
+
+    // Submitted with the row when a cell of a logical column is clicked, see
+    // TableColumn::on_click
+    fn on_click(&self, _col: LogIdx) -> Option<Selector<LogIdx>> {
+        None
+    }
+
+    // Whether two rows have equal values in a logical column, as it compares them to sort
+    fn equal_in_column(&self, _col: LogIdx, _a: &TableData::Item, _b: &TableData::Item) -> bool {
+        false
+    }
+
+    // Space around the content of a logical column's cells, None for the table's cell padding
+    fn column_padding(&self, _col: LogIdx) -> Option<Insets> {
+        None
+    }
+
+    // Whether dragging the fill handle can copy a logical column's value between rows
+    fn can_fill(&self, _col: LogIdx) -> bool {
+        false
+    }
+
+    // Copies a logical column's value from one row to another, see TableColumn::fillable
+    fn fill(&self, _col: LogIdx, _from: &TableData::Item, _to: &mut TableData::Item) {}
+
+    // The heading text for a logical column, used as the first line of a CSV export
+    fn column_header(&self, _col: LogIdx) -> Option<String> {
+        None
+    }
+
+    // Gives a logical column a new heading, as the user renamed it
+    fn rename_column(&mut self, _col: LogIdx, _header: String) {}
+
+    // Whether the table has a footer row, see footer_value
+    fn has_footer(&self) -> bool {
+        false
+    }
+
+    // The footer text for a logical column, given the rows shown. None leaves it blank.
+    fn footer_value(
+        &self,
+        _col: LogIdx,
+        _data: &TableData,
+        _rows: &Remap,
+        _env: &Env,
+    ) -> Option<String> {
+        None
+    }
 }
 
+// Editors closed since, by logical column, so the next edit in a column reuses the widget
+// rather than making another
+type SpareEditors<RowData> = HashMap<LogIdx, WidgetPod<RowData, Box<dyn Widget<RowData>>>>;
+
+// Editors write through to their row as they go. An open editor stays with its cell when the
+// cell scrolls out of view, still taking keys, and is there again on scrolling back.
 enum Editing<RowData> {
     Inactive,
     Cell {
         single_cell: SingleCell,
         child: WidgetPod<RowData, Box<dyn Widget<RowData>>>,
+        original: RowData, // Put back if the edit is cancelled
     },
 }
 
@@ -38,6 +138,13 @@ impl<RowData: Data> Editing<RowData> {
         !matches!(self, Inactive)
     }
 
+    fn cell(&self) -> Option<&SingleCell> {
+        match self {
+            Editing::Cell { single_cell, .. } => Some(single_cell),
+            _ => None,
+        }
+    }
+
     fn is_editing(&self, cell: &SingleCell) -> bool {
         match self {
             Editing::Cell { single_cell, .. } => single_cell.vis.eq(&cell.vis),
@@ -55,27 +162,42 @@ impl<RowData: Data> Editing<RowData> {
         if let Editing::Cell {
                 ref single_cell,
                 ref mut child,
+                ..
         } = self {
             data.with_mut(single_cell.log.row, |row| child.event(ctx, event, row, env));
         }
     }
 
-    fn start_editing<TableData: IndexedItems<Item = RowData>>(
+    fn start_editing<TableData: IndexedItems<Idx = LogIdx, Item = RowData>>(
         &mut self,
         ctx: &mut EventCtx,
         data: &mut TableData,
         cell: &SingleCell,
-        make_editor: impl FnMut(&CellCtx) -> Option<Box<dyn Widget<RowData>>>,
+        size: Size,
+        spares: &mut SpareEditors<RowData>,
+        editors: &mut impl EditorFactory<RowData>,
     ) {
-        self.stop_editing(data);
-        let mut me = make_editor;
-        let cell_ctx = CellCtx::Cell(cell);
-        if let Some(editor) = me(&cell_ctx) {
-            let pod = WidgetPod::new(editor);
-
+        self.stop_editing(data, spares);
+        let cell_ctx = CellCtx::Cell(cell, size, Default::default());
+        // Cells of a column may not all be editable, so only one that is gets the spare
+        if !editors.can_edit(&cell_ctx) {
+            return;
+        }
+        let original = data.with(cell.log.row, |row| row.clone());
+        let made = || editors.make_editor(&cell_ctx);
+        let pod = match Self::take_editor(spares, cell.log.col, made) {
+            Some((pod, true)) => {
+                // Brought up to date with its new row in update
+                ctx.request_update();
+                Some(pod)
+            }
+            made => made.map(|(pod, _)| pod),
+        };
+        if let (Some(pod), Some(original)) = (pod, original) {
             *self = Editing::Cell {
                 single_cell: cell.clone(),
                 child: pod,
+                original,
             };
 
             ctx.children_changed();
@@ -84,19 +206,139 @@ impl<RowData: Data> Editing<RowData> {
         }
     }
 
-    fn stop_editing<TableData: IndexedItems<Item = RowData>>(&mut self, _data: &mut TableData) {
-        match self {
-            Editing::Cell { .. } => {
-                // Work out what to do with the previous pod if there is one.
+    // The column's spare editor if there is one, and whether it was. An editor is only made
+    // when there isn't.
+    fn take_editor(
+        spares: &mut SpareEditors<RowData>,
+        col: LogIdx,
+        make: impl FnOnce() -> Option<Box<dyn Widget<RowData>>>,
+    ) -> Option<(WidgetPod<RowData, Box<dyn Widget<RowData>>>, bool)> {
+        match spares.remove(&col) {
+            Some(spare) => Some((spare, true)),
+            None => make().map(|made| (WidgetPod::new(made), false)),
+        }
+    }
+
+    fn stop_editing<TableData: IndexedItems<Item = RowData>>(
+        &mut self,
+        _data: &mut TableData,
+        spares: &mut SpareEditors<RowData>,
+    ) {
+        match std::mem::replace(self, Editing::Inactive) {
+            Editing::Cell {
+                single_cell, child, ..
+            } => {
                 // We could have lazy editors (that don't write back to data immediately) and send them a special command saying 'you are being shut down'.
                 // Would need to give them data for their row
+                spares.insert(single_cell.log.col, child);
             }
             Editing::Inactive => {}
         }
-        *self = Editing::Inactive
+    }
+
+    // Editors write through to the row as they go, so this puts back the row as it was
+    fn cancel_editing<TableData: IndexedItems<Idx = LogIdx, Item = RowData>>(
+        &mut self,
+        data: &mut TableData,
+        spares: &mut SpareEditors<RowData>,
+    ) {
+        if let Editing::Cell {
+            single_cell,
+            original,
+            ..
+        } = self
+        {
+            data.with_mut(single_cell.log.row, |row| *row = original.clone());
+        }
+        self.stop_editing(data, spares)
+    }
+}
+
+// How long typing can pause before type ahead starts on a new prefix
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+// What has been typed to jump to a row, forgotten after a pause
+#[derive(Default)]
+struct TypeAhead {
+    typed: String,
+    last_key: Option<Instant>,
+}
+
+impl TypeAhead {
+    // The lowercase prefix to look for, and whether to look past the focused row. Typing on
+    // extends the prefix, which the focused row may still match, while the same letter again
+    // moves on to the next row that starts with it.
+    fn push(&mut self, typed: &str, now: Instant) -> (String, bool) {
+        let paused = self
+            .last_key
+            .map_or(true, |last| now.duration_since(last) > TYPE_AHEAD_TIMEOUT);
+        if paused {
+            self.typed.clear();
+        }
+        self.last_key = Some(now);
+        self.typed.push_str(&typed.to_lowercase());
+        let mut chars = self.typed.chars();
+        let first = chars.next();
+        if chars.all(|c| Some(c) == first) {
+            (first.map(String::from).unwrap_or_default(), true)
+        } else {
+            (self.typed.clone(), false)
+        }
     }
 }
 
+// Where each run of merged rows starts, by merging column. Only good for the data and row
+// remap they were worked out from.
+struct MergedRuns<TableData> {
+    data: TableData,
+    rows: Remap,
+    starts: HashMap<LogIdx, Vec<usize>>,
+}
+
+// F2, or typing into a cell, starts editing it
+fn opens_editor(ke: &KeyEvent) -> bool {
+    match &ke.key {
+        KbKey::F2 => true,
+        KbKey::Character(s) => s != " " && !ke.mods.ctrl() && !ke.mods.meta(),
+        _ => false,
+    }
+}
+
+// Quotes a field holding the delimiter, a quote or a line break, as RFC 4180 has it. Copied
+// cells go the same way with tabs, which is what spreadsheets paste.
+fn csv_field(text: &str, delimiter: char) -> Cow<str> {
+    if text.contains(|c| c == delimiter || matches!(c, '"' | '\n' | '\r')) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+fn csv_line(fields: &[String], delimiter: char) -> String {
+    let fields: Vec<Cow<str>> = fields
+        .iter()
+        .map(|field| csv_field(field, delimiter))
+        .collect();
+    fields.join(&delimiter.to_string())
+}
+
+// The headings of the shown columns from `first` to `last`, in the order they are shown
+fn copied_headers<TableData, ColDel>(
+    delegate: &ColDel,
+    cols: &Remap,
+    (first, last): (VisIdx, VisIdx),
+) -> Vec<String>
+where
+    TableData: IndexedData,
+    TableData::Item: Data,
+    ColDel: CellsDelegate<TableData>,
+{
+    VisIdx::range_inc_iter(first, last)
+        .filter_map(|vis| cols.get_log_idx(vis))
+        .map(|log| delegate.column_header(log).unwrap_or_default())
+        .collect()
+}
+
 pub struct Cells<TableData, CellDel>
 where
     TableData: IndexedData<Idx = LogIdx>,
@@ -107,10 +349,97 @@ where
     resolved_config: Option<ResolvedTableConfig>,
     cell_delegate: CellDel,
     editing: Editing<TableData::Item>,
+    spares: SpareEditors<TableData::Item>,
     dragging_selection: bool,
+    filling: Option<(CellRect, VisIdx)>, // Dragging the fill handle from a block to a row
+    clicking: Option<SingleCell>,        // Pressed on an on_click cell, fires on release
+    measured_heights: RefCell<HashMap<LogIdx, f64>>, // Row heights set by measure_rows
+    merged_runs: RefCell<Option<MergedRuns<TableData>>>,
+    viewport: Size, // What the scroll shows of the cells, for paging and scrolling into view
+    animation: CellsAnimation,
+    tooltip: Tooltip<SingleCell>,
+    search: Option<SearchSpec>,
+    visible_rows: Option<(VisIdx, VisIdx)>, // As last submitted to on_visible_rows
+    visible_rows_timer: Option<TimerToken>,
+    type_ahead: TypeAhead,
+    loading_phase: f64, // How far loading spinners have turned
+    spinning: bool,     // Anim frames are requested for loading cells
     phantom_td: PhantomData<TableData>,
 }
 
+// Something moving from one value to another over the configured animation duration
+struct Transition<T> {
+    from: T,
+    to: T,
+    elapsed: f64, // Seconds
+}
+
+impl<T: HasInterp> Transition<T> {
+    fn new(from: T, to: T) -> Self {
+        Transition {
+            from,
+            to,
+            elapsed: 0.,
+        }
+    }
+
+    fn current(&self, duration: f64) -> T {
+        let frac = if duration > 0. {
+            (self.elapsed / duration).min(1.)
+        } else {
+            1.
+        };
+        let (from, to) = (self.from.clone(), self.to.clone());
+        from.tween_now(to, frac).unwrap_or_else(|_| self.to.clone())
+    }
+}
+
+// The focus highlight sliding between cells, and rows resizing to fit, see TableConfig::animate
+#[derive(Default)]
+struct CellsAnimation {
+    focus: Option<Transition<Rect>>,
+    rows: Vec<(VisIdx, Transition<f64>)>,
+}
+
+impl CellsAnimation {
+    // Starts from wherever the focus is drawn now, so moving quickly doesn't build up a queue
+    fn slide_focus(&mut self, from: Rect, to: Rect, duration: f64) {
+        let from = self.focus_rect(duration).unwrap_or(from);
+        self.focus = Some(Transition::new(from, to));
+    }
+
+    fn focus_rect(&self, duration: f64) -> Option<Rect> {
+        self.focus.as_ref().map(|focus| focus.current(duration))
+    }
+
+    fn resize_rows(&mut self, resized: Vec<(VisIdx, f64, f64)>) {
+        for (vis_row, from, to) in resized {
+            self.rows.retain(|(row, _)| *row != vis_row);
+            self.rows.push((vis_row, Transition::new(from, to)));
+        }
+    }
+
+    // Moves everything on by `step` seconds, and says if there is more to do
+    fn advance(&mut self, step: f64, duration: f64, row_measure: &mut AxisMeasure) -> bool {
+        if let Some(focus) = &mut self.focus {
+            focus.elapsed += step;
+            if focus.elapsed >= duration {
+                self.focus = None;
+            }
+        }
+        for (vis_row, row) in &mut self.rows {
+            row.elapsed += step;
+            row_measure.set_pixel_length_for_vis(*vis_row, row.current(duration));
+        }
+        self.rows.retain(|(_, row)| row.elapsed < duration);
+        self.is_running()
+    }
+
+    fn is_running(&self) -> bool {
+        self.focus.is_some() || !self.rows.is_empty()
+    }
+}
+
 impl<TableData, CellDel> Cells<TableData, CellDel>
 where
     TableData: IndexedData<Idx = LogIdx>,
@@ -123,16 +452,303 @@ where
             resolved_config: None,
             cell_delegate: cells_delegate,
             editing: Inactive,
+            spares: HashMap::new(),
             dragging_selection: false,
+            filling: None,
+            clicking: None,
+            measured_heights: Default::default(),
+            merged_runs: RefCell::new(None),
+            viewport: Size::ZERO,
+            animation: CellsAnimation::default(),
+            tooltip: Tooltip::Idle,
+            search: None,
+            visible_rows: None,
+            visible_rows_timer: None,
+            type_ahead: TypeAhead::default(),
+            loading_phase: 0.,
+            spinning: false,
             phantom_td: PhantomData,
         }
     }
 
+    // Visible rows and columns
+    fn vis_lens(&self, data: &TableState<TableData>) -> AxisPair<usize> {
+        AxisPair::new(
+            data.remaps[TableAxis::Rows].vis_len(data.data.idx_len()),
+            data.remaps[TableAxis::Columns]
+                .vis_len(self.cell_delegate.number_of_columns_in_data(&data.data)),
+        )
+    }
+
+    // The cell under a plain left click, if its column has TableColumn::on_click
+    fn clickable_at(&self, data: &TableState<TableData>, me: &MouseEvent) -> Option<SingleCell> {
+        let modified = me.mods.shift() || me.mods.ctrl() || me.mods.meta();
+        if !me.button.is_left() || modified {
+            return None;
+        }
+        let cell = self.find_cell(data, &me.pos)?;
+        self.cell_delegate.on_click(cell.log.col)?;
+        Some(cell)
+    }
+
+    // Presses a cell with TableColumn::on_click, which works as a button. The selection is
+    // left be, and any editor closes without another opening.
+    fn press(&mut self, data: &mut TableState<TableData>, cell: SingleCell) {
+        self.clicking = Some(cell);
+        self.editing.stop_editing(&mut data.data, &mut self.spares);
+    }
+
+    // None if the table has no cells
+    fn last_vis_cell(&self, data: &TableState<TableData>) -> Option<AxisPair<VisIdx>> {
+        let lens = self.vis_lens(data);
+        if lens.row == 0 || lens.col == 0 {
+            return None;
+        }
+        Some(lens.map(|len| VisIdx(len - 1)))
+    }
+
+    // By logical column, over the rows now shown
+    fn footer_values(&self, data: &TableState<TableData>, env: &Env) -> Vector<Option<String>> {
+        if !self.cell_delegate.has_footer() {
+            return Vector::new();
+        }
+        let (delegate, table_data) = (&self.cell_delegate, &data.data);
+        let rows = &data.remaps[TableAxis::Rows];
+        (0..delegate.number_of_columns_in_data(table_data))
+            .map(|col| delegate.footer_value(LogIdx(col), table_data, rows, env))
+            .collect()
+    }
+
+    // The first and last rows on screen, frozen ones aside
+    fn visible_rows(&self, data: &TableState<TableData>) -> (VisIdx, VisIdx) {
+        data.measures[TableAxis::Rows]
+            .vis_range_from_pixels(data.scroll_y, data.scroll_y + self.viewport.height)
+    }
+
+    // Rows in the viewport, at least one
+    fn page_rows(&self, data: &TableState<TableData>) -> usize {
+        let (first, last) = data.measures[TableAxis::Rows]
+            .vis_range_from_pixels(data.scroll_y, data.scroll_y + self.viewport.height);
+        last.0.saturating_sub(first.0).max(1)
+    }
+
+    fn jump_focus(
+        &self,
+        data: &TableState<TableData>,
+        f: impl FnOnce(AxisPair<VisIdx>, AxisPair<VisIdx>) -> AxisPair<VisIdx>,
+    ) -> Option<TableSelection> {
+        let last = self.last_vis_cell(data)?;
+        let focus = data
+            .selection
+            .vis_focus()
+            .copied()
+            .unwrap_or_else(|| AxisPair::new(VisIdx(0), VisIdx(0)));
+        data.selection
+            .move_focus_to(f(focus, last), last, &data.remaps)
+    }
+
+    fn select_all(&self, data: &TableState<TableData>) -> TableSelection {
+        match self.last_vis_cell(data) {
+            Some(last) => TableSelection::select_all(last, &data.remaps),
+            None => TableSelection::NoSelection,
+        }
+    }
+
+    // The selected block as tab separated rows. The headings of the copied columns go first
+    // if `headers` is set, or with TableConfig::copy_headers when every row is copied.
+    fn selection_as_text(
+        &self,
+        data: &TableState<TableData>,
+        env: &Env,
+        rtc: &ResolvedTableConfig,
+        headers: bool,
+    ) -> Option<String> {
+        let last = self.last_vis_cell(data)?;
+        let rect = data.selection.to_cell_rect(last)?;
+        let all_rows = rect.start_row == VisIdx(0) && rect.end_row == last.row;
+        let mut lines = Vec::new();
+        if headers || (self.config.copy_headers && all_rows) {
+            let cols = (rect.start_col, rect.end_col);
+            let remap = &data.remaps[TableAxis::Columns];
+            let headers = copied_headers::<TableData, _>(&self.cell_delegate, remap, cols);
+            lines.push(csv_line(&headers, '\t'));
+        }
+        lines.extend(rect.rows().filter_map(|vis_row| {
+            let log_row = data.remaps[TableAxis::Rows].get_log_idx(vis_row)?;
+            data.data.with(log_row, |row| {
+                let fields: Vec<_> = rect
+                    .cols()
+                    .map(|vis_col| {
+                        let vis = AxisPair::new(vis_row, vis_col);
+                        self.cell_as_text(data, env, rtc, vis, row)
+                            .unwrap_or_default()
+                    })
+                    .collect();
+                csv_line(&fields, '\t')
+            })
+        }));
+        Some(lines.join("\n"))
+    }
+
+    fn cell_as_text(
+        &self,
+        data: &TableState<TableData>,
+        env: &Env,
+        rtc: &ResolvedTableConfig,
+        vis: AxisPair<VisIdx>,
+        row: &TableData::Item,
+    ) -> Option<String> {
+        let log = data.remaps.get_log_cell(&vis)?;
+        let padding = Self::padding(&self.cell_delegate, rtc, log.col);
+        let size = Self::content_size(&data.measures, &vis, padding).unwrap_or(Size::ZERO);
+        let cell = SingleCell::new(vis, log);
+        self.cell_delegate
+            .as_text(&CellCtx::Cell(&cell, size, Default::default()), row, env)
+    }
+
+    // Shown columns in order, with a line per row. Filtered out rows are included in the data's
+    // own order if `all_rows` is set.
+    fn to_csv(
+        &self,
+        data: &TableState<TableData>,
+        env: &Env,
+        rtc: &ResolvedTableConfig,
+        all_rows: bool,
+    ) -> String {
+        let col_count = self.cell_delegate.number_of_columns_in_data(&data.data);
+        let cols = &data.remaps[TableAxis::Columns];
+        let cols: Vec<(VisIdx, LogIdx)> =
+            VisIdx::range_inc_iter(VisIdx(0), cols.max_vis_idx(col_count))
+                .filter_map(|vis| Some((vis, cols.get_log_idx(vis)?)))
+                .collect();
+        let rows = &data.remaps[TableAxis::Rows];
+        let rows: Vec<LogIdx> = if all_rows {
+            (0..data.data.idx_len()).map(LogIdx).collect()
+        } else {
+            VisIdx::range_inc_iter(VisIdx(0), rows.max_vis_idx(data.data.idx_len()))
+                .filter_map(|vis| rows.get_log_idx(vis))
+                .collect()
+        };
+
+        let delegate = &self.cell_delegate;
+        let header: Vec<String> = cols
+            .iter()
+            .map(|(_, log_col)| delegate.column_header(*log_col).unwrap_or_default())
+            .collect();
+        let mut lines = vec![csv_line(&header, ',')];
+        for (idx, log_row) in rows.into_iter().enumerate() {
+            let line = data.data.with(log_row, |row| {
+                let fields: Vec<String> = cols
+                    .iter()
+                    .map(|(vis_col, log_col)| {
+                        let vis = AxisPair::new(VisIdx(idx), *vis_col);
+                        let cell = SingleCell::new(vis, AxisPair::new(log_row, *log_col));
+                        let padding = Self::padding(delegate, rtc, *log_col);
+                        let size =
+                            Self::content_size(&data.measures, &vis, padding).unwrap_or(Size::ZERO);
+                        let cell_ctx = CellCtx::Cell(&cell, size, Default::default());
+                        delegate.as_text(&cell_ctx, row, env).unwrap_or_default()
+                    })
+                    .collect();
+                csv_line(&fields, ',')
+            });
+            lines.extend(line);
+        }
+        lines.join("\r\n")
+    }
+
+    fn copy_selection(
+        &self,
+        data: &TableState<TableData>,
+        env: &Env,
+        rtc: &ResolvedTableConfig,
+        headers: bool,
+    ) {
+        if let Some(text) = self.selection_as_text(data, env, rtc, headers) {
+            Application::global().clipboard().put_string(text);
+        }
+    }
+    // The row that differs, if the rows are the same apart from one
+    fn only_changed_row(old_data: &TableData, data: &TableData) -> Option<LogIdx> {
+        if old_data.idx_len() != data.idx_len() {
+            return None;
+        }
+        let mut changed = None;
+        for log_idx in (0..data.idx_len()).map(LogIdx) {
+            let same = old_data
+                .with(log_idx, |old_row| data.with(log_idx, |row| old_row.same(row)))
+                .flatten()
+                .unwrap_or(false);
+            if !same {
+                if changed.is_some() {
+                    return None;
+                }
+                changed = Some(log_idx);
+            }
+        }
+        changed
+    }
+
+    fn frozen(&self) -> AxisPair<usize> {
+        self.resolved_config
+            .as_ref()
+            .map_or(AxisPair::new(0, 0), |rtc| {
+                AxisPair::new(rtc.frozen_rows, rtc.frozen_columns)
+            })
+    }
+
+    // Whether the view reaches the end of the last row, as it always does for a short table
+    fn at_bottom(&self, data: &TableState<TableData>) -> bool {
+        let length = data.measures[TableAxis::Rows].total_pixel_length();
+        data.scroll_y + self.viewport.height >= length - 1.
+    }
+
+    // How long the rows are, if stick_to_bottom is keeping the view at the end of them
+    fn pinned_length(&self, data: &TableState<TableData>) -> Option<f64> {
+        let pinned = self.config.stick_to_bottom && self.at_bottom(data);
+        pinned.then(|| data.measures[TableAxis::Rows].total_pixel_length())
+    }
+
+    // Scrolls on to rows appended since the rows were `pinned` at that length
+    fn follow_appended(&self, data: &mut TableState<TableData>, pinned: Option<f64>) {
+        let length = data.measures[TableAxis::Rows].total_pixel_length();
+        if pinned.map_or(false, |pinned| length > pinned) {
+            self.scroll_to_bottom(data);
+        }
+    }
+
+    fn scroll_to_bottom(&self, data: &mut TableState<TableData>) {
+        let count = data.remaps[TableAxis::Rows].vis_len(data.data.idx_len());
+        if count > 0 {
+            let last = VisIdx(count - 1);
+            let (frozen, height) = (self.frozen().row, self.viewport.height);
+            data.ensure_visible_on(TableAxis::Rows, (last, last), height, frozen);
+        }
+    }
+
+    // How far a cell is drawn from where the measures have it, by being frozen
+    fn frozen_offset(
+        data: &TableState<TableData>,
+        frozen: AxisPair<usize>,
+        vis: &AxisPair<VisIdx>,
+    ) -> Vec2 {
+        Vec2::new(
+            data.frozen_shift(TableAxis::Columns, vis.col, frozen.col),
+            data.frozen_shift(TableAxis::Rows, vis.row, frozen.row),
+        )
+    }
+
     fn find_cell(&self, data: &TableState<TableData>, pos: &Point) -> Option<SingleCell> {
+        let frozen = self.frozen();
+        let x = data.unfrozen_pixel(TableAxis::Columns, pos.x, frozen.col);
+        let y = data.unfrozen_pixel(TableAxis::Rows, pos.y, frozen.row);
         let (r, c) = (
-            data.measures[TableAxis::Rows].vis_idx_from_pixel(pos.y)?,
-            data.measures[TableAxis::Columns].vis_idx_from_pixel(pos.x)?,
+            data.measures[TableAxis::Rows].vis_idx_from_pixel(y)?,
+            data.measures[TableAxis::Columns].vis_idx_from_pixel(x)?,
         );
+        // Any part of a spanning cell selects all of it. Merged rows are still told apart, so
+        // each of them can be selected and edited.
+        let c = self.spanned_rect(data, &AxisPair::new(r, c)).start_col;
         let log_row = data.remaps[TableAxis::Rows].get_log_idx(r)?;
         let log_col = data.remaps[TableAxis::Columns].get_log_idx(c)?;
         Some(SingleCell::new(
@@ -141,10 +757,745 @@ where
         ))
     }
 
+    // The first and last visible column of each cell in a row that covers part of first..=last
+    fn row_spans(
+        &self,
+        row: &TableData::Item,
+        col_remap: &Remap,
+        col_count: usize,
+        (first, last): (VisIdx, VisIdx),
+    ) -> Vec<(VisIdx, VisIdx)> {
+        if !self.cell_delegate.spans_columns() {
+            let end = (last.0 + 1).min(col_count);
+            return (first.0..end).map(VisIdx).map(|col| (col, col)).collect();
+        }
+        // A cell from further left may span into view, so walk in from the first column
+        let mut spans = Vec::new();
+        let mut start = 0;
+        while start <= last.0 && start < col_count {
+            let span = col_remap.get_log_idx(VisIdx(start)).map_or(1, |log_col| {
+                self.cell_delegate.col_span(row, log_col).max(1)
+            });
+            let end = (start + span - 1).min(col_count - 1);
+            if end >= first.0 {
+                spans.push((VisIdx(start), VisIdx(end)));
+            }
+            start = end + 1;
+        }
+        spans
+    }
+
+    // The visible rows around this one whose cells in the logical column are merged with it,
+    // as they show equal values. The rows are compared as they are shown, so merges follow
+    // sorting and filtering. Group headers end a run.
+    fn merged_rows(
+        &self,
+        data: &TableState<TableData>,
+        vis_row: VisIdx,
+        log_col: LogIdx,
+    ) -> (VisIdx, VisIdx) {
+        let row_count = self.vis_lens(data).row;
+        if !self.cell_delegate.merges_equal(log_col) || vis_row.0 >= row_count {
+            return (vis_row, vis_row);
+        }
+        let rows = &data.remaps[TableAxis::Rows];
+        let mut cached = self.merged_runs.borrow_mut();
+        let stale = cached.as_ref().map_or(true, |runs| {
+            !runs.data.same(&data.data) || !runs.rows.same(rows)
+        });
+        if stale {
+            *cached = Some(MergedRuns {
+                data: data.data.clone(),
+                rows: rows.clone(),
+                starts: HashMap::new(),
+            });
+        }
+        let starts = cached
+            .as_mut()
+            .expect("merged runs")
+            .starts
+            .entry(log_col)
+            .or_insert_with(|| self.merged_run_starts(data, log_col));
+        // The run this row is in ends where the next one starts
+        let run = starts.partition_point(|start| *start <= vis_row.0);
+        let end = starts.get(run).map_or(row_count, |next| *next);
+        (VisIdx(starts[run - 1]), VisIdx(end - 1))
+    }
+
+    // The first visible row of each run of merged rows in a logical column
+    fn merged_run_starts(&self, data: &TableState<TableData>, log_col: LogIdx) -> Vec<usize> {
+        let rows = &data.remaps[TableAxis::Rows];
+        let cd = &self.cell_delegate;
+        let merged = |above: usize, below: usize| {
+            let above = rows.get_log_idx(VisIdx(above));
+            let below = rows.get_log_idx(VisIdx(below));
+            let (above, below) = match (above, below) {
+                (Some(above), Some(below)) => (above, below),
+                _ => return false,
+            };
+            let table = &data.data;
+            table
+                .with(above, |a| {
+                    table.with(below, |b| cd.equal_in_column(log_col, a, b))
+                })
+                .flatten()
+                .unwrap_or(false)
+        };
+        (0..self.vis_lens(data).row)
+            .filter(|row| *row == 0 || !merged(row - 1, *row))
+            .collect()
+    }
+
+    // The whole of the cell covering a visible cell, wider where a cell spans columns and
+    // taller where it is merged with equal ones
+    fn spanned_rect(&self, data: &TableState<TableData>, vis: &AxisPair<VisIdx>) -> CellRect {
+        let col_count = self.vis_lens(data).col;
+        let col_remap = &data.remaps[TableAxis::Columns];
+        let span = data.remaps[TableAxis::Rows]
+            .get_log_idx(vis.row)
+            .and_then(|log_row| {
+                data.data.with(log_row, |row| {
+                    self.row_spans(row, col_remap, col_count, (vis.col, vis.col))
+                })
+            })
+            .and_then(|spans| spans.first().copied());
+        let (start, end) = span.unwrap_or((vis.col, vis.col));
+        let rows = match col_remap.get_log_idx(start) {
+            Some(log_col) => self.merged_rows(data, vis.row, log_col),
+            None => (vis.row, vis.row),
+        };
+        CellRect::new(rows, (start, end))
+    }
+
+    // The cell with the fill handle and where the handle is drawn, if its column can be filled
+    fn fill_handle(&self, data: &TableState<TableData>) -> Option<(SingleCell, Rect)> {
+        if self.editing.is_active() {
+            return None;
+        }
+        let focus = data.selection.vis_focus()?;
+        let bounding = CellRect::point(focus.row, focus.col);
+        let vis = data
+            .selection
+            .get_drawable_selections(&bounding)
+            .fill_handle?;
+        let log = data.remaps.get_log_cell(&vis)?;
+        if !self.cell_delegate.can_fill(log.col) {
+            return None;
+        }
+        let rect = self
+            .spanned_rect(data, &vis)
+            .to_pixel_rect(&data.measures)?;
+        // Right to left, the cell's far corner is on its left
+        let x = if data.measures[TableAxis::Columns].is_reversed() {
+            rect.x0
+        } else {
+            rect.x1
+        };
+        let handle = Rect::from_center_size((x, rect.y1), (FILL_HANDLE_SIZE, FILL_HANDLE_SIZE));
+        Some((SingleCell::new(vis, log), handle))
+    }
+
+    fn fill_handle_at(&self, data: &TableState<TableData>, pos: &Point) -> Option<SingleCell> {
+        let (cell, handle) = self.fill_handle(data)?;
+        let frozen = self.frozen();
+        let x = data.unfrozen_pixel(TableAxis::Columns, pos.x, frozen.col);
+        let y = data.unfrozen_pixel(TableAxis::Rows, pos.y, frozen.row);
+        // A little slack, as the handle is small
+        if handle.inflate(2., 2.).contains(Point::new(x, y)) {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    // The row a fill dragged to this height reaches, kept to the rows there are
+    fn fill_target(&self, data: &TableState<TableData>, y: f64) -> Option<VisIdx> {
+        let last = VisIdx(self.vis_lens(data).row.checked_sub(1)?);
+        let y = data.unfrozen_pixel(TableAxis::Rows, y, self.frozen().row);
+        let row = match data.measures[TableAxis::Rows].vis_idx_from_pixel(y) {
+            Some(row) => row,
+            None if y < 0. => VisIdx(0),
+            None => last,
+        };
+        Some(row.min(last))
+    }
+
+    // The selected block a fill handle drag starts from, with the cell spans its handle's
+    // cell takes in
+    fn fill_block(&self, data: &TableState<TableData>, handle: &SingleCell) -> CellRect {
+        let spanned = self.spanned_rect(data, &handle.vis);
+        let block = self
+            .last_vis_cell(data)
+            .and_then(|last| data.selection.to_cell_rect(last))
+            .unwrap_or_else(|| CellRect::point(handle.vis.row, handle.vis.col));
+        CellRect::new(
+            (block.start_row, block.end_row),
+            (block.start_col, block.end_col.max(spanned.end_col)),
+        )
+    }
+
+    // The block and the rows a fill dragged to `to` covers
+    fn fill_reach(block: &CellRect, to: VisIdx) -> CellRect {
+        let rows = (block.start_row.min(to), block.end_row.max(to));
+        CellRect::new(rows, (block.start_col, block.end_col))
+    }
+
+    // Copies the block's values along each of its columns that can be filled, to every row up
+    // to and including `to`. A block of several rows is repeated, as far as it goes. Group
+    // headings in the way are passed over.
+    fn fill_rows(&self, data: &mut TableState<TableData>, block: &CellRect, to: VisIdx) {
+        let rows = &data.remaps[TableAxis::Rows];
+        let sources: Vec<TableData::Item> = block
+            .rows()
+            .filter_map(|vis_row| rows.get_log_idx(vis_row))
+            .filter_map(|log_row| data.data.with(log_row, |row| row.clone()))
+            .collect();
+        let reach = Self::fill_reach(block, to);
+        let cols: Vec<LogIdx> = block
+            .cols()
+            .filter_map(|vis_col| data.remaps[TableAxis::Columns].get_log_idx(vis_col))
+            .filter(|log_col| self.cell_delegate.can_fill(*log_col))
+            .collect();
+        if sources.is_empty() || cols.is_empty() {
+            return;
+        }
+        for vis_row in reach.rows() {
+            if vis_row >= block.start_row && vis_row <= block.end_row {
+                continue;
+            }
+            let offset = vis_row.0 as isize - block.start_row.0 as isize;
+            let source = &sources[offset.rem_euclid(sources.len() as isize) as usize];
+            if let Some(log_row) = data.remaps[TableAxis::Rows].get_log_idx(vis_row) {
+                data.data.with_mut(log_row, |row| {
+                    for log_col in &cols {
+                        self.cell_delegate.fill(*log_col, source, row)
+                    }
+                });
+            }
+        }
+    }
+
+    // How far the focus moves along the axis to reach the next cell, stepping over the rest of
+    // a cell that spans columns. Merged rows are stepped through one at a time, as they are
+    // clicked on.
+    fn span_step(
+        &self,
+        data: &TableState<TableData>,
+        axis: TableAxis,
+        backwards: bool,
+    ) -> VisOffset {
+        let focus = match (data.selection.vis_focus(), axis) {
+            (Some(focus), TableAxis::Columns) => *focus,
+            _ => return VisOffset(if backwards { -1 } else { 1 }),
+        };
+        let spanned = self.spanned_rect(data, &focus);
+        let target = if !backwards {
+            spanned.end_col.0 as isize + 1
+        } else if spanned.start_col.0 > 0 {
+            let before = AxisPair::new(focus.row, VisIdx(spanned.start_col.0 - 1));
+            self.spanned_rect(data, &before).start_col.0 as isize
+        } else {
+            -1
+        };
+        VisOffset(target - focus.col.0 as isize)
+    }
+
+    // The cell's full text, if it doesn't fit in the cell
+    fn clipped_text(
+        &self,
+        text: &mut PietText,
+        data: &TableState<TableData>,
+        env: &Env,
+        cell: &SingleCell,
+    ) -> Option<String> {
+        let rtc = self.resolved_config.as_ref()?;
+        let spanned = self.spanned_rect(data, &cell.vis);
+        let padding = Self::padding(&self.cell_delegate, rtc, cell.log.col);
+        let size = spanned
+            .to_pixel_rect(&data.measures)?
+            .inset(-padding)
+            .size();
+        let cell_ctx = CellCtx::Cell(cell, size, Default::default());
+        let cd = &self.cell_delegate;
+        data.data
+            .with(cell.log.row, |row| {
+                let mut wanted = |axis| cd.measure(text, axis, &cell_ctx, row, env);
+                let wider = wanted(TableAxis::Columns).map_or(false, |w| w > size.width);
+                let taller = wanted(TableAxis::Rows).map_or(false, |h| h > size.height);
+                if wider || taller {
+                    cd.as_text(&cell_ctx, row, env)
+                } else {
+                    None
+                }
+            })
+            .flatten()
+    }
+
+    // Resting on a clipped cell for a while shows all of its text. Moving off it, pressing,
+    // scrolling or a change to the data hides it again.
+    fn track_tooltip(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &TableState<TableData>,
+        env: &Env,
+    ) {
+        match event {
+            Event::MouseMove(me) => {
+                let hovered = self
+                    .find_cell(data, &me.pos)
+                    .filter(|_| !self.dragging_selection && !self.editing.is_active());
+                if hovered.as_ref() != self.tooltip.part() {
+                    if let Some(window) = self.tooltip.dismiss() {
+                        ctx.submit_command(CLOSE_WINDOW.to(window));
+                    }
+                    if let Some(cell) = hovered {
+                        let token = ctx.request_timer(self.config.tooltip_delay);
+                        self.tooltip = Tooltip::Waiting(cell, token, me.window_pos);
+                    }
+                } else if let Tooltip::Waiting(_, _, window_pos) = &mut self.tooltip {
+                    *window_pos = me.window_pos;
+                }
+            }
+            Event::MouseDown(_) | Event::Wheel(_) | Event::KeyDown(_) => {
+                if let Some(window) = self.tooltip.dismiss() {
+                    ctx.submit_command(CLOSE_WINDOW.to(window));
+                }
+            }
+            Event::Timer(fired) => {
+                if let Tooltip::Waiting(cell, token, window_pos) = &self.tooltip {
+                    if fired == token {
+                        let (cell, window_pos) = (cell.clone(), *window_pos);
+                        let window = self
+                            .clipped_text(ctx.text(), data, env, &cell)
+                            .map(|text| show_tooltip(ctx, text, window_pos, env));
+                        self.tooltip = Tooltip::Shown(cell, window);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Scrolling, resizing or new data starts a short wait, after which any change to the rows
+    // on screen is submitted, along with those of them yet to load. Scrolling on in the
+    // meantime doesn't restart it, so a long scroll reports every so often rather than on
+    // every step.
+    fn track_visible_rows(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &TableState<TableData>,
+    ) {
+        match event {
+            Event::Command(cmd) if cmd.is(VIEWPORT_MOVED) => self.wait_for_visible_rows(ctx),
+            Event::Timer(fired) if self.visible_rows_timer == Some(*fired) => {
+                self.visible_rows_timer = None;
+                let visible = self.visible_rows(data);
+                if let Some(selector) = self.config.on_visible_rows {
+                    if self.visible_rows != Some(visible) {
+                        ctx.submit_command(selector.with(visible));
+                    }
+                }
+                self.visible_rows = Some(visible);
+                if let Some(selector) = self.config.on_missing_rows {
+                    let missing = Self::missing_rows(data, visible);
+                    if !missing.is_empty() {
+                        ctx.submit_command(selector.with(missing));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Logical rows among the visual ones that the data has yet to load
+    fn missing_rows(data: &TableState<TableData>, (first, last): (VisIdx, VisIdx)) -> Vec<LogIdx> {
+        let rows = &data.remaps[TableAxis::Rows];
+        VisIdx::range_inc_iter(first, last)
+            .filter_map(|vis| rows.get_log_idx(vis))
+            .filter(|log| log.0 < data.data.idx_len() && data.data.with(*log, |_| ()).is_none())
+            .collect()
+    }
+
+    // True if the size changed
+    fn resize_viewport(&mut self, size: Size) -> bool {
+        let resized = self.viewport != size;
+        self.viewport = size;
+        resized
+    }
+
+    fn watches_rows(&self) -> bool {
+        self.config.on_visible_rows.is_some() || self.config.on_missing_rows.is_some()
+    }
+
+    fn wait_for_visible_rows(&mut self, ctx: &mut EventCtx) {
+        if self.watches_rows() && self.visible_rows_timer.is_none() {
+            self.visible_rows_timer = Some(ctx.request_timer(VISIBLE_ROWS_DELAY));
+        }
+    }
+
+    // Loading cells spin for as long as any of them is on screen. Until the scroll gives the
+    // cells their view there is no telling which are, so they keep going.
+    fn track_loading(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &TableState<TableData>,
+        env: &Env,
+    ) {
+        match event {
+            Event::WindowSize(_) => self.spinning = true,
+            Event::AnimFrame(nanos) if self.spinning => {
+                self.loading_phase =
+                    (self.loading_phase + *nanos as f64 / 1e9 * SPIN_SPEED).fract();
+                ctx.request_paint();
+                self.spinning = self.viewport == Size::ZERO || self.any_loading(data, env);
+            }
+            _ => return,
+        }
+        if self.spinning {
+            ctx.request_anim_frame();
+        }
+    }
+
+    // Whether any cell on screen is still waiting for its value
+    fn any_loading(&self, data: &TableState<TableData>, env: &Env) -> bool {
+        if self.last_vis_cell(data).is_none() {
+            return false;
+        }
+        let (first_row, last_row) = self.visible_rows(data);
+        let (first_col, last_col) = data.measures[TableAxis::Columns]
+            .vis_range_from_pixels(data.scroll_x, data.scroll_x + self.viewport.width);
+        VisIdx::range_inc_iter(first_row, last_row).any(|row| {
+            VisIdx::range_inc_iter(first_col, last_col).any(|col| {
+                let vis = AxisPair::new(row, col);
+                let log = match data.remaps.get_log_cell(&vis) {
+                    Some(log) => log,
+                    None => return false,
+                };
+                let cell = SingleCell::new(vis, log);
+                let cell_ctx = CellCtx::Cell(&cell, Size::ZERO, Default::default());
+                let delegate = &self.cell_delegate;
+                data.data
+                    .with(log.row, |item| delegate.is_loading(&cell_ctx, item, env))
+                    .unwrap_or(false)
+            })
+        })
+    }
+
     fn measured_size(&mut self, measures: &AxisPair<AxisMeasure>) -> Size {
         measures.map(|m| m.total_pixel_length()).size()
     }
 
+    // The column's own padding if it sets one, otherwise the table's
+    fn padding(cell_delegate: &CellDel, rtc: &ResolvedTableConfig, log_col: LogIdx) -> Insets {
+        cell_delegate
+            .column_padding(log_col)
+            .unwrap_or_else(|| Insets::uniform(rtc.cell_padding))
+    }
+
+    fn content_size(
+        measures: &AxisPair<AxisMeasure>,
+        vis: &AxisPair<VisIdx>,
+        padding: Insets,
+    ) -> Option<Size> {
+        let size = measures
+            .zip_with(vis, |m, vis| m.pixels_length_for_vis(*vis))
+            .opt()?
+            .size();
+        Some(
+            Rect::from_origin_size(Point::ZERO, size)
+                .inset(-padding)
+                .size(),
+        )
+    }
+
+    // The next cell along the row with an editor, wrapping onto the rows either side. Editors
+    // are taken to be per column, so a whole row's worth of misses ends the search.
+    fn next_editable_cell(
+        cell_delegate: &mut CellDel,
+        data: &TableState<TableData>,
+        lens: AxisPair<usize>,
+        from: &SingleCell,
+        backwards: bool,
+        rtc: &ResolvedTableConfig,
+    ) -> Option<SingleCell> {
+        let (rows, cols) = (lens.row as isize, lens.col as isize);
+        let step = if backwards { -1 } else { 1 };
+        let mut pos = from.vis.row.0 as isize * cols + from.vis.col.0 as isize + step;
+        let mut misses = 0;
+        while (0..rows * cols).contains(&pos) && misses < cols {
+            let vis = AxisPair::new(VisIdx((pos / cols) as usize), VisIdx((pos % cols) as usize));
+            if let Some(log) = data.remaps.get_log_cell(&vis) {
+                let cell = SingleCell::new(vis, log);
+                let padding = Self::padding(cell_delegate, rtc, log.col);
+                let size = Self::content_size(&data.measures, &vis, padding).unwrap_or(Size::ZERO);
+                if cell_delegate.can_edit(&CellCtx::Cell(&cell, size, Default::default())) {
+                    return Some(cell);
+                }
+            }
+            misses += 1;
+            pos += step;
+        }
+        None
+    }
+
+    fn matches_search(&self, cell: &SingleCell, row: &TableData::Item, env: &Env) -> bool {
+        let search = match &self.search {
+            Some(search) => search,
+            None => return false,
+        };
+        let cell = CellCtx::Cell(cell, Size::ZERO, Default::default());
+        self.cell_delegate
+            .as_text(&cell, row, env)
+            .map_or(false, |text| search.matches(&text))
+    }
+
+    // The first cell matching the search in the order shown, going on from the focus and
+    // wrapping around at the end. `skip_focus` leaves the focus itself until last.
+    fn find_match(
+        &self,
+        data: &TableState<TableData>,
+        env: &Env,
+        backwards: bool,
+        skip_focus: bool,
+    ) -> Option<SingleCell> {
+        self.search.as_ref()?;
+        let lens = self.vis_lens(data);
+        let (total, cols) = ((lens.row * lens.col) as isize, lens.col as isize);
+        let step = if backwards { -1 } else { 1 };
+        let (start, skip) = match data.selection.vis_focus() {
+            Some(focus) => {
+                let pos = focus.row.0 as isize * cols + focus.col.0 as isize;
+                (pos, skip_focus)
+            }
+            None => (if backwards { total - 1 } else { 0 }, false),
+        };
+        let col_remap = &data.remaps[TableAxis::Columns];
+        let all_cols = (VisIdx(0), VisIdx(lens.col.saturating_sub(1)));
+        // The row last searched, with the columns its cells start at
+        let mut starts: Option<(VisIdx, Vec<VisIdx>)> = None;
+        (0..total).find_map(|k| {
+            let pos = (start + step * (k + skip as isize)).rem_euclid(total);
+            let vis = AxisPair::new(VisIdx((pos / cols) as usize), VisIdx((pos % cols) as usize));
+            let cell = SingleCell::new(vis, data.remaps.get_log_cell(&vis)?);
+            let found = data.data.with(cell.log.row, |row| {
+                // Cells covered by a span aren't shown, so can't match
+                if self.cell_delegate.spans_columns() {
+                    if !matches!(&starts, Some((searched, _)) if *searched == vis.row) {
+                        let spans = self.row_spans(row, col_remap, lens.col, all_cols);
+                        let at = spans.into_iter().map(|(start, _)| start).collect();
+                        starts = Some((vis.row, at));
+                    }
+                    if let Some((_, at)) = &starts {
+                        if at.binary_search(&vis.col).is_err() {
+                            return false;
+                        }
+                    }
+                }
+                self.matches_search(&cell, row, env)
+            });
+            if found.unwrap_or(false) {
+                Some(cell)
+            } else {
+                None
+            }
+        })
+    }
+
+    // The next row in visible order whose text starts with the prefix, from the focused row
+    // on or, with `skip_focus`, after it. The text is from the first sorted column, or the
+    // first column shown if nothing is sorted.
+    fn type_ahead_row(
+        &self,
+        data: &TableState<TableData>,
+        env: &Env,
+        prefix: &str,
+        skip_focus: bool,
+    ) -> Option<VisIdx> {
+        // The sorted column, or the first shown one if there is none or it is hidden
+        let columns = &data.remaps[TableAxis::Columns];
+        let sorted = data.remap_specs[TableAxis::Rows].sort_by.front();
+        let (vis_col, log_col) = sorted
+            .map(|sort| LogIdx(sort.idx))
+            .and_then(|log_col| Some((columns.get_vis_idx(log_col)?, log_col)))
+            .or_else(|| Some((VisIdx(0), columns.get_log_idx(VisIdx(0))?)))?;
+        let rows = self.vis_lens(data).row;
+        let focus = data.selection.vis_focus().map(|focus| focus.row.0);
+        let skip = (focus.is_some() && skip_focus) as usize;
+        let start = focus.unwrap_or(0);
+        (0..rows)
+            .map(|k| VisIdx((start + k + skip) % rows))
+            .find(|vis_row| {
+                let log_row = match data.remaps[TableAxis::Rows].get_log_idx(*vis_row) {
+                    Some(log_row) => log_row,
+                    None => return false, // A group header
+                };
+                let cell = SingleCell::new(
+                    AxisPair::new(*vis_row, vis_col),
+                    AxisPair::new(log_row, log_col),
+                );
+                let cell_ctx = CellCtx::Cell(&cell, Size::ZERO, Default::default());
+                let delegate = &self.cell_delegate;
+                data.data
+                    .with(log_row, |row| delegate.as_text(&cell_ctx, row, env))
+                    .flatten()
+                    .map_or(false, |text| text.to_lowercase().starts_with(prefix))
+            })
+    }
+
+    // Fits the given rows to cells that ask for a height, eg wrapped text. A row grows to fit,
+    // and shrinks back as far as its default once its cells want less, unless it was sized by
+    // hand. With `ease` set, rows below the top of the view are left as they are and returned
+    // with the height they want.
+    fn measure_rows(
+        &self,
+        text: &mut PietText,
+        data: &mut TableState<TableData>,
+        rtc: &ResolvedTableConfig,
+        env: &Env,
+        ease: bool,
+        which: impl IntoIterator<Item = VisIdx>,
+    ) -> Vec<(VisIdx, f64, f64)> {
+        let rows = &data.remaps[TableAxis::Rows];
+        let cols = &data.remaps[TableAxis::Columns];
+        let measures = &mut data.measures;
+        let mut resized = Vec::new();
+        if data.data.is_empty() || !measures[TableAxis::Rows].can_resize(VisIdx(0)) {
+            return resized;
+        }
+        let col_count = self.cell_delegate.number_of_columns_in_data(&data.data);
+        let default = measures[TableAxis::Rows].default_length();
+        let mut measured = self.measured_heights.borrow_mut();
+        let mut scroll_shift = 0.;
+
+        for vis_row in which {
+            let log_row = match rows.get_log_idx(vis_row) {
+                Some(log_row) => log_row,
+                None => continue,
+            };
+            let required = data
+                .data
+                .with(log_row, |row| {
+                    VisIdx::range_inc_iter(VisIdx(0), cols.max_vis_idx(col_count))
+                        .filter_map(|vis_col| {
+                            let sc = SingleCell::new(
+                                AxisPair::new(vis_row, vis_col),
+                                AxisPair::new(log_row, cols.get_log_idx(vis_col)?),
+                            );
+                            let padding = Self::padding(&self.cell_delegate, rtc, sc.log.col);
+                            let size = Self::content_size(measures, &sc.vis, padding)?;
+                            let cell = CellCtx::Cell(&sc, size, Default::default());
+                            self.cell_delegate
+                                .measure(text, TableAxis::Rows, &cell, row, env)
+                                .map(|height| height + padding.y_value())
+                        })
+                        .fold(None, |acc: Option<f64>, h| {
+                            Some(acc.map_or(h, |a| a.max(h)))
+                        })
+                })
+                .flatten();
+
+            let row_measure = &mut measures[TableAxis::Rows];
+            let (current, far) = match (
+                row_measure.pixels_length_for_vis(vis_row),
+                row_measure.far_pixel_from_vis(vis_row),
+            ) {
+                (Some(current), Some(far)) => (current, far),
+                _ => continue,
+            };
+            // A row sized by hand only ever grows to fit
+            let ours = current == default || measured.get(&log_row) == Some(&current);
+            let wanted = match required {
+                Some(wanted) if ours || wanted > current => wanted.max(default),
+                None if ours => default,
+                _ => continue,
+            };
+            if wanted == default {
+                measured.remove(&log_row);
+            } else {
+                measured.insert(log_row, wanted);
+            }
+            if wanted == current {
+                continue;
+            }
+            // Rows resized above the viewport shouldn't move what is on screen
+            if far <= data.scroll_y {
+                row_measure.set_pixel_length_for_vis(vis_row, wanted);
+                scroll_shift += wanted - current;
+            } else if ease {
+                resized.push((vis_row, current, wanted));
+            } else {
+                row_measure.set_pixel_length_for_vis(vis_row, wanted);
+            }
+        }
+        data.scroll_y += scroll_shift;
+        resized
+    }
+
+    // The frozen rows and those scrolled into view, the rows worth measuring
+    fn rows_in_view(&self, data: &TableState<TableData>) -> Vec<VisIdx> {
+        let (first, last) = self.visible_rows(data);
+        let frozen = self.frozen().row.min(first.0);
+        (0..frozen)
+            .map(VisIdx)
+            .chain(VisIdx::range_inc_iter(first, last))
+            .collect()
+    }
+
+    // Fits a column to its widest cell. Columns with no rows, or whose cells
+    // don't report a width, are left as they are
+    fn auto_size_column(
+        &self,
+        text: &mut PietText,
+        data: &mut TableState<TableData>,
+        rtc: &ResolvedTableConfig,
+        env: &Env,
+        vis_col: VisIdx,
+    ) {
+        let rows = &data.remaps[TableAxis::Rows];
+        let measures = &mut data.measures;
+        let log_col = match data.remaps[TableAxis::Columns].get_log_idx(vis_col) {
+            Some(log_col) => log_col,
+            None => return,
+        };
+        if data.data.is_empty() || !measures[TableAxis::Columns].can_resize(vis_col) {
+            return;
+        }
+        let row_limit = rtc.auto_size_row_limit.unwrap_or(usize::MAX);
+        let padding = Self::padding(&self.cell_delegate, rtc, log_col);
+
+        let required = VisIdx::range_inc_iter(VisIdx(0), rows.max_vis_idx(data.data.idx_len()))
+            .take(row_limit)
+            .filter_map(|vis_row| {
+                let sc = SingleCell::new(
+                    AxisPair::new(vis_row, vis_col),
+                    AxisPair::new(rows.get_log_idx(vis_row)?, log_col),
+                );
+                let size = Self::content_size(measures, &sc.vis, padding)?;
+                data.data
+                    .with(sc.log.row, |row| {
+                        self.cell_delegate.measure(
+                            text,
+                            TableAxis::Columns,
+                            &CellCtx::Cell(&sc, size, Default::default()),
+                            row,
+                            env,
+                        )
+                    })
+                    .flatten()
+            })
+            .fold(None, |acc: Option<f64>, w| {
+                Some(acc.map_or(w, |a| a.max(w)))
+            });
+
+        if let Some(required) = required {
+            measures[TableAxis::Columns]
+                .set_pixel_length_for_vis(vis_col, required + padding.x_value());
+        }
+    }
+
     fn paint_cells(
         &self,
         ctx: &mut PaintCtx,
@@ -152,17 +1503,22 @@ where
         env: &Env,
         rect: &CellRect,
     ) {
+        let col_count = self.vis_lens(data).col;
         for vis_row_idx in rect.rows() {
+            let row_remap = &data.remaps[TableAxis::Rows];
             let col_remap = &data.remaps[TableAxis::Columns];
             let measures = &data.measures;
 
-            if let Some(log_row_idx) = data.remaps[TableAxis::Rows].get_log_idx(vis_row_idx) {
+            if let Some(log_row_idx) = row_remap.get_log_idx(vis_row_idx) {
                 let table_data = &data.data;
-                table_data.with(log_row_idx, |row| {
+                let loaded = table_data.with(log_row_idx, |row| {
+                    let cols = (rect.start_col, rect.end_col);
+                    let spans = self.row_spans(row, col_remap, col_count, cols);
+                    let cells = self.merged_cells(data, &spans, vis_row_idx, rect.start_row);
                     self.paint_row(
                         ctx,
                         env,
-                        &mut rect.cols(),
+                        &cells,
                         log_row_idx,
                         vis_row_idx,
                         row,
@@ -170,15 +1526,113 @@ where
                         measures,
                     )
                 });
+                if loaded.is_none() {
+                    self.paint_missing_row(ctx, data, env, vis_row_idx);
+                }
+            } else if let Some(first_row) = row_remap.group_header_at(vis_row_idx) {
+                self.paint_group_header(ctx, data, env, vis_row_idx, first_row);
             }
         }
     }
 
+    // The cells to paint in a row, from its spans. A merged cell covers all of its rows, so it
+    // is only painted from the first of them being painted.
+    fn merged_cells(
+        &self,
+        data: &TableState<TableData>,
+        spans: &[(VisIdx, VisIdx)],
+        vis_row: VisIdx,
+        first_painted: VisIdx,
+    ) -> Vec<CellRect> {
+        let col_remap = &data.remaps[TableAxis::Columns];
+        spans
+            .iter()
+            .filter_map(|&(start, end)| {
+                let rows = match col_remap.get_log_idx(start) {
+                    Some(log_col) => self.merged_rows(data, vis_row, log_col),
+                    None => (vis_row, vis_row),
+                };
+                if vis_row == rows.0.max(first_painted) {
+                    Some(CellRect::new(rows, (start, end)))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    // A band across the whole row, showing the group's value as its column would
+    fn paint_group_header(
+        &self,
+        ctx: &mut PaintCtx,
+        data: &TableState<TableData>,
+        env: &Env,
+        vis_row_idx: VisIdx,
+        first_row: LogIdx,
+    ) -> Option<()> {
+        let rtc = self.resolved_config.as_ref()?;
+        let group_col = LogIdx(data.remap_specs[TableAxis::Rows].group_by?);
+        let rows = &data.measures[TableAxis::Rows];
+        let y = rows.first_pixel_from_vis(vis_row_idx)?;
+        let height = rows.pixels_length_for_vis(vis_row_idx)?;
+        let width = data.measures[TableAxis::Columns].total_pixel_length();
+        let band = Rect::new(0., y, width, y + height);
+        ctx.fill(band, &rtc.header_background);
+
+        let padded_rect = band.inset(-rtc.cell_padding);
+        let sc = SingleCell::new(
+            AxisPair::new(vis_row_idx, VisIdx(0)),
+            AxisPair::new(first_row, group_col),
+        );
+        data.data.with(first_row, |row| {
+            ctx.with_save(|ctx| {
+                ctx.clip(padded_rect);
+                ctx.transform(Affine::translate(padded_rect.origin().to_vec2()));
+                let cell = CellCtx::Cell(&sc, padded_rect.size(), Default::default());
+                ctx.with_child_ctx(padded_rect, |ctxt| {
+                    self.cell_delegate.paint(ctxt, &cell, row, env);
+                });
+            })
+        });
+        ctx.stroke_bottom_left_border(
+            &band,
+            rtc.gridlines,
+            &rtc.cells_border,
+            rtc.cell_border_thickness,
+        );
+        Some(())
+    }
+
+    // A greyed bar in place of a row the data has yet to load
+    fn paint_missing_row(
+        &self,
+        ctx: &mut PaintCtx,
+        data: &TableState<TableData>,
+        env: &Env,
+        vis_row_idx: VisIdx,
+    ) -> Option<()> {
+        let rtc = self.resolved_config.as_ref()?;
+        let rows = &data.measures[TableAxis::Rows];
+        let y = rows.first_pixel_from_vis(vis_row_idx)?;
+        let height = rows.pixels_length_for_vis(vis_row_idx)?;
+        let width = data.measures[TableAxis::Columns].total_pixel_length();
+        let band = Rect::new(0., y, width, y + height);
+        let placeholder = env.get(theme::PLACEHOLDER_COLOR).with_alpha(0.3);
+        ctx.fill(band.inset(-rtc.cell_padding), &placeholder);
+        ctx.stroke_bottom_left_border(
+            &band,
+            rtc.gridlines,
+            &rtc.cells_border,
+            rtc.cell_border_thickness,
+        );
+        Some(())
+    }
+
     fn paint_row(
         &self,
         ctx: &mut PaintCtx,
         env: &Env,
-        cols: &mut impl Iterator<Item = VisIdx>,
+        cells: &[CellRect],
         log_row_idx: LogIdx,
         vis_row_idx: VisIdx,
         row: &TableData::Item,
@@ -186,30 +1640,34 @@ where
         measures: &AxisPair<AxisMeasure>,
     ) -> Option<()> {
         if let Some(rtc) = &self.resolved_config {
-            for vis_col_idx in cols {
+            for cell in cells {
+                let vis_col_idx = cell.start_col;
                 if let Some(log_col_idx) = col_remap.get_log_idx(vis_col_idx) {
                     let sc = SingleCell::new(
                         AxisPair::new(vis_row_idx, vis_col_idx),
                         AxisPair::new(log_row_idx, log_col_idx),
                     );
 
-                    let cell_rect = Rect::from_origin_size(
-                        measures
-                            .zip_with(&sc.vis, |m, vis| m.first_pixel_from_vis(*vis))
-                            .opt()?
-                            .point(),
-                        measures
-                            .zip_with(&sc.vis, |m, vis| m.pixels_length_for_vis(*vis))
-                            .opt()?
-                            .size(),
-                    );
-                    let padded_rect = cell_rect.inset(-rtc.cell_padding);
+                    // Only the outside of a spanning or merged cell gets borders
+                    let cell_rect = cell.to_pixel_rect(measures)?;
+                    let padding = Self::padding(&self.cell_delegate, rtc, log_col_idx);
+                    let padded_rect = cell_rect.inset(-padding);
+                    if cell.start_row != cell.end_row {
+                        // One cell, rather than the stripes of the rows it covers
+                        ctx.fill(cell_rect, &rtc.cells_background);
+                    }
+                    let cell = CellCtx::Cell(&sc, padded_rect.size(), Default::default());
+                    if let Some(color) = self.cell_delegate.background(&cell, row, env) {
+                        ctx.fill(cell_rect, &color);
+                    }
 
+                    if self.matches_search(&sc, row, env) {
+                        ctx.fill(cell_rect, &rtc.search_highlight);
+                    }
                     ctx.with_save(|ctx| {
                         let layout_origin = padded_rect.origin().to_vec2();
                         ctx.clip(padded_rect);
                         ctx.transform(Affine::translate(layout_origin));
-                        let cell = CellCtx::Cell(&sc);
                         ctx.with_child_ctx(padded_rect, |ctxt| {
                             self.cell_delegate.paint(ctxt, &cell, row, env);
                         });
@@ -217,6 +1675,7 @@ where
 
                     ctx.stroke_bottom_left_border(
                         &cell_rect,
+                        rtc.gridlines,
                         &rtc.cells_border,
                         rtc.cell_border_thickness,
                     );
@@ -237,37 +1696,191 @@ where
     ) -> Option<()> {
         let selected = data.selection.get_drawable_selections(cell_rect);
 
-        let sel_color = &rtc.selection_color;
-        let sel_fill = &(*sel_color).with_alpha(0.2);
-
-        for range_rect in &selected.ranges {
-            if let Some(range_draw_rect) = range_rect.to_pixel_rect(&data.measures) {
-                ctx.fill(range_draw_rect, sel_fill);
-                ctx.stroke(range_draw_rect, sel_color, rtc.cell_border_thickness)
+        let colored = [
+            (&selected.ranges, &rtc.selection_color),
+            (&selected.slices, &rtc.slice_selection_color),
+        ];
+        for (range_rects, color) in colored.iter() {
+            let fill = &(**color).with_alpha(rtc.selection_opacity);
+            for range_rect in range_rects.iter() {
+                if let Some(range_draw_rect) = range_rect.to_pixel_rect(&data.measures) {
+                    ctx.fill(range_draw_rect, fill);
+                    ctx.stroke(range_draw_rect, *color, rtc.cell_border_thickness)
+                }
             }
         }
 
         let focus = selected.focus?;
+        let focus_rect = self
+            .spanned_rect(data, &focus)
+            .to_pixel_rect(&data.measures)?;
+        let duration = rtc.animation_duration.as_secs_f64();
+        let focus_rect = self.animation.focus_rect(duration).unwrap_or(focus_rect);
+
+        ctx.stroke(focus_rect, &rtc.focus_color, rtc.focus_border_width);
+
+        // Outlining the rows a fill will reach, over the handle
+        if let Some((block, to)) = &self.filling {
+            let reach = Self::fill_reach(block, *to);
+            if let Some(reach_rect) = reach.to_pixel_rect(&data.measures) {
+                ctx.stroke(reach_rect, &rtc.focus_color, rtc.cell_border_thickness);
+            }
+        } else if let Some((_, handle)) = self.fill_handle(data) {
+            ctx.fill(handle, &rtc.focus_color);
+        }
+        Some(())
+    }
+
+    fn shows_empty_message(&self, data: &TableState<TableData>) -> bool {
+        let rows = &data.remaps[TableAxis::Rows];
+        self.config.empty_message.is_some() && rows.vis_len(data.data.idx_len()) == 0
+    }
+
+    // Centred in the part of the cells in view. Only painted, so clicks go where they would.
+    fn paint_empty_message(
+        &self,
+        ctx: &mut PaintCtx,
+        rtc: &ResolvedTableConfig,
+        env: &Env,
+        region: &Rect,
+    ) -> Option<()> {
+        let message = self.config.empty_message.as_ref()?;
+        let shown = region.intersect(ctx.size().to_rect());
+        ctx.fill(shown, &rtc.cells_background);
+        let layout = ctx
+            .text()
+            .new_text_layout(message.clone())
+            .font(FontFamily::SYSTEM_UI, env.get(theme::TEXT_SIZE_NORMAL))
+            .text_color(env.get(theme::PLACEHOLDER_COLOR))
+            .build()
+            .ok()?;
+        let size = layout.size();
+        let origin = shown.center() - (size / 2.).to_vec2();
+        ctx.draw_text(&layout, origin);
+        Some(())
+    }
+
+    // Under the cells and the selection, so that still shows through
+    fn paint_stripes(
+        ctx: &mut PaintCtx,
+        data: &TableState<TableData>,
+        rtc: &ResolvedTableConfig,
+        (start, end): (VisIdx, VisIdx),
+        (x0, x1): (f64, f64),
+    ) {
+        let measure = &data.measures[TableAxis::Rows];
+        for vis_row in VisIdx::range_inc_iter(start, end) {
+            let color = if vis_row.0 % 2 == 0 {
+                &rtc.even_row_background
+            } else {
+                &rtc.odd_row_background
+            };
+            if color.as_rgba_u32() & 0xff == 0 {
+                continue;
+            }
+            if let (Some(y0), Some(y1)) = (
+                measure.first_pixel_from_vis(vis_row),
+                measure.far_pixel_from_vis(vis_row),
+            ) {
+                ctx.fill(Rect::new(x0, y0, x1, y1), color);
+            }
+        }
+    }
 
+    // Repaints the frozen columns over whatever has scrolled under them
+    fn paint_frozen(
+        &mut self,
+        ctx: &mut PaintCtx,
+        data: &TableState<TableData>,
+        env: &Env,
+        rtc: &ResolvedTableConfig,
+        rows: (VisIdx, VisIdx),
+        draw_rect: &Rect,
+    ) -> Option<()> {
+        let (shift, width) = data.frozen_band(TableAxis::Columns, rtc.frozen_columns)?;
+        if shift > 0. {
+            let cell_rect = CellRect::new(rows, (VisIdx(0), VisIdx(rtc.frozen_columns - 1)));
+            ctx.with_save(|ctx| {
+                ctx.transform(Affine::translate((shift, 0.)));
+                let band = Rect::new(0., draw_rect.y0, width, draw_rect.y1);
+                ctx.clip(band);
+                ctx.fill(band, &rtc.cells_background);
+                Self::paint_stripes(ctx, data, rtc, rows, (0., width));
+                self.paint_cells(ctx, data, env, &cell_rect);
+                self.paint_selections(ctx, data, rtc, &cell_rect);
+            });
+        }
         ctx.stroke(
-            CellRect::point(focus.row, focus.col).to_pixel_rect(&data.measures)?,
-            &rtc.focus_color,
-            (rtc.cell_border_thickness * 1.5).min(2.),
+            Line::new((shift + width, draw_rect.y0), (shift + width, draw_rect.y1)),
+            &rtc.frozen_divider,
+            (rtc.cell_border_thickness * 2.).max(1.),
         );
         Some(())
     }
 
-    fn paint_editing(
+    // Repaints the frozen rows over whatever has scrolled under them, and then their part in
+    // the frozen columns, which stays put both ways
+    fn paint_frozen_rows(
         &mut self,
         ctx: &mut PaintCtx,
         data: &TableState<TableData>,
         env: &Env,
+        rtc: &ResolvedTableConfig,
+        cols: (VisIdx, VisIdx),
+        draw_rect: &Rect,
     ) -> Option<()> {
-        if let Editing::Cell { single_cell, child } = &mut self.editing {
-            let vis = &single_cell.vis;
-            // TODO: excessive unwrapping
-            let rect = CellRect::point(vis.row, vis.col).to_pixel_rect(&data.measures)?;
+        let (shift, height) = data.frozen_band(TableAxis::Rows, rtc.frozen_rows)?;
+        let rows = (VisIdx(0), VisIdx(rtc.frozen_rows - 1));
+        let thickness = (rtc.cell_border_thickness * 2.).max(1.);
+        let frozen_cols = data.frozen_band(TableAxis::Columns, rtc.frozen_columns);
+        if shift > 0. {
+            let cell_rect = CellRect::new(rows, cols);
+            ctx.with_save(|ctx| {
+                ctx.transform(Affine::translate((0., shift)));
+                let band = Rect::new(draw_rect.x0, 0., draw_rect.x1, height);
+                ctx.clip(band);
+                ctx.fill(band, &rtc.cells_background);
+                Self::paint_stripes(ctx, data, rtc, rows, (band.x0, band.x1));
+                self.paint_cells(ctx, data, env, &cell_rect);
+                self.paint_selections(ctx, data, rtc, &cell_rect);
+            });
+            if let Some((shift_x, width)) = frozen_cols {
+                let corner_cols = (VisIdx(0), VisIdx(rtc.frozen_columns - 1));
+                let cell_rect = CellRect::new(rows, corner_cols);
+                ctx.with_save(|ctx| {
+                    ctx.transform(Affine::translate((shift_x, shift)));
+                    let corner = Rect::new(0., 0., width, height);
+                    ctx.clip(corner);
+                    ctx.fill(corner, &rtc.cells_background);
+                    Self::paint_stripes(ctx, data, rtc, rows, (0., width));
+                    self.paint_cells(ctx, data, env, &cell_rect);
+                    self.paint_selections(ctx, data, rtc, &cell_rect);
+                });
+                // The band covered this part of the frozen columns' divider
+                let x = shift_x + width;
+                let divider = Line::new((x, shift), (x, shift + height));
+                ctx.stroke(divider, &rtc.frozen_divider, thickness);
+            }
+        }
+        let y = shift + height;
+        let divider = Line::new((draw_rect.x0, y), (draw_rect.x1, y));
+        ctx.stroke(divider, &rtc.frozen_divider, thickness);
+        Some(())
+    }
 
+    fn paint_editing(
+        &mut self,
+        ctx: &mut PaintCtx,
+        data: &TableState<TableData>,
+        env: &Env,
+    ) -> Option<()> {
+        let frozen = self.frozen();
+        let vis = self.editing.cell()?.vis;
+        let rect = self
+            .spanned_rect(data, &vis)
+            .to_pixel_rect(&data.measures)?
+            + Self::frozen_offset(data, frozen, &vis);
+        if let Editing::Cell { single_cell, child, .. } = &mut self.editing {
             ctx.with_save(|ctx| {
                 ctx.render_ctx.clip(rect);
                 data.data
@@ -280,6 +1893,92 @@ where
 
 pub const INIT_CELLS: Selector<()> = Selector::new("druid-builtin.table.init-cells");
 pub const REMAP_CHANGED: Selector<TableAxis> = Selector::new("druid-builtin.table.remap-changed");
+// Submit to select every cell, as Ctrl+A does
+pub const SELECT_ALL: Selector<()> = Selector::new("druid-builtin.table.select-all");
+// Submit to hide rows, replacing any filter on the same column
+pub const SET_ROW_FILTER: Selector<FilterSpec> =
+    Selector::new("druid-builtin.table.set-row-filter");
+// Submit to show every row again
+pub const CLEAR_ROW_FILTERS: Selector<()> = Selector::new("druid-builtin.table.clear-row-filters");
+// Submit to group rows by the values in a logical column, or None to stop grouping
+pub const GROUP_ROWS_BY: Selector<Option<usize>> =
+    Selector::new("druid-builtin.table.group-rows-by");
+// Submit to highlight the cells matching a search, or None to stop. Submit again as the query
+// changes.
+pub const SET_SEARCH: Selector<Option<SearchSpec>> =
+    Selector::new("druid-builtin.table.set-search");
+// Submit to move the focus to the next or previous match in the order shown, as F3 and
+// Shift+F3 do. Nothing happens if no cell matches.
+pub const FIND_NEXT: Selector<()> = Selector::new("druid-builtin.table.find-next");
+pub const FIND_PREV: Selector<()> = Selector::new("druid-builtin.table.find-prev");
+// Submit to scroll as little as it takes to show a cell in full, clear of any frozen rows and
+// columns. Moving the focus from the keyboard does this itself.
+pub const ENSURE_VISIBLE: Selector<AxisPair<VisIdx>> =
+    Selector::new("druid-builtin.table.ensure-visible");
+// Submit to scroll a logical row into view, unless it is filtered out
+pub const SCROLL_TO_ROW: Selector<LogIdx> = Selector::new("druid-builtin.table.scroll-to-row");
+// Submit to scroll the last row into view. With TableConfig::stick_to_bottom, appended rows
+// keep it there.
+pub const SCROLL_TO_BOTTOM: Selector<()> = Selector::new("druid-builtin.table.scroll-to-bottom");
+// Submit to select a logical cell wherever sorting has moved it. A cell filtered out or in a
+// hidden column clears the selection rather than being revealed.
+pub const SELECT_CELL: Selector<SelectCell> = Selector::new("druid-builtin.table.select-cell");
+// Submit to hide or show a logical column. A hidden column still sorts the rows if they
+// were sorted by it; toggle its sort off first to drop that.
+pub const HIDE_COLUMN: Selector<LogIdx> = Selector::new("druid-builtin.table.hide-column");
+pub const SHOW_COLUMN: Selector<LogIdx> = Selector::new("druid-builtin.table.show-column");
+// Submit to have the table's contents sent back as CSV
+pub const EXPORT_CSV: Selector<CsvExport> = Selector::new("druid-builtin.table.export-csv");
+// Submit with a selector to have the column layout sent back with it, eg to save it. Submit
+// a layout to apply it.
+pub const EXPORT_LAYOUT: Selector<Selector<TableLayout>> =
+    Selector::new("druid-builtin.table.export-layout");
+pub const APPLY_LAYOUT: Selector<TableLayout> = Selector::new("druid-builtin.table.apply-layout");
+// Submit with a selector to have the first and last rows on screen sent back with it. See
+// TableConfig::on_visible_rows to hear as they change.
+pub const VISIBLE_ROWS: Selector<Selector<(VisIdx, VisIdx)>> =
+    Selector::new("druid-builtin.table.visible-rows");
+// Submit with a selector to have the cells' TableGeometry sent back with it, eg to draw
+// over them. It is as of the command, so ask again after scrolling or resizing.
+pub const CELL_GEOMETRY: Selector<Selector<TableGeometry>> =
+    Selector::new("druid-builtin.table.cell-geometry");
+// Submitted with a logical column and its new header when the user renames it, see
+// TableConfig::editable_headers
+pub const RENAME_COLUMN: Selector<(LogIdx, String)> =
+    Selector::new("druid-builtin.table.rename-column");
+// Submit to fit the given visual column to its content
+pub const AUTO_SIZE_COLUMN: Selector<VisIdx> =
+    Selector::new("druid-builtin.table.auto-size-column");
+
+// A cell for SELECT_CELL
+#[derive(Clone, Debug)]
+pub struct SelectCell {
+    pub cell: AxisPair<LogIdx>,
+    pub scroll: bool, // Scroll it into view
+}
+
+// What EXPORT_CSV exports, and where to
+#[derive(Clone, Debug)]
+pub struct CsvExport {
+    pub reply: Selector<String>, // Submitted with the CSV text
+    pub all_rows: bool,          // Include the rows filtered out
+}
+
+const ROW_CHANGED: Selector<LogIdx> = Selector::new("druid-builtin.table.row-changed");
+// The key that started an edit, passed on to the new editor
+const EDIT_WITH_KEY: Selector<KeyEvent> = Selector::new("druid-builtin.table.edit-with-key");
+// Sent by the cells to themselves when the env changes, to resolve it again
+const ENV_CHANGED: Selector<()> = Selector::new("druid-builtin.table.env-changed");
+// Sent by the cells to themselves on scrolling or resizing
+const VIEWPORT_MOVED: Selector<()> = Selector::new("druid-builtin.table.viewport-moved");
+const VISIBLE_ROWS_DELAY: Duration = Duration::from_millis(100);
+
+// Turns a second of the loading spinners
+const SPIN_SPEED: f64 = 1.;
+
+const FILL_HANDLE_SIZE: f64 = 6.;
+// The height the cells take with no rows, to show TableConfig::empty_message in
+const EMPTY_MESSAGE_HEIGHT: f64 = 60.;
 
 impl<TableData, ColDel> Widget<TableState<TableData>> for Cells<TableData, ColDel>
 where
@@ -294,44 +1993,139 @@ where
         data: &mut TableState<TableData>,
         env: &Env,
     ) {
+        self.track_tooltip(ctx, event, data, env);
+        self.track_visible_rows(ctx, event, data);
+        self.track_loading(ctx, event, data, env);
         if let Some(rtc) = &self.resolved_config {
             let mut new_selection: Option<TableSelection> = None;
             let mut remap_changed = AxisPair::new(false, false);
+            let mut rows_remapped = false;
+            let mut changed_row = None;
+            let mut keep_scroll = false;
 
+            let pressed = match event {
+                Event::MouseDown(me) => self.clickable_at(data, me),
+                _ => None,
+            };
             match event {
+                Event::MouseDown(me)
+                    if me.button.is_left() && self.fill_handle_at(data, &me.pos).is_some() =>
+                {
+                    let from = self.fill_handle_at(data, &me.pos);
+                    self.filling = from.map(|cell| (self.fill_block(data, &cell), cell.vis.row));
+                    ctx.set_active(true);
+                    ctx.set_handled();
+                }
+                Event::MouseDown(_) if pressed.is_some() => {
+                    if let Some(cell) = pressed {
+                        self.press(data, cell);
+                    }
+                    ctx.set_active(true);
+                    ctx.set_handled();
+                }
+                Event::MouseUp(me) if self.clicking.is_some() => {
+                    let pressed = self.clicking.take();
+                    let released = self.find_cell(data, &me.pos);
+                    if let (Some(pressed), Some(released)) = (pressed, released) {
+                        let command = self.cell_delegate.on_click(pressed.log.col);
+                        if let (Some(selector), true) = (command, pressed.log == released.log) {
+                            ctx.submit_command(selector.with(pressed.log.row));
+                        }
+                    }
+                    ctx.set_active(false);
+                    ctx.set_handled();
+                }
                 Event::MouseDown(me) => {
                     if let Some(cell) = self.find_cell(data, &me.pos) {
                         if self.editing.is_editing(&cell) {
                             self.editing.handle_event(ctx, event, &mut data.data, env);
+                        } else if me.button.is_right() {
+                            // The menu acts on the selection, so it takes in the clicked cell
+                            if !data.selection.selects_cell(&cell.vis) {
+                                new_selection = Some(cell.clone().into());
+                            }
+                            self.editing.stop_editing(&mut data.data, &mut self.spares);
+                            if let Some(show_menu) = &self.config.context_menu {
+                                show_menu(ctx, &cell.log, me.window_pos);
+                            }
+                            ctx.set_handled();
                         } else if me.count == 1 {
+                            let clicked = rtc.selection_mode.shape(cell.into());
                             if me.mods.meta() || me.mods.ctrl() {
-                                new_selection = data.selection.add_selection(cell.into());
+                                new_selection = data.selection.add_selection(clicked);
                             } else if me.mods.shift() {
-                                new_selection = data.selection.move_extent(cell.into());
+                                // The focus stays as the anchor, or else just select
+                                new_selection = data
+                                    .selection
+                                    .move_extent(clicked.clone())
+                                    .or(Some(clicked));
                             } else {
-                                new_selection = Some(cell.into());
+                                new_selection = Some(clicked);
                             }
 
                             ctx.set_handled();
-                            self.editing.stop_editing(&mut data.data);
+                            self.editing.stop_editing(&mut data.data, &mut self.spares);
                             self.dragging_selection = true;
                             ctx.set_active(true);
                         } else if me.count == 2 {
+                            let padding = Self::padding(&self.cell_delegate, rtc, cell.log.col);
+                            let size = Self::content_size(&data.measures, &cell.vis, padding)
+                                .unwrap_or(Size::ZERO);
                             let cd = &mut self.cell_delegate;
                             self.editing.start_editing(
                                 ctx,
                                 &mut data.data,
                                 &cell,
-                                |cell_ctx| cd.make_editor(cell_ctx),
+                                size,
+                                &mut self.spares,
+                                cd,
                             );
                         }
                     }
                 }
+                Event::MouseMove(me) if self.filling.is_some() => {
+                    let to = self.fill_target(data, me.pos.y);
+                    if let (Some((_, row)), Some(to)) = (&mut self.filling, to) {
+                        *row = to;
+                    }
+                    ctx.request_paint();
+                }
+                Event::MouseUp(_) if self.filling.is_some() => {
+                    if let Some((block, to)) = self.filling.take() {
+                        self.fill_rows(data, &block, to);
+                        // The filled rows end up selected
+                        let reach = Self::fill_reach(&block, to);
+                        let corner = |vis: AxisPair<VisIdx>| {
+                            let log = data.remaps.get_log_cell(&vis)?;
+                            Some(SingleCell::new(vis, log))
+                        };
+                        let first = corner(AxisPair::new(reach.start_row, reach.start_col));
+                        let last = corner(AxisPair::new(reach.end_row, reach.end_col));
+                        if let (Some(first), Some(last)) = (first, last) {
+                            new_selection = TableSelection::from(first).move_extent(last.into());
+                        }
+                    }
+                    ctx.set_active(false);
+                    ctx.request_paint();
+                }
                 Event::MouseMove(me) if !self.editing.is_active() && self.dragging_selection => {
                     if let Some(cell) = self.find_cell(data, &me.pos) {
                         new_selection = data.selection.move_extent(cell.into());
                     }
                 }
+                Event::AnimFrame(nanos) if self.animation.is_running() => {
+                    if self.animation.rows.is_empty() {
+                        ctx.request_paint();
+                    } else {
+                        ctx.request_layout();
+                    }
+                    let duration = rtc.animation_duration.as_secs_f64();
+                    let step = *nanos as f64 / 1e9;
+                    let rows = &mut data.measures[TableAxis::Rows];
+                    if self.animation.advance(step, duration, rows) {
+                        ctx.request_anim_frame();
+                    }
+                }
                 Event::MouseUp(_) if self.dragging_selection => {
                     self.dragging_selection = false;
                     ctx.set_active(false);
@@ -344,18 +2138,178 @@ where
                     } else if let Some(ax) = cmd.get(REMAP_CHANGED) {
                         log::info!("Remap changed:{:?}", ax);
                         remap_changed[*ax] = true;
-                    } else if let Editing::Cell { single_cell, child } = &mut self.editing {
+                    } else if cmd.is(VIEWPORT_MOVED) {
+                        let before = data.measures[TableAxis::Rows].clone();
+                        let rows = self.rows_in_view(data);
+                        self.measure_rows(ctx.text(), data, rtc, env, false, rows);
+                        if !before.same(&data.measures[TableAxis::Rows]) {
+                            ctx.request_layout();
+                        }
+                    } else if cmd.is(ENV_CHANGED) {
+                        // Column widths can come from the env, eg a theme's density
+                        let measure = &mut data.measures[TableAxis::Columns];
+                        measure.set_log_limits(self.cell_delegate.column_width_limits(env));
+                        measure.set_log_initial(self.cell_delegate.column_initial_widths(env));
+                        ctx.request_layout();
+                    } else if let Some(log_idx) = cmd.get(ROW_CHANGED) {
+                        changed_row = Some(*log_idx);
+                        match self.cell_delegate.remap_item_changed(
+                            &data.data,
+                            &data.remap_specs[TableAxis::Rows],
+                            &data.remaps[TableAxis::Rows],
+                            *log_idx,
+                            env,
+                        ) {
+                            Some(remap) => {
+                                data.remaps[TableAxis::Rows] = remap;
+                                rows_remapped = true;
+                            }
+                            None => remap_changed[TableAxis::Rows] = true,
+                        }
+                    } else if let Some(filter) = cmd.get(SET_ROW_FILTER) {
+                        // The remap happens in update. Visual indices are about to change, so
+                        // a selection made through them would now point at other rows.
+                        data.remap_specs[TableAxis::Rows].set_filter(filter.clone());
+                        new_selection = Some(TableSelection::NoSelection);
+                    } else if cmd.get(CLEAR_ROW_FILTERS).is_some() {
+                        data.remap_specs[TableAxis::Rows].clear_filters();
+                        new_selection = Some(TableSelection::NoSelection);
+                    } else if let Some(group_by) = cmd.get(GROUP_ROWS_BY) {
+                        data.remap_specs[TableAxis::Rows].set_group_by(*group_by);
+                        new_selection = Some(TableSelection::NoSelection);
+                    } else if let Some(log_idx) = cmd.get(HIDE_COLUMN) {
+                        data.remap_specs[TableAxis::Columns].hide(*log_idx);
+                        remap_changed[TableAxis::Columns] = true;
+                        new_selection = Some(TableSelection::NoSelection);
+                    } else if let Some(log_idx) = cmd.get(SHOW_COLUMN) {
+                        data.remap_specs[TableAxis::Columns].show(*log_idx);
+                        remap_changed[TableAxis::Columns] = true;
+                        new_selection = Some(TableSelection::NoSelection);
+                    } else if let Some(ke) = cmd.get(EDIT_WITH_KEY) {
+                        let key_down = Event::KeyDown(ke.clone());
+                        self.editing
+                            .handle_event(ctx, &key_down, &mut data.data, env);
+                    } else if let Some(export) = cmd.get(EXPORT_CSV) {
+                        let csv = self.to_csv(data, env, rtc, export.all_rows);
+                        ctx.submit_command(export.reply.with(csv));
+                    } else if let Some(reply) = cmd.get(VISIBLE_ROWS) {
+                        ctx.submit_command(reply.with(self.visible_rows(data)));
+                    } else if let Some((col, header)) = cmd.get(RENAME_COLUMN) {
+                        self.cell_delegate.rename_column(*col, header.clone());
+                    } else if let Some(reply) = cmd.get(CELL_GEOMETRY) {
+                        ctx.submit_command(reply.with(data.geometry(self.frozen())));
+                    } else if let Some(reply) = cmd.get(EXPORT_LAYOUT) {
+                        let columns = self.cell_delegate.number_of_columns_in_data(&data.data);
+                        ctx.submit_command(reply.with(data.export_layout(columns)));
+                    } else if let Some(layout) = cmd.get(APPLY_LAYOUT) {
+                        let columns = self.cell_delegate.number_of_columns_in_data(&data.data);
+                        data.apply_layout(layout, columns);
+                        remap_changed = AxisPair::new(true, true);
+                    } else if cmd.get(SELECT_ALL).is_some() {
+                        new_selection = Some(self.select_all(data));
+                    } else if let Some(vis) = cmd.get(ENSURE_VISIBLE) {
+                        let rect = self.spanned_rect(data, vis);
+                        data.ensure_visible(&rect, self.viewport, self.frozen());
+                    } else if let Some(log_row) = cmd.get(SCROLL_TO_ROW) {
+                        if let Some(vis_row) = data.remaps[TableAxis::Rows].get_vis_idx(*log_row) {
+                            let (frozen, height) = (self.frozen().row, self.viewport.height);
+                            let rows = (vis_row, vis_row);
+                            data.ensure_visible_on(TableAxis::Rows, rows, height, frozen);
+                        }
+                    } else if cmd.is(SCROLL_TO_BOTTOM) {
+                        self.scroll_to_bottom(data);
+                    } else if let Some(select) = cmd.get(SELECT_CELL) {
+                        let vis = select
+                            .cell
+                            .zip_with(&data.remaps, |log, remap| remap.get_vis_idx(*log))
+                            .opt();
+                        new_selection = Some(match vis {
+                            Some(vis) => SingleCell::new(vis, select.cell).into(),
+                            None => TableSelection::NoSelection,
+                        });
+                        keep_scroll = !select.scroll;
+                    } else if let Some(search) = cmd.get(SET_SEARCH) {
+                        self.search = search.clone();
+                        if self.search.as_ref().map_or(false, |search| search.jump) {
+                            let found = self.find_match(data, env, false, false);
+                            new_selection = found.map(TableSelection::SingleCell);
+                        }
+                        ctx.request_paint();
+                    } else if cmd.get(FIND_NEXT).is_some() || cmd.get(FIND_PREV).is_some() {
+                        let found = self.find_match(data, env, cmd.get(FIND_PREV).is_some(), true);
+                        new_selection = found.map(TableSelection::SingleCell);
+                    } else if let Some(vis_col) = cmd.get(AUTO_SIZE_COLUMN) {
+                        self.auto_size_column(ctx.text(), data, rtc, env, *vis_col);
+                        // Wrapped rows depend on the column width
+                        let rows = self.rows_in_view(data);
+                        let resized =
+                            self.measure_rows(ctx.text(), data, rtc, env, rtc.animate, rows);
+                        if !resized.is_empty() {
+                            self.animation.resize_rows(resized);
+                            ctx.request_anim_frame();
+                        }
+                        ctx.request_layout();
+                    } else if let Editing::Cell { single_cell, child, .. } = &mut self.editing {
                         data.data.with_mut(single_cell.log.row, |row| {
                             child.event(ctx, event, row, env)
                         });
                     }
                 }
+                Event::KeyDown(ke) if ke.key == KbKey::Tab => {
+                    let backwards = ke.mods.shift();
+                    let lens = self.vis_lens(data);
+                    let cd = &mut self.cell_delegate;
+                    let next = data.selection.focus().and_then(|from| {
+                        Self::next_editable_cell(cd, data, lens, from, backwards, rtc)
+                    });
+                    // At either end of the table the focus, and any edit, stays where it is
+                    if let Some(next) = next {
+                        if self.editing.is_active() {
+                            let padding = Self::padding(cd, rtc, next.log.col);
+                            let size = Self::content_size(&data.measures, &next.vis, padding)
+                                .unwrap_or(Size::ZERO);
+                            self.editing.start_editing(
+                                ctx,
+                                &mut data.data,
+                                &next,
+                                size,
+                                &mut self.spares,
+                                cd,
+                            );
+                        }
+                        new_selection = Some(next.into());
+                        ctx.request_paint();
+                    }
+                    ctx.set_handled();
+                }
+                Event::KeyDown(ke)
+                    if self.editing.is_active()
+                        && matches!(ke.key, KbKey::Enter | KbKey::Escape) =>
+                {
+                    if ke.key == KbKey::Escape {
+                        self.editing
+                            .cancel_editing(&mut data.data, &mut self.spares);
+                    } else {
+                        // Committed as it was typed, so just move on to the next row
+                        self.editing.stop_editing(&mut data.data, &mut self.spares);
+                        new_selection = data.selection.move_focus(
+                            TableAxis::Rows,
+                            self.span_step(data, TableAxis::Rows, false),
+                            self.vis_lens(data),
+                            &data.remaps,
+                        );
+                    }
+                    ctx.children_changed();
+                    ctx.request_paint();
+                    ctx.set_handled();
+                }
                 Event::KeyDown(ke) if !self.editing.is_active() => {
                     match &ke.key {
                         KbKey::ArrowDown => {
                             new_selection = data.selection.move_focus(
                                 TableAxis::Rows,
-                                VisOffset(1),
+                                self.span_step(data, TableAxis::Rows, false),
+                                self.vis_lens(data),
                                 &data.remaps,
                             );
                             ctx.set_handled();
@@ -363,7 +2317,8 @@ where
                         KbKey::ArrowUp => {
                             new_selection = data.selection.move_focus(
                                 TableAxis::Rows,
-                                VisOffset(-1),
+                                self.span_step(data, TableAxis::Rows, true),
+                                self.vis_lens(data),
                                 &data.remaps,
                             );
                             ctx.set_handled();
@@ -371,7 +2326,8 @@ where
                         KbKey::ArrowRight => {
                             new_selection = data.selection.move_focus(
                                 TableAxis::Columns,
-                                VisOffset(1),
+                                self.span_step(data, TableAxis::Columns, false),
+                                self.vis_lens(data),
                                 &data.remaps,
                             );
                             ctx.set_handled();
@@ -379,11 +2335,49 @@ where
                         KbKey::ArrowLeft => {
                             new_selection = data.selection.move_focus(
                                 TableAxis::Columns,
-                                VisOffset(-1),
+                                self.span_step(data, TableAxis::Columns, true),
+                                self.vis_lens(data),
                                 &data.remaps,
                             );
                             ctx.set_handled();
                         }
+                        KbKey::Home => {
+                            let to_corner = ke.mods.ctrl() || ke.mods.meta();
+                            new_selection = self.jump_focus(data, |focus, _| {
+                                let row = if to_corner { VisIdx(0) } else { focus.row };
+                                AxisPair::new(row, VisIdx(0))
+                            });
+                            ctx.set_handled();
+                        }
+                        KbKey::End => {
+                            let to_corner = ke.mods.ctrl() || ke.mods.meta();
+                            new_selection = self.jump_focus(data, |focus, last| {
+                                let row = if to_corner { last.row } else { focus.row };
+                                AxisPair::new(row, last.col)
+                            });
+                            ctx.set_handled();
+                        }
+                        KbKey::PageUp => {
+                            let page = self.page_rows(data);
+                            new_selection = self.jump_focus(data, |focus, _| {
+                                AxisPair::new(VisIdx(focus.row.0.saturating_sub(page)), focus.col)
+                            });
+                            ctx.set_handled();
+                        }
+                        KbKey::PageDown => {
+                            let page = self.page_rows(data);
+                            new_selection = self.jump_focus(data, |focus, _| {
+                                AxisPair::new(VisIdx(focus.row.0 + page), focus.col)
+                            });
+                            ctx.set_handled();
+                        }
+                        KbKey::Character(s)
+                            if s.eq_ignore_ascii_case("a")
+                                && (ke.mods.ctrl() || ke.mods.meta()) =>
+                        {
+                            new_selection = Some(self.select_all(data));
+                            ctx.set_handled();
+                        }
                         KbKey::Character(s) if s == " " => {
                             // This is to match Excel
                             if ke.mods.meta() || ke.mods.ctrl() {
@@ -400,26 +2394,90 @@ where
 
                             // TODO - when Ctrl + Shift, select full grid
                         }
-                        KbKey::Copy => log::info!("Copy"),
+                        // Ctrl+Shift+C copies the headings too
+                        KbKey::Character(s)
+                            if s.eq_ignore_ascii_case("c")
+                                && (ke.mods.ctrl() || ke.mods.meta()) =>
+                        {
+                            self.copy_selection(data, env, rtc, ke.mods.shift());
+                            ctx.set_handled();
+                        }
+                        KbKey::Copy => {
+                            self.copy_selection(data, env, rtc, false);
+                            ctx.set_handled();
+                        }
+                        KbKey::F3 => {
+                            let found = self.find_match(data, env, ke.mods.shift(), true);
+                            new_selection = found.map(TableSelection::SingleCell);
+                            ctx.set_handled();
+                        }
+                        // With type ahead, keys that would start editing look for a row instead
+                        KbKey::Character(typed) if self.config.type_ahead && opens_editor(ke) => {
+                            let (prefix, skip_focus) = self.type_ahead.push(typed, Instant::now());
+                            if let Some(row) = self.type_ahead_row(data, env, &prefix, skip_focus) {
+                                new_selection =
+                                    self.jump_focus(data, |focus, _| AxisPair::new(row, focus.col));
+                            }
+                            ctx.set_handled();
+                        }
+                        _ if opens_editor(ke) => {
+                            if let Some(cell) = data.selection.focus().cloned() {
+                                let padding = Self::padding(&self.cell_delegate, rtc, cell.log.col);
+                                let size = Self::content_size(&data.measures, &cell.vis, padding)
+                                    .unwrap_or(Size::ZERO);
+                                let cd = &mut self.cell_delegate;
+                                self.editing.start_editing(
+                                    ctx,
+                                    &mut data.data,
+                                    &cell,
+                                    size,
+                                    &mut self.spares,
+                                    cd,
+                                );
+                                // The editor can only take the key once it has been added
+                                if self.editing.is_active() && ke.key != KbKey::F2 {
+                                    let replay = EDIT_WITH_KEY.with(ke.clone());
+                                    ctx.submit_command(replay.to(ctx.widget_id()));
+                                }
+                            }
+                            ctx.set_handled();
+                        }
                         k => log::info!("Key {:?}", k),
                     }
                 }
-                _ => if let Editing::Cell { single_cell, child } = &mut self.editing {
+                _ => if let Editing::Cell { single_cell, child, .. } = &mut self.editing {
                     data.data
                         .with_mut(single_cell.log.row, |row| child.event(ctx, event, row, env));
                 },
             }
 
             if let Some(sel) = new_selection {
-                data.selection = sel;
+                // Heading clicks select slices themselves, so only cells are shaped here
+                data.selection = rtc.selection_mode.shape(sel);
                 if data.selection.has_focus() && !self.editing.is_active() {
                     ctx.request_focus();
                 }
+                // A cell clicked on is already in view, and scrolling would fight a drag
+                let from_mouse = matches!(event, Event::MouseDown(_) | Event::MouseMove(_));
+                let scroll = !from_mouse && !keep_scroll;
+                if let (Some(focus), true) = (data.selection.vis_focus().copied(), scroll) {
+                    let rect = self.spanned_rect(data, &focus);
+                    data.ensure_visible(&rect, self.viewport, self.frozen());
+                }
             }
 
+            // Measured before any appended rows are
+            let pinned = self.pinned_length(data);
+
             // TODO: move to update but need versioned pointers on measures
             if remap_changed[TableAxis::Rows] {
-                data.remap_axis(TableAxis::Rows, |d, s| self.cell_delegate.remap_items(d, s));
+                data.remap_axis(TableAxis::Rows, |d, s, prior| {
+                    self.cell_delegate.remap_items(d, s, prior, env)
+                });
+                rows_remapped = true;
+            }
+            if rows_remapped {
+                data.footer = self.footer_values(data, env);
                 data.measures[TableAxis::Rows].set_axis_properties(
                     rtc.cell_border_thickness,
                     data.data.idx_len(),
@@ -428,7 +2486,7 @@ where
                 ctx.request_layout(); // Could avoid if we know we overflow scroll?
             }
             if remap_changed[TableAxis::Columns] {
-                data.remap_axis(TableAxis::Columns, |d, s| {
+                data.remap_axis(TableAxis::Columns, |d, s, _| {
                     s.remap_placements(LogIdx(self.cell_delegate.number_of_columns_in_data(d) - 1))
                     // TODO check for none
                 });
@@ -438,8 +2496,24 @@ where
                     self.cell_delegate.number_of_columns_in_data(&data.data),
                     &data.remaps[TableAxis::Columns],
                 );
+                data.measures[TableAxis::Columns]
+                    .set_log_limits(self.cell_delegate.column_width_limits(env));
+                data.measures[TableAxis::Columns]
+                    .set_log_initial(self.cell_delegate.column_initial_widths(env));
+                data.measures[TableAxis::Columns]
+                    .set_log_flex(self.cell_delegate.column_flex_weights());
+                data.measures[TableAxis::Columns].set_reversed(rtc.right_to_left);
                 ctx.request_layout();
             }
+            if rows_remapped || remap_changed[TableAxis::Columns] {
+                // Rows scrolled to later are measured then
+                let mut rows = self.rows_in_view(data);
+                rows.extend(
+                    changed_row.and_then(|log| data.remaps[TableAxis::Rows].get_vis_idx(log)),
+                );
+                self.measure_rows(ctx.text(), data, rtc, env, false, rows);
+            }
+            self.follow_appended(data, pinned);
             // Todo remap cols
         }
     }
@@ -451,10 +2525,22 @@ where
         data: &TableState<TableData>,
         env: &Env,
     ) {
+        if let LifeCycle::HotChanged(false) = event {
+            if let Some(window) = self.tooltip.dismiss() {
+                ctx.submit_command(CLOSE_WINDOW.to(window));
+            }
+        }
+        // The paint region can be just a damaged part, so the view comes from the scroll. It
+        // changes size as whatever holds the table is resized, not just the window.
+        if let LifeCycle::ViewContextChanged(view) = event {
+            if self.resize_viewport(view.clip.size()) {
+                ctx.submit_command(Command::new(VIEWPORT_MOVED, (), ctx.widget_id()));
+            }
+        }
         if let LifeCycle::WidgetAdded = event {
             self.resolved_config = Some(self.config.resolve(env));
             ctx.submit_command(Command::new(INIT_CELLS, (), ctx.widget_id()));
-        } else if let Editing::Cell { single_cell, child } = &mut self.editing {
+        } else if let Editing::Cell { single_cell, child, .. } = &mut self.editing {
             log::info!("LC event {:?}", event);
             data.data.with(single_cell.log.row, |row| {
                 child.lifecycle(ctx, event, row, env)
@@ -467,17 +2553,53 @@ where
         ctx: &mut UpdateCtx,
         old_data: &TableState<TableData>,
         data: &TableState<TableData>,
-        _env: &Env,
+        env: &Env,
     ) {
+        // Keeps an open editor in step with its row, including a reused one just given it
+        if let Editing::Cell { single_cell, child, .. } = &mut self.editing {
+            data.data.with(single_cell.log.row, |row| child.update(ctx, row, env));
+        }
+
+        if ctx.env_changed() {
+            ctx.submit_command(Command::new(ENV_CHANGED, (), ctx.widget_id()));
+        }
+
+        // Rows coming into view are measured, and reported if anyone is watching
+        let rows_moved = old_data.scroll_y != data.scroll_y || !old_data.data.same(&data.data);
+        if rows_moved {
+            ctx.submit_command(Command::new(VIEWPORT_MOVED, (), ctx.widget_id()));
+        }
+
+        let scrolled = old_data.scroll_x != data.scroll_x || old_data.scroll_y != data.scroll_y;
+        // Cells may have started loading, or come into view while they are
+        let changed = scrolled || !old_data.data.same(&data.data);
+        if changed && !self.spinning && self.any_loading(data, env) {
+            self.spinning = true;
+            ctx.request_anim_frame();
+        }
+
+        // The tooltip belongs to a cell that may have moved or changed
+        if scrolled || !old_data.data.same(&data.data) || !old_data.remaps.same(&data.remaps) {
+            if let Some(window) = self.tooltip.dismiss() {
+                ctx.submit_command(CLOSE_WINDOW.to(window));
+            }
+        }
+
         // TODO move all sorting up to table level so we don't need commands
-        if !old_data.data.same(&data.data)
-            || !old_data.remap_specs[TableAxis::Rows].same(&data.remap_specs[TableAxis::Rows])
-        {
-            ctx.submit_command(Command::new(
-                REMAP_CHANGED,
-                TableAxis::Rows,
-                ctx.widget_id(),
-            ));
+        let same_row_spec =
+            old_data.remap_specs[TableAxis::Rows].same(&data.remap_specs[TableAxis::Rows]);
+        if !old_data.data.same(&data.data) || !same_row_spec {
+            // An edit to one row can just move that row
+            match Self::only_changed_row(&old_data.data, &data.data).filter(|_| same_row_spec) {
+                Some(log_idx) => {
+                    ctx.submit_command(Command::new(ROW_CHANGED, log_idx, ctx.widget_id()))
+                }
+                None => ctx.submit_command(Command::new(
+                    REMAP_CHANGED,
+                    TableAxis::Rows,
+                    ctx.widget_id(),
+                )),
+            }
         }
 
         if !old_data.remap_specs[TableAxis::Columns].same(&data.remap_specs[TableAxis::Columns]) {
@@ -489,8 +2611,29 @@ where
         }
 
         if !old_data.selection.same(&data.selection) {
+            if let Some(selector) = self.config.on_selection {
+                ctx.submit_command(selector.with(data.selection.clone()));
+            }
+            if let Some(rtc) = self.resolved_config.as_ref().filter(|rtc| rtc.animate) {
+                let pixel_focus = |sel: &TableSelection| {
+                    let focus = sel.vis_focus()?;
+                    CellRect::point(focus.row, focus.col).to_pixel_rect(&data.measures)
+                };
+                let duration = rtc.animation_duration.as_secs_f64();
+                if let (Some(from), Some(to)) =
+                    (pixel_focus(&old_data.selection), pixel_focus(&data.selection))
+                {
+                    self.animation.slide_focus(from, to, duration);
+                    ctx.request_anim_frame();
+                }
+            }
             ctx.request_paint();
         }
+
+        // Resized from the headings
+        if !old_data.measures.same(&data.measures) {
+            ctx.request_layout();
+        }
         //TODO Columns update from data
     }
 
@@ -499,38 +2642,40 @@ where
         ctx: &mut LayoutCtx,
         bc: &BoxConstraints,
         data: &TableState<TableData>,
-        _env: &Env,
+        env: &Env,
     ) -> Size {
         bc.debug_check("TableCells");
 
-        if let Editing::Cell { single_cell, child } = &mut self.editing {
-            let vis = &single_cell.vis;
-            (|| -> Option<_> {
-                // let bc = BoxConstraints::tight(
-                //     data.measures
-                //         .zip_with(&vis, |m, v| m.pixels_length_for_vis(*v))
-                //         .opt()?
-                //         .size(),
-                // );
-                let origin = data
-                    .measures
-                    .zip_with(vis, |m, v| m.first_pixel_from_vis(*v))
-                    .opt()?
-                    .point();
-                // data.data.with(single_cell.log.row, |row| {
-                    // let size = child.layout(ctx, &bc, row, env);
-                    // child.set_layout_rect(ctx, row, env, Rect::from_origin_size(origin, size))
-                    child.set_origin(ctx, origin);
-                // });
-                Some(())
-            })();
-        }
-        let measured = self.measured_size(&data.measures);
-        
+        let frozen = self.frozen();
+        let editor_rect = self.editing.cell().and_then(|cell| {
+            // The editor covers the cell, following it when frozen
+            let rect = self
+                .spanned_rect(data, &cell.vis)
+                .to_pixel_rect(&data.measures)?;
+            Some(rect + Self::frozen_offset(data, frozen, &cell.vis))
+        });
+        if let Editing::Cell { single_cell, child, .. } = &mut self.editing {
+            if let Some(rect) = editor_rect {
+                let bc = BoxConstraints::tight(rect.size());
+                data.data
+                    .with(single_cell.log.row, |row| child.layout(ctx, &bc, row, env));
+                child.set_origin(ctx, rect.origin());
+            }
+        }
+        let mut measured = self.measured_size(&data.measures);
+        if self.shows_empty_message(data) {
+            // No rows to give the cells any height
+            measured.height = measured.height.max(EMPTY_MESSAGE_HEIGHT);
+        }
         bc.constrain(measured)
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, data: &TableState<TableData>, env: &Env) {
+        let env = &env
+            .clone()
+            .adding(RIGHT_TO_LEFT, self.config.right_to_left)
+            .adding(CACHE_TEXT_LAYOUTS, self.config.cache_text_layouts)
+            .adding(LOADING_PHASE, self.loading_phase);
         self.cell_delegate.init(ctx, env); // TODO reduce calls? Invalidate on some changes
 
         let rtc = self.config.resolve(env);
@@ -541,17 +2686,25 @@ where
             self.measured_size(&data.measures),
         ));
 
-        ctx.fill(draw_rect, &rtc.cells_background);
-
         let cell_rect = CellRect::new(
             data.measures[TableAxis::Rows].vis_range_from_pixels(draw_rect.y0, draw_rect.y1),
             data.measures[TableAxis::Columns].vis_range_from_pixels(draw_rect.x0, draw_rect.x1),
         );
+        let rows = (cell_rect.start_row, cell_rect.end_row);
+
+        ctx.fill(draw_rect, &rtc.cells_background);
+        Self::paint_stripes(ctx, data, &rtc, rows, (draw_rect.x0, draw_rect.x1));
 
         self.paint_cells(ctx, data, env, &cell_rect);
         self.paint_selections(ctx, data, &rtc, &cell_rect);
+        self.paint_frozen(ctx, data, env, &rtc, rows, &draw_rect);
+        let cols = (cell_rect.start_col, cell_rect.end_col);
+        self.paint_frozen_rows(ctx, data, env, &rtc, cols, &draw_rect);
 
         self.paint_editing(ctx, data, env);
+        if self.shows_empty_message(data) {
+            self.paint_empty_message(ctx, &rtc, env, &rect);
+        }
     }
 }
 
@@ -563,3 +2716,475 @@ where
 {
     bindable_self_body!();
 }
+
+#[cfg(test)]
+mod test {
+    use crate::axis_measure::{AxisPair, LogIdx, TableAxis, VisIdx};
+    use crate::cells::{
+        copied_headers, csv_field, csv_line, Editing, SpareEditors, TypeAhead, TYPE_AHEAD_TIMEOUT,
+    };
+    use crate::columns::{column, CellDelegate, ProvidedColumns};
+    use crate::data::{IndexedItems, RemapDetails, SearchSpec, SortDirection, SortSpec};
+    use crate::selection::{CellRange, CellRect, SingleCell, TableSelection};
+    use crate::table::TableState;
+    use crate::{AxisMeasure, AxisMeasurementType, CellRenderExt, Cells, Remap};
+    use crate::{CellCtx, EditorFactory, NumberCell, TableConfig, TextCell};
+    use druid::im::Vector;
+    use druid::piet::{Device, RenderContext};
+    use druid::widget::TextBox;
+    use druid::{lens, Data, Env, Modifiers, MouseButton, MouseButtons, MouseEvent, Point};
+    use druid::{Selector, Size, Vec2, Widget, WidgetPod};
+    use std::collections::HashMap;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("plain", ','), "plain");
+        assert_eq!(csv_field("a,b", ','), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\"", ','), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines", ','), "\"two\nlines\"");
+        let fields = vec!["1".to_string(), String::new(), "x,y".to_string()];
+        assert_eq!(csv_line(&fields, ','), "1,,\"x,y\"");
+        assert_eq!(csv_line(&fields, '\t'), "1\t\tx,y");
+    }
+
+    #[test]
+    fn copied_cells_are_quoted_when_needed() {
+        type Pair = (String, String);
+        let cols = ProvidedColumns::new(vec![
+            column("Name", TextCell::new().lens(lens!(Pair, 0))),
+            column("Notes", TextCell::new().lens(lens!(Pair, 1))),
+        ]);
+        let cells = Cells::new(TableConfig::new(), cols);
+        let env = Env::default();
+        let rtc = cells.config.resolve(&env);
+        let rows = vec![
+            ("tab\there".to_string(), "plain".to_string()),
+            ("two\nlines".to_string(), "say \"hi\"".to_string()),
+        ];
+        let mut measures = AxisPair::new(
+            AxisMeasure::new(AxisMeasurementType::Individual, 20.),
+            AxisMeasure::new(AxisMeasurementType::Individual, 100.),
+        );
+        measures[TableAxis::Rows].set_axis_properties(0., 2, &Remap::Pristine);
+        measures[TableAxis::Columns].set_axis_properties(0., 2, &Remap::Pristine);
+        let mut data = TableState::new(Vector::from(rows), measures);
+        let at = |row, col| {
+            let vis = AxisPair::new(VisIdx(row), VisIdx(col));
+            SingleCell::new(vis, AxisPair::new(LogIdx(row), LogIdx(col)))
+        };
+        data.selection = TableSelection::CellRange(CellRange::new(at(0, 0), at(1, 1)));
+
+        let text = cells.selection_as_text(&data, &env, &rtc, false);
+        let expected = "\"tab\there\"\tplain\n\"two\nlines\"\t\"say \"\"hi\"\"\"";
+        assert_eq!(text.as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn spans_are_only_walked_when_columns_span() {
+        let names = |count| (0..count).map(|i| column(i.to_string(), TextCell::new()));
+        let plain: ProvidedColumns<Vector<String>, _> = ProvidedColumns::new(names(500).collect());
+        let cells = Cells::new(TableConfig::new(), plain);
+        let row = String::new();
+        let spans = cells.row_spans(&row, &Remap::Pristine, 500, (VisIdx(498), VisIdx(510)));
+        let (second_last, last) = (VisIdx(498), VisIdx(499));
+        assert_eq!(spans, vec![(second_last, second_last), (last, last)]);
+
+        let mut spanning: Vec<_> = names(5).collect();
+        spanning[0] = column("Wide", TextCell::new()).col_span(|_| 3);
+        let cells = Cells::new(TableConfig::new(), ProvidedColumns::new(spanning));
+        let spans = cells.row_spans(&row, &Remap::Pristine, 5, (VisIdx(1), VisIdx(3)));
+        assert_eq!(spans, vec![(VisIdx(0), VisIdx(2)), (VisIdx(3), VisIdx(3))]);
+    }
+
+    #[test]
+    fn measured_rows_grow_and_shrink_back() {
+        let cols = ProvidedColumns::new(vec![column("Notes", TextCell::new().wrap(true))]);
+        let cells = Cells::new(TableConfig::new(), cols);
+        let env = Env::default();
+        let rtc = cells.config.resolve(&env);
+        let long = "a note long enough to wrap onto a few lines".to_string();
+        let mut measures = AxisPair::new(
+            AxisMeasure::new(AxisMeasurementType::Individual, 20.),
+            AxisMeasure::new(AxisMeasurementType::Individual, 60.),
+        );
+        measures[TableAxis::Rows].set_axis_properties(0., 2, &Remap::Pristine);
+        measures[TableAxis::Columns].set_axis_properties(0., 1, &Remap::Pristine);
+        let mut data = TableState::new(Vector::from(vec![long.clone(), long]), measures);
+
+        let mut device = Device::new().expect("device");
+        let mut target = device.bitmap_target(10, 10, 1.0).expect("target");
+        let mut piet = target.render_context();
+        let both = || vec![VisIdx(0), VisIdx(1)];
+        cells.measure_rows(piet.text(), &mut data, &rtc, &env, false, both());
+        let height =
+            |data: &TableState<_>, row| data.measures[TableAxis::Rows].pixels_length_for_vis(row);
+        assert!(height(&data, VisIdx(0)).unwrap() > 20.);
+        // Sized by hand, so only ever grown
+        data.measures[TableAxis::Rows].set_pixel_length_for_vis(VisIdx(1), 200.);
+
+        data.data = Vector::from(vec!["short".to_string(), "short".to_string()]);
+        cells.measure_rows(piet.text(), &mut data, &rtc, &env, false, both());
+        assert_eq!(height(&data, VisIdx(0)), Some(20.));
+        assert_eq!(height(&data, VisIdx(1)), Some(200.));
+        piet.finish().expect("finish");
+    }
+
+    #[test]
+    fn equal_rows_merge_but_are_clicked_apart() {
+        let cols = ProvidedColumns::new(vec![column("Team", TextCell::new()).merge_equal()]);
+        let cells = Cells::new(TableConfig::new(), cols);
+        let teams = ["red", "red", "red", "blue", "red"]
+            .iter()
+            .map(|t| t.to_string());
+        let mut measures = AxisPair::new(
+            AxisMeasure::new(AxisMeasurementType::Individual, 20.),
+            AxisMeasure::new(AxisMeasurementType::Individual, 100.),
+        );
+        measures[TableAxis::Rows].set_axis_properties(0., 5, &Remap::Pristine);
+        measures[TableAxis::Columns].set_axis_properties(0., 1, &Remap::Pristine);
+        let mut data = TableState::new(teams.collect::<Vector<_>>(), measures);
+
+        let merged = |data: &TableState<_>, row| cells.merged_rows(data, VisIdx(row), LogIdx(0));
+        assert_eq!(merged(&data, 1), (VisIdx(0), VisIdx(2)));
+        assert_eq!(merged(&data, 3), (VisIdx(3), VisIdx(3)));
+        assert_eq!(merged(&data, 4), (VisIdx(4), VisIdx(4)));
+
+        // The bottom row of the block is picked out, though the block is drawn as one cell
+        let cell = cells.find_cell(&data, &Point::new(50., 45.)).unwrap();
+        assert_eq!(cell.vis, AxisPair::new(VisIdx(2), VisIdx(0)));
+        let rect = cells.spanned_rect(&data, &cell.vis);
+        assert_eq!((rect.start_row, rect.end_row), (VisIdx(0), VisIdx(2)));
+
+        data.data.set(1, "blue".to_string());
+        assert_eq!(merged(&data, 2), (VisIdx(2), VisIdx(2)));
+    }
+
+    #[test]
+    fn editors_are_only_made_without_a_spare() {
+        let mut spares: SpareEditors<String> = HashMap::new();
+        spares.insert(LogIdx(0), WidgetPod::new(Box::new(TextBox::new())));
+        let taken = Editing::take_editor(&mut spares, LogIdx(0), || panic!("Made an editor"));
+        assert!(matches!(taken, Some((_, true))));
+        assert!(spares.is_empty());
+
+        assert!(Editing::take_editor(&mut spares, LogIdx(0), || None).is_none());
+        let made = || -> Option<Box<dyn Widget<String>>> { Some(Box::new(TextBox::new())) };
+        let taken = Editing::take_editor(&mut spares, LogIdx(0), made);
+        assert!(matches!(taken, Some((_, false))));
+    }
+
+    #[test]
+    fn editability_is_asked_without_making_editors() {
+        let cols: ProvidedColumns<Vector<(String, f64)>, _> = ProvidedColumns::new(vec![
+            column("Name", TextCell::new().lens(lens!((String, f64), 0))),
+            column("Value", NumberCell::new().lens(lens!((String, f64), 1))),
+        ]);
+        let at = |col| {
+            let vis = AxisPair::new(VisIdx(0), VisIdx(col));
+            SingleCell::new(vis, AxisPair::new(LogIdx(0), LogIdx(col)))
+        };
+        let (name, value) = (at(0), at(1));
+        assert!(cols.can_edit(&CellCtx::Cell(&name, Size::ZERO, Default::default())));
+        assert!(!cols.can_edit(&CellCtx::Cell(&value, Size::ZERO, Default::default())));
+        assert!(!cols.can_edit(&CellCtx::Absent));
+    }
+
+    #[test]
+    fn fills_repeat_the_block_down_its_columns() {
+        type Pair = (String, String);
+        let cols: ProvidedColumns<Vector<Pair>, _> = ProvidedColumns::new(vec![
+            column("A", TextCell::new().lens(lens!(Pair, 0)))
+                .fillable(|from: &Pair, to: &mut Pair| to.0 = from.0.clone()),
+            column("B", TextCell::new().lens(lens!(Pair, 1)))
+                .fillable(|from: &Pair, to: &mut Pair| to.1 = from.1.clone()),
+        ]);
+        let cells = Cells::new(TableConfig::new(), cols);
+        let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+        let blank = || pair("", "");
+        let rows = vec![pair("a", "x"), pair("b", "y"), blank(), blank(), blank()];
+        let mut measures = AxisPair::new(
+            AxisMeasure::new(AxisMeasurementType::Individual, 20.),
+            AxisMeasure::new(AxisMeasurementType::Individual, 100.),
+        );
+        measures[TableAxis::Rows].set_axis_properties(0., 5, &Remap::Pristine);
+        measures[TableAxis::Columns].set_axis_properties(0., 2, &Remap::Pristine);
+        let mut data = TableState::new(rows.into_iter().collect(), measures);
+
+        assert_eq!(cells.fill_target(&data, 45.), Some(VisIdx(2)));
+        assert_eq!(cells.fill_target(&data, -10.), Some(VisIdx(0)));
+        assert_eq!(cells.fill_target(&data, 1000.), Some(VisIdx(4)));
+
+        let block = CellRect::new((VisIdx(0), VisIdx(1)), (VisIdx(0), VisIdx(1)));
+        cells.fill_rows(&mut data, &block, VisIdx(4));
+        let (a, b): (Vec<_>, Vec<_>) = data.data.iter().cloned().unzip();
+        assert_eq!(a, ["a", "b", "a", "b", "a"]);
+        assert_eq!(b, ["x", "y", "x", "y", "x"]);
+
+        // Upwards, the block's last row ends up just above it
+        for row in 0..3 {
+            data.data.set(row, blank());
+        }
+        let block = CellRect::new((VisIdx(3), VisIdx(4)), (VisIdx(0), VisIdx(1)));
+        cells.fill_rows(&mut data, &block, VisIdx(0));
+        let filled: Vec<_> = data.data.iter().take(3).cloned().collect();
+        assert_eq!(filled, vec![pair("a", "x"), pair("b", "y"), pair("a", "x")]);
+    }
+
+    #[test]
+    fn appended_rows_are_followed_only_from_the_bottom() {
+        let mut config = TableConfig::new();
+        config.stick_to_bottom = true;
+        let mut cells = Cells::new(
+            config,
+            ProvidedColumns::new(vec![column("Line", TextCell::new())]),
+        );
+        cells.viewport = Size::new(100., 100.);
+        let lines: Vector<String> = (0..10).map(|idx| idx.to_string()).collect();
+        let mut measures = AxisPair::new(
+            AxisMeasure::new(AxisMeasurementType::Individual, 20.),
+            AxisMeasure::new(AxisMeasurementType::Individual, 100.),
+        );
+        measures[TableAxis::Rows].set_axis_properties(0., 10, &Remap::Pristine);
+        measures[TableAxis::Columns].set_axis_properties(0., 1, &Remap::Pristine);
+        let mut data = TableState::new(lines, measures);
+        let append = |data: &mut TableState<Vector<String>>| {
+            data.data.push_back("more".to_string());
+            let len = data.data.len();
+            data.measures[TableAxis::Rows].set_axis_properties(0., len, &Remap::Pristine);
+        };
+
+        data.scroll_y = 100.;
+        let pinned = cells.pinned_length(&data);
+        append(&mut data);
+        cells.follow_appended(&mut data, pinned);
+        assert_eq!(data.scroll_y, 120.);
+
+        // Scrolled up to read, it stays put
+        data.scroll_y = 50.;
+        let pinned = cells.pinned_length(&data);
+        append(&mut data);
+        cells.follow_appended(&mut data, pinned);
+        assert_eq!(data.scroll_y, 50.);
+    }
+
+    // Rows still loading are None
+    #[derive(Clone, Data)]
+    struct Loading(Vector<Option<String>>);
+
+    impl IndexedItems for Loading {
+        type Item = String;
+        type Idx = LogIdx;
+        fn with<V>(&self, idx: LogIdx, f: impl FnOnce(&String) -> V) -> Option<V> {
+            self.0.get(idx.0)?.as_ref().map(f)
+        }
+
+        fn with_mut<V>(&mut self, idx: LogIdx, f: impl FnOnce(&mut String) -> V) -> Option<V> {
+            self.0.get_mut(idx.0)?.as_mut().map(f)
+        }
+
+        fn idx_len(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    #[test]
+    fn missing_rows_are_asked_for_under_a_sort() {
+        type LoadingCells = Cells<Loading, ProvidedColumns<Loading, Box<dyn CellDelegate<String>>>>;
+        let rows = vec![Some("c".to_string()), None, Some("a".to_string()), None];
+        let mut measures = AxisPair::new(
+            AxisMeasure::new(AxisMeasurementType::Individual, 20.),
+            AxisMeasure::new(AxisMeasurementType::Individual, 100.),
+        );
+        measures[TableAxis::Rows].set_axis_properties(0., 4, &Remap::Pristine);
+        let mut data = TableState::new(Loading(rows.into()), measures);
+        let missing = LoadingCells::missing_rows(&data, (VisIdx(0), VisIdx(3)));
+        assert_eq!(missing, [LogIdx(1), LogIdx(3)]);
+
+        // Reversed, the view's first two places show the last two rows
+        let sorted = RemapDetails::Full(vec![LogIdx(3), LogIdx(2), LogIdx(1), LogIdx(0)].into());
+        data.remaps[TableAxis::Rows] = Remap::selected(sorted);
+        let missing = LoadingCells::missing_rows(&data, (VisIdx(0), VisIdx(1)));
+        assert_eq!(missing, [LogIdx(3)]);
+        let missing = LoadingCells::missing_rows(&data, (VisIdx(2), VisIdx(3)));
+        assert_eq!(missing, [LogIdx(1)]);
+    }
+
+    #[test]
+    fn searches_pass_over_cells_covered_by_a_span() {
+        type Pair = (String, String);
+        let cols: ProvidedColumns<Vector<Pair>, _> = ProvidedColumns::new(vec![
+            column("Title", TextCell::new().lens(lens!(Pair, 0)))
+                .col_span(|row: &Pair| 1 + (row.0 == "wide") as usize),
+            column("Note", TextCell::new().lens(lens!(Pair, 1))),
+        ]);
+        let mut cells = Cells::new(TableConfig::new(), cols);
+        cells.search = Some(SearchSpec::new("X"));
+        let rows = [("wide", "x"), ("narrow", "x")];
+        let rows = rows.iter().map(|(a, b)| (a.to_string(), b.to_string()));
+        let mut measures = AxisPair::new(
+            AxisMeasure::new(AxisMeasurementType::Individual, 20.),
+            AxisMeasure::new(AxisMeasurementType::Individual, 100.),
+        );
+        measures[TableAxis::Rows].set_axis_properties(0., 2, &Remap::Pristine);
+        measures[TableAxis::Columns].set_axis_properties(0., 2, &Remap::Pristine);
+        let data = TableState::new(rows.collect(), measures);
+
+        let found = cells.find_match(&data, &Env::default(), false, false);
+        let found = found.map(|cell| cell.vis);
+        assert_eq!(found, Some(AxisPair::new(VisIdx(1), VisIdx(1))));
+    }
+
+    #[test]
+    fn spans_from_left_of_the_view_are_found() {
+        let cols: ProvidedColumns<Vector<String>, _> = ProvidedColumns::new(vec![
+            column("A", TextCell::new()).col_span(|row: &String| row.len()),
+            column("B", TextCell::new()),
+            column("C", TextCell::new()),
+            column("D", TextCell::new()),
+        ]);
+        let cells = Cells::new(TableConfig::new(), cols);
+        let mut measures = AxisPair::new(
+            AxisMeasure::new(AxisMeasurementType::Individual, 20.),
+            AxisMeasure::new(AxisMeasurementType::Individual, 100.),
+        );
+        measures[TableAxis::Rows].set_axis_properties(0., 2, &Remap::Pristine);
+        measures[TableAxis::Columns].set_axis_properties(0., 4, &Remap::Pristine);
+        let data = TableState::new(vec!["xxx".to_string(), "x".to_string()].into(), measures);
+
+        // Scrolled so the view starts at C, the first row's cell still starts at A
+        let remap = &data.remaps[TableAxis::Columns];
+        let spans = cells.row_spans(&data.data[0], remap, 4, (VisIdx(2), VisIdx(3)));
+        assert_eq!(spans, [(VisIdx(0), VisIdx(2)), (VisIdx(3), VisIdx(3))]);
+        let spans = cells.row_spans(&data.data[1], remap, 4, (VisIdx(2), VisIdx(3)));
+        assert_eq!(spans, [(VisIdx(2), VisIdx(2)), (VisIdx(3), VisIdx(3))]);
+
+        let hit = |x, y| Some(cells.find_cell(&data, &Point::new(x, y))?.vis.col);
+        assert_eq!(hit(250., 10.), Some(VisIdx(0)));
+        assert_eq!(hit(250., 30.), Some(VisIdx(2)));
+        assert_eq!(hit(350., 10.), Some(VisIdx(3)));
+    }
+
+    #[test]
+    fn plain_clicks_press_buttons_without_selecting_or_editing() {
+        const DELETE: Selector<LogIdx> = Selector::new("test.delete");
+        let mut cells = Cells::new(
+            TableConfig::new(),
+            ProvidedColumns::new(vec![
+                column("Name", TextCell::new()),
+                column("Delete", TextCell::new()).on_click(DELETE),
+            ]),
+        );
+        let mut measures = AxisPair::new(
+            AxisMeasure::new(AxisMeasurementType::Individual, 20.),
+            AxisMeasure::new(AxisMeasurementType::Individual, 100.),
+        );
+        measures[TableAxis::Rows].set_axis_properties(0., 2, &Remap::Pristine);
+        measures[TableAxis::Columns].set_axis_properties(0., 2, &Remap::Pristine);
+        let rows: Vector<String> = vec!["a".to_string(), "b".to_string()].into();
+        let mut data = TableState::new(rows, measures);
+        let name = SingleCell::new(
+            AxisPair::new(VisIdx(0), VisIdx(0)),
+            AxisPair::new(LogIdx(0), LogIdx(0)),
+        );
+        cells.editing = Editing::Cell {
+            single_cell: name,
+            child: WidgetPod::new(Box::new(TextBox::new())),
+            original: "a".to_string(),
+        };
+        let click = |x, mods| MouseEvent {
+            pos: Point::new(x, 30.),
+            window_pos: Point::new(x, 30.),
+            buttons: MouseButtons::new().with(MouseButton::Left),
+            mods,
+            count: 1,
+            focus: false,
+            button: MouseButton::Left,
+            wheel_delta: Vec2::ZERO,
+        };
+
+        // Clicks with modifiers, or off the buttons, select as usual
+        let plain = Modifiers::empty();
+        let (shifted, off) = (click(150., Modifiers::SHIFT), click(50., plain));
+        assert_eq!(cells.clickable_at(&data, &shifted), None);
+        assert_eq!(cells.clickable_at(&data, &off), None);
+
+        let pressed = cells.clickable_at(&data, &click(150., plain));
+        let pressed = pressed.expect("a button");
+        assert_eq!(pressed.log, AxisPair::new(LogIdx(1), LogIdx(1)));
+        cells.press(&mut data, pressed);
+        assert!(!cells.editing.is_active());
+        assert!(data.selection.vis_focus().is_none());
+    }
+
+    #[test]
+    fn resizing_the_view_is_noticed() {
+        let cols: ProvidedColumns<Vector<String>, _> =
+            ProvidedColumns::new(vec![column("Name", TextCell::new())]);
+        let mut cells = Cells::new(TableConfig::new(), cols);
+        assert!(cells.resize_viewport(Size::new(200., 100.)));
+        assert!(!cells.resize_viewport(Size::new(200., 100.)));
+        // Eg a split pane moving, with the window left alone
+        assert!(cells.resize_viewport(Size::new(200., 60.)));
+        assert_eq!(cells.viewport, Size::new(200., 60.));
+    }
+
+    #[test]
+    fn copied_headers_follow_the_shown_columns() {
+        let cols: ProvidedColumns<Vector<String>, _> = ProvidedColumns::new(vec![
+            column("Name", TextCell::new()),
+            column("Hidden", TextCell::new()),
+            column("Age", TextCell::new()),
+        ]);
+        // Moved to the front, with the middle column hidden
+        let remap = Remap::selected(RemapDetails::Full(vec![LogIdx(2), LogIdx(0)].into()));
+        let headers = copied_headers::<Vector<String>, _>(&cols, &remap, (VisIdx(0), VisIdx(1)));
+        assert_eq!(headers, vec!["Age", "Name"]);
+        let one = copied_headers::<Vector<String>, _>(&cols, &remap, (VisIdx(1), VisIdx(1)));
+        assert_eq!(one, vec!["Name"]);
+    }
+
+    #[test]
+    fn type_ahead_extends_or_cycles_the_prefix() {
+        let mut type_ahead = TypeAhead::default();
+        let now = Instant::now();
+        assert_eq!(type_ahead.push("B", now), ("b".to_string(), true));
+        assert_eq!(type_ahead.push("b", now), ("b".to_string(), true));
+        let soon = now + Duration::from_millis(100);
+        assert_eq!(type_ahead.push("e", soon), ("bbe".to_string(), false));
+
+        let later = soon + TYPE_AHEAD_TIMEOUT * 2;
+        assert_eq!(type_ahead.push("a", later), ("a".to_string(), true));
+    }
+
+    #[test]
+    fn rows_are_found_by_the_first_column_when_the_sorted_one_is_hidden() {
+        type Pair = (String, String);
+        let cols: ProvidedColumns<Vector<Pair>, _> = ProvidedColumns::new(vec![
+            column("Name", TextCell::new().lens(lens!(Pair, 0))),
+            column("City", TextCell::new().lens(lens!(Pair, 1))),
+        ]);
+        let cells = Cells::new(TableConfig::new(), cols);
+        let rows = [("ann", "rome"), ("bob", "oslo"), ("cal", "bern")];
+        let find = |data: &TableState<Vector<Pair>>| {
+            cells.type_ahead_row(data, &Env::default(), "b", false)
+        };
+        let mut measures = AxisPair::new(
+            AxisMeasure::new(AxisMeasurementType::Individual, 20.),
+            AxisMeasure::new(AxisMeasurementType::Individual, 100.),
+        );
+        measures[TableAxis::Rows].set_axis_properties(0., 3, &Remap::Pristine);
+        measures[TableAxis::Columns].set_axis_properties(0., 2, &Remap::Pristine);
+        let rows = rows.iter().map(|(a, b)| (a.to_string(), b.to_string()));
+        let mut data = TableState::new(rows.collect(), measures);
+
+        let by_city = SortSpec::new(1, SortDirection::Ascending);
+        data.remap_specs[TableAxis::Rows].add_sort(by_city);
+        assert_eq!(find(&data), Some(VisIdx(2)));
+
+        // With the city column hidden, it searches the names
+        let names = RemapDetails::Full(vec![LogIdx(0)].into());
+        data.remaps[TableAxis::Columns] = Remap::selected(names);
+        assert_eq!(find(&data), Some(VisIdx(1)));
+    }
+}
@@ -1,23 +1,36 @@
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 
-use crate::axis_measure::{AxisPair, LogIdx};
+use crate::axis_measure::{AxisPair, LogIdx, VisIdx};
 use crate::data::SortDirection::Ascending;
 use crate::data::{RemapDetails, SortDirection, SortSpec};
 use crate::selection::SingleCell;
 use crate::{CellsDelegate, IndexedData, IndexedItems, Remap, RemapSpec, Remapper, TableAxis};
 use druid::im::Vector;
-use druid::kurbo::{Line, PathEl};
-use druid::piet::{FontFamily, Text, TextLayoutBuilder};
+use druid::kurbo::{Affine, Arc, Circle, Line, PathEl, Rect, RoundedRect, Vec2};
+use druid::piet::{
+    FontFamily, PietText, PietTextLayout, Text, TextAlignment, TextLayout, TextLayoutBuilder,
+};
 use druid::widget::prelude::*;
-use druid::widget::TextBox;
-use druid::{theme, ArcStr, Color, Data, Env, KeyOrValue, Lens, PaintCtx, Point, WidgetExt};
+use druid::widget::{Checkbox, Controller, Label, TextBox};
+use druid::{
+    theme, ArcStr, Color, Data, Env, Insets, KbKey, Key, KeyOrValue, Lens, PaintCtx, Point,
+    Selector, Size, WidgetExt,
+};
+use std::cell::RefCell;
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::f64::consts::PI;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use std::iter::Peekable;
+use std::str::Chars;
+use std::sync::Once;
 
 pub trait EditorFactory<RowData> {
     fn make_editor(&mut self, ctx: &CellCtx) -> Option<Box<dyn Widget<RowData>>>;
+    // Whether make_editor gives an editor for the cell, without making one
+    fn can_edit(&self, ctx: &CellCtx) -> bool;
 }
 
 pub trait CellDelegate<RowData>:
@@ -32,12 +45,40 @@ impl<T> CellRender<T> for Box<dyn CellDelegate<T>> {
     fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env) {
         self.deref().paint(ctx, cell, data, env);
     }
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        self.deref().measure(text, axis, cell, data, env)
+    }
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        self.deref().as_text(cell, data, env)
+    }
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        self.deref().as_number(cell, data, env)
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        self.deref().is_loading(cell, data, env)
+    }
+
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        self.deref().background(cell, data, env)
+    }
 }
 
 impl<RowData> EditorFactory<RowData> for Box<dyn CellDelegate<RowData>> {
     fn make_editor(&mut self, ctx: &CellCtx) -> Option<Box<dyn Widget<RowData>>> {
         self.deref_mut().make_editor(ctx)
     }
+
+    fn can_edit(&self, ctx: &CellCtx) -> bool {
+        self.deref().can_edit(ctx)
+    }
 }
 
 impl<T> DataCompare<T> for Box<dyn CellDelegate<T>> {
@@ -59,18 +100,119 @@ impl<T> CellRender<T> for Box<dyn CellRender<T>> {
     fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env) {
         self.deref().paint(ctx, cell, data, env);
     }
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        self.deref().measure(text, axis, cell, data, env)
+    }
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        self.deref().as_text(cell, data, env)
+    }
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        self.deref().as_number(cell, data, env)
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        self.deref().is_loading(cell, data, env)
+    }
+
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        self.deref().background(cell, data, env)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum CellCtx<'a> {
     Absent,
-    Cell(&'a SingleCell),
-    Header(&'a TableAxis, LogIdx, Option<&'a SortSpec>),
+    Cell(&'a SingleCell, Size, CellStyle), // The size is the content area, ie after padding
+    Header(&'a TableAxis, LogIdx, Option<&'a SortSpec>, Size),
+}
+
+impl CellCtx<'_> {
+    pub fn size(&self) -> Option<Size> {
+        match self {
+            CellCtx::Cell(_, size, _) => Some(*size),
+            CellCtx::Header(_, _, _, size) => Some(*size),
+            _ => None,
+        }
+    }
+
+    pub fn style(&self) -> Option<&CellStyle> {
+        match self {
+            CellCtx::Cell(_, _, style) => Some(style),
+            _ => None,
+        }
+    }
+
+    // The same cell, with its text in `color`
+    pub fn with_text_color(&self, color: Color) -> Self {
+        let mut cell = self.clone();
+        if let CellCtx::Cell(_, _, style) = &mut cell {
+            style.text_color = Some(color);
+        }
+        cell
+    }
+
+    // The same cell, with its text aligned by `align`
+    pub fn with_alignment(&self, align: TextAlignment) -> Self {
+        let mut cell = self.clone();
+        if let CellCtx::Cell(_, _, style) = &mut cell {
+            style.align = Some(align);
+        }
+        cell
+    }
+}
+
+// How a cell's text is drawn, where something between the table and the renderer decides,
+// like text_color_with or TableColumn::align. What is left unset is up to the renderer.
+#[derive(Debug, Clone, Default)]
+pub struct CellStyle {
+    pub text_color: Option<Color>,
+    pub align: Option<TextAlignment>,
 }
 
 pub trait CellRender<T> {
     fn init(&mut self, ctx: &mut PaintCtx, env: &Env); // Use to cache resources like fonts
     fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env);
+
+    // The content length this cell would like along the given axis, using the size on the
+    // CellCtx for the other axis. None means no preference, so the measure is left alone.
+    fn measure(
+        &self,
+        _text: &mut PietText,
+        _axis: TableAxis,
+        _cell: &CellCtx,
+        _data: &T,
+        _env: &Env,
+    ) -> Option<f64> {
+        None
+    }
+
+    // Plain text for the cell, used when copying. None means it has no textual form.
+    fn as_text(&self, _cell: &CellCtx, _data: &T, _env: &Env) -> Option<String> {
+        None
+    }
+
+    // The cell as a number, for aggregates like footer totals. None if it isn't numeric.
+    fn as_number(&self, _cell: &CellCtx, _data: &T, _env: &Env) -> Option<f64> {
+        None
+    }
+
+    // Whether the cell is still waiting for its value, so is drawn as loading. The cells keep
+    // animating while any cell in view is, see CellRenderExt::loading.
+    fn is_loading(&self, _cell: &CellCtx, _data: &T, _env: &Env) -> bool {
+        false
+    }
+
+    // Filled in behind the whole cell, padding included, before it is painted
+    fn background(&self, _cell: &CellCtx, _data: &T, _env: &Env) -> Option<Color> {
+        None
+    }
 }
 
 impl<T, CR: CellRender<T>> CellRender<T> for Vec<CR> {
@@ -81,24 +223,116 @@ impl<T, CR: CellRender<T>> CellRender<T> for Vec<CR> {
     }
 
     fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env) {
-        if let CellCtx::Cell(SingleCell {
-            log: AxisPair { col, .. },
-            ..
-        }) = cell
+        if let CellCtx::Cell(
+            SingleCell {
+                log: AxisPair { col, .. },
+                ..
+            },
+            ..,
+        ) = cell
         {
             if let Some(cell_render) = self.get(col.0) {
                 cell_render.paint(ctx, cell, data, env)
             }
         }
     }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        if let CellCtx::Cell(
+            SingleCell {
+                log: AxisPair { col, .. },
+                ..
+            },
+            ..,
+        ) = cell
+        {
+            self.get(col.0)
+                .and_then(|cell_render| cell_render.measure(text, axis, cell, data, env))
+        } else {
+            None
+        }
+    }
+
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        if let CellCtx::Cell(
+            SingleCell {
+                log: AxisPair { col, .. },
+                ..
+            },
+            ..,
+        ) = cell
+        {
+            self.get(col.0)
+                .and_then(|cell_render| cell_render.as_text(cell, data, env))
+        } else {
+            None
+        }
+    }
+
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        if let CellCtx::Cell(
+            SingleCell {
+                log: AxisPair { col, .. },
+                ..
+            },
+            ..,
+        ) = cell
+        {
+            self.get(col.0)
+                .and_then(|cell_render| cell_render.as_number(cell, data, env))
+        } else {
+            None
+        }
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        if let CellCtx::Cell(
+            SingleCell {
+                log: AxisPair { col, .. },
+                ..
+            },
+            ..,
+        ) = cell
+        {
+            self.get(col.0)
+                .map_or(false, |cell_render| cell_render.is_loading(cell, data, env))
+        } else {
+            false
+        }
+    }
+
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        if let CellCtx::Cell(
+            SingleCell {
+                log: AxisPair { col, .. },
+                ..
+            },
+            ..,
+        ) = cell
+        {
+            self.get(col.0)?.background(cell, data, env)
+        } else {
+            None
+        }
+    }
 }
 
 impl<T, EF: EditorFactory<T>> EditorFactory<T> for Vec<EF> {
     fn make_editor(&mut self, cell: &CellCtx) -> Option<Box<dyn Widget<T>>> {
-        if let CellCtx::Cell(SingleCell {
-            log: AxisPair { col, .. },
-            ..
-        }) = cell
+        if let CellCtx::Cell(
+            SingleCell {
+                log: AxisPair { col, .. },
+                ..
+            },
+            ..,
+        ) = cell
         {
             if let Some(ef) = self.get_mut(col.0) {
                 return ef.make_editor(cell);
@@ -106,6 +340,21 @@ impl<T, EF: EditorFactory<T>> EditorFactory<T> for Vec<EF> {
         }
         None
     }
+
+    fn can_edit(&self, cell: &CellCtx) -> bool {
+        if let CellCtx::Cell(
+            SingleCell {
+                log: AxisPair { col, .. },
+                ..
+            },
+            ..,
+        ) = cell
+        {
+            self.get(col.0).map_or(false, |ef| ef.can_edit(cell))
+        } else {
+            false
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -125,6 +374,26 @@ pub struct FuncWrapped<T, U, W, I>(Wrapped<T, U, W, I>)
 where
     W: Fn(&T) -> U;
 
+#[derive(Clone)]
+pub struct BgWrapped<T, W, I>(Wrapped<T, T, W, I>)
+where
+    W: Fn(&T, &Env) -> Option<Color>;
+
+#[derive(Clone)]
+pub struct CompareWrapped<T, W, I>(Wrapped<T, T, W, I>)
+where
+    W: Fn(&T, &T) -> Ordering;
+
+#[derive(Clone)]
+pub struct TextColorWrapped<T, W, I>(Wrapped<T, T, W, I>)
+where
+    W: Fn(&T, &Env) -> Color;
+
+#[derive(Clone)]
+pub struct LoadingWrapped<T, W, I>(Wrapped<T, T, W, I>)
+where
+    W: Fn(&T) -> bool;
+
 impl<T, U, W, I> Wrapped<T, U, W, I> {
     fn new(inner: I, wrapper: W) -> Wrapped<T, U, W, I> {
         Wrapped {
@@ -144,6 +413,31 @@ pub trait CellRenderExt<T: Data>: CellRender<T> + Sized + 'static {
     fn on_result_of<S: Data, F: Fn(&S) -> T>(self, f: F) -> FuncWrapped<S, T, F, Self> {
         FuncWrapped(Wrapped::new(self, f))
     }
+
+    // Fill the cell with a colour picked from the data before painting it
+    fn with_bg<F: Fn(&T, &Env) -> Option<Color>>(self, f: F) -> BgWrapped<T, F, Self> {
+        BgWrapped(Wrapped::new(self, f))
+    }
+
+    // Sort with the given function instead of the inner delegate's comparison
+    fn compare_with<F: Fn(&T, &T) -> Ordering>(self, f: F) -> CompareWrapped<T, F, Self> {
+        CompareWrapped(Wrapped::new(self, f))
+    }
+
+    // Colour text from the data. Applies to any TextCell painted by the inner delegate
+    fn text_color_with<F: Fn(&T, &Env) -> Color>(self, f: F) -> TextColorWrapped<T, F, Self> {
+        TextColorWrapped(Wrapped::new(self, f))
+    }
+
+    // Show an Option<T>, painting Some with this delegate and None as a placeholder
+    fn optional(self) -> OptionalWrapped<T, Self> {
+        OptionalWrapped::new(self)
+    }
+
+    // Draw a spinner instead of the value until is_ready says it has been computed
+    fn loading<F: Fn(&T) -> bool>(self, is_ready: F) -> LoadingWrapped<T, F, Self> {
+        LoadingWrapped(Wrapped::new(self, is_ready))
+    }
 }
 
 impl<T: Data, CR: CellRender<T> + 'static> CellRenderExt<T> for CR {}
@@ -169,6 +463,48 @@ where
             inner.paint(ctx, cell, inner_data, env);
         })
     }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        let inner = &self.0.inner;
+        self.0.wrapper.with(data, |inner_data| {
+            inner.measure(text, axis, cell, inner_data, env)
+        })
+    }
+
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        let inner = &self.0.inner;
+        self.0
+            .wrapper
+            .with(data, |inner_data| inner.as_text(cell, inner_data, env))
+    }
+
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        let inner = &self.0.inner;
+        self.0
+            .wrapper
+            .with(data, |inner_data| inner.as_number(cell, inner_data, env))
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        let inner = &self.0.inner;
+        self.0
+            .wrapper
+            .with(data, |inner_data| inner.is_loading(cell, inner_data, env))
+    }
+
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        let inner = &self.0.inner;
+        self.0
+            .wrapper
+            .with(data, |inner_data| inner.background(cell, inner_data, env))
+    }
 }
 
 impl<T, U, L, DC> DataCompare<T> for LensWrapped<T, U, L, DC>
@@ -200,6 +536,10 @@ where
             None
         }
     }
+
+    fn can_edit(&self, ctx: &CellCtx) -> bool {
+        self.0.inner.can_edit(ctx)
+    }
 }
 
 impl<T, U, F, CR> CellRender<T> for FuncWrapped<T, U, F, CR>
@@ -218,6 +558,38 @@ where
         let inner_data = (self.0.wrapper)(data);
         inner.paint(ctx, cell, &inner_data, env);
     }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        let inner_data = (self.0.wrapper)(data);
+        self.0.inner.measure(text, axis, cell, &inner_data, env)
+    }
+
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        let inner_data = (self.0.wrapper)(data);
+        self.0.inner.as_text(cell, &inner_data, env)
+    }
+
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        let inner_data = (self.0.wrapper)(data);
+        self.0.inner.as_number(cell, &inner_data, env)
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        let inner_data = (self.0.wrapper)(data);
+        self.0.inner.is_loading(cell, &inner_data, env)
+    }
+
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        let inner_data = (self.0.wrapper)(data);
+        self.0.inner.background(cell, &inner_data, env)
+    }
 }
 
 impl<T, U, F, DC> DataCompare<T> for FuncWrapped<T, U, F, DC>
@@ -234,178 +606,1676 @@ where
     }
 }
 
-#[derive(Clone)]
-pub struct TextCell {
-    text_color: KeyOrValue<Color>,
-    font_name: KeyOrValue<ArcStr>,
-    font_size: KeyOrValue<f64>,
-    cached_font: Option<FontFamily>,
-}
+impl<T, F, CR> CellRender<T> for BgWrapped<T, F, CR>
+where
+    T: Data,
+    F: Fn(&T, &Env) -> Option<Color>,
+    CR: CellRender<T>,
+{
+    fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        self.0.inner.init(ctx, env)
+    }
 
-impl TextCell {
-    pub fn new() -> Self {
-        TextCell {
-            text_color: Color::BLACK.into(),
-            font_name: ArcStr::from("Gill Sans").into(),
-            font_size: theme::TEXT_SIZE_NORMAL.into(),
-            cached_font: None,
-        }
+    // The background is filled in by the cells, across the padding too
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env) {
+        self.0.inner.paint(ctx, cell, data, env);
     }
 
-    pub fn text_color(mut self, text_color: impl Into<KeyOrValue<Color>>) -> TextCell {
-        self.text_color = text_color.into();
-        self
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        self.0.inner.measure(text, axis, cell, data, env)
     }
 
-    pub fn font_name(mut self, font_name: impl Into<KeyOrValue<ArcStr>>) -> TextCell {
-        self.font_name = font_name.into();
-        self
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        self.0.inner.as_text(cell, data, env)
     }
 
-    pub fn font_size(mut self, font_size: impl Into<KeyOrValue<f64>>) -> TextCell {
-        self.font_size = font_size.into();
-        self
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        self.0.inner.as_number(cell, data, env)
     }
 
-    fn resolve_font(&self, ctx: &mut PaintCtx, env: &Env) -> FontFamily {
-        let font: FontFamily = ctx
-            .text()
-            .font_family(&self.font_name.resolve(env))
-            .unwrap(); // TODO errors / fallback
-        font
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        self.0.inner.is_loading(cell, data, env)
     }
 
-    fn paint_impl(&self, ctx: &mut PaintCtx, data: &str, env: &Env, font: &FontFamily) {
-        // TODO: error handling
-        // TODO: wrapping (multi line)
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        (self.0.wrapper)(data, env).or_else(|| self.0.inner.background(cell, data, env))
+    }
+}
 
-        if let Ok(layout) = ctx
-            .text()
-            .new_text_layout(data.to_string())
-            .font(font.clone(), self.font_size.resolve(env))
-            .text_color(self.text_color.resolve(env))
-            .build()
-        {
-            ctx.draw_text(&layout, (0.0, 0.0));
-        }
+impl<T, F, DC> DataCompare<T> for BgWrapped<T, F, DC>
+where
+    F: Fn(&T, &Env) -> Option<Color>,
+    DC: DataCompare<T>,
+{
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self.0.inner.compare(a, b)
     }
 }
 
-impl Default for TextCell {
-    fn default() -> Self {
-        TextCell::new()
+impl<T, F, EF> EditorFactory<T> for BgWrapped<T, F, EF>
+where
+    F: Fn(&T, &Env) -> Option<Color>,
+    EF: EditorFactory<T>,
+{
+    fn make_editor(&mut self, ctx: &CellCtx) -> Option<Box<dyn Widget<T>>> {
+        self.0.inner.make_editor(ctx)
+    }
+
+    fn can_edit(&self, ctx: &CellCtx) -> bool {
+        self.0.inner.can_edit(ctx)
     }
 }
 
-impl CellRender<String> for TextCell {
+impl<T, F, CR> CellRender<T> for CompareWrapped<T, F, CR>
+where
+    T: Data,
+    F: Fn(&T, &T) -> Ordering,
+    CR: CellRender<T>,
+{
     fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
-        if self.cached_font.is_none() {
-            let font = self.resolve_font(ctx, env);
-            self.cached_font = Some(font);
-        }
+        self.0.inner.init(ctx, env)
     }
 
-    fn paint(&self, ctx: &mut PaintCtx, _cell: &CellCtx, data: &String, env: &Env) {
-        if let Some(font) = &self.cached_font {
-            self.paint_impl(ctx, data, env, font);
-        } else {
-            log::warn!("Font not cached, are you missing a call to init");
-            let font = self.resolve_font(ctx, env);
-            ctx.stroke(
-                Line::new((0., 0.), (100., 100.)),
-                &Color::rgb8(0xff, 0, 0),
-                2.,
-            );
-            self.paint_impl(ctx, data, env, &font);
-        }
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env) {
+        self.0.inner.paint(ctx, cell, data, env)
     }
-}
 
-impl EditorFactory<String> for TextCell {
-    fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<String>>> {
-        Some(Box::new(TextBox::new().expand_height()))
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        self.0.inner.measure(text, axis, cell, data, env)
     }
-}
 
-pub(crate) struct HeaderCell<T, I: CellRender<T>> {
-    inner: I,
-    phantom_t: PhantomData<T>,
-}
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        self.0.inner.as_text(cell, data, env)
+    }
 
-impl<T, I: CellRender<T>> HeaderCell<T, I> {
-    pub fn new(inner: I) -> Self {
-        HeaderCell {
-            inner,
-            phantom_t: Default::default(),
-        }
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        self.0.inner.as_number(cell, data, env)
     }
-}
 
-fn make_arrow(top_point: &Point, up: bool, height: f64, head_rad: f64) -> Vec<PathEl> {
-    let start_y = top_point.y;
-    let tip_y = start_y + height;
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        self.0.inner.is_loading(cell, data, env)
+    }
 
-    let (start_y, tip_y, mult) = if up {
-        (tip_y, start_y, -1.)
-    } else {
-        (start_y, tip_y, 1.0)
-    };
-    let head_start_y = tip_y - (head_rad * mult);
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        self.0.inner.background(cell, data, env)
+    }
+}
 
-    let mid_x = top_point.x;
+impl<T, F, I> DataCompare<T> for CompareWrapped<T, F, I>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0.wrapper)(a, b)
+    }
+}
 
-    let arrow = vec![
-        PathEl::MoveTo((mid_x, start_y).into()),
-        PathEl::LineTo((mid_x, tip_y).into()),
-        PathEl::LineTo((mid_x - head_rad, head_start_y).into()),
-        PathEl::MoveTo((mid_x, tip_y).into()),
-        PathEl::LineTo((mid_x + head_rad, head_start_y).into()),
-    ];
-    arrow
+impl<T, F, EF> EditorFactory<T> for CompareWrapped<T, F, EF>
+where
+    F: Fn(&T, &T) -> Ordering,
+    EF: EditorFactory<T>,
+{
+    fn make_editor(&mut self, ctx: &CellCtx) -> Option<Box<dyn Widget<T>>> {
+        self.0.inner.make_editor(ctx)
+    }
+
+    fn can_edit(&self, ctx: &CellCtx) -> bool {
+        self.0.inner.can_edit(ctx)
+    }
 }
 
-impl<T, I: CellRender<T>> CellRender<T> for HeaderCell<T, I> {
+impl<T, F, CR> CellRender<T> for TextColorWrapped<T, F, CR>
+where
+    T: Data,
+    F: Fn(&T, &Env) -> Color,
+    CR: CellRender<T>,
+{
     fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
-        self.inner.init(ctx, env);
+        self.0.inner.init(ctx, env)
+    }
+
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env) {
+        let cell = cell.with_text_color((self.0.wrapper)(data, env));
+        self.0.inner.paint(ctx, &cell, data, env)
+    }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        self.0.inner.measure(text, axis, cell, data, env)
+    }
+
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        self.0.inner.as_text(cell, data, env)
+    }
+
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        self.0.inner.as_number(cell, data, env)
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        self.0.inner.is_loading(cell, data, env)
+    }
+
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        self.0.inner.background(cell, data, env)
+    }
+}
+
+impl<T, F, DC> DataCompare<T> for TextColorWrapped<T, F, DC>
+where
+    F: Fn(&T, &Env) -> Color,
+    DC: DataCompare<T>,
+{
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self.0.inner.compare(a, b)
+    }
+}
+
+impl<T, F, EF> EditorFactory<T> for TextColorWrapped<T, F, EF>
+where
+    F: Fn(&T, &Env) -> Color,
+    EF: EditorFactory<T>,
+{
+    fn make_editor(&mut self, ctx: &CellCtx) -> Option<Box<dyn Widget<T>>> {
+        self.0.inner.make_editor(ctx)
+    }
+
+    fn can_edit(&self, ctx: &CellCtx) -> bool {
+        self.0.inner.can_edit(ctx)
+    }
+}
+
+impl<T, F, CR> CellRender<T> for LoadingWrapped<T, F, CR>
+where
+    T: Data,
+    F: Fn(&T) -> bool,
+    CR: CellRender<T>,
+{
+    fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        self.0.inner.init(ctx, env)
+    }
+
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env) {
+        if (self.0.wrapper)(data) {
+            self.0.inner.paint(ctx, cell, data, env)
+        } else if let Some(size) = cell.size() {
+            paint_spinner(ctx, size, env)
+        }
+    }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        if (self.0.wrapper)(data) {
+            self.0.inner.measure(text, axis, cell, data, env)
+        } else {
+            None
+        }
+    }
+
+    // Nothing to copy or total until the value is there
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        if (self.0.wrapper)(data) {
+            self.0.inner.as_text(cell, data, env)
+        } else {
+            None
+        }
+    }
+
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        if (self.0.wrapper)(data) {
+            self.0.inner.as_number(cell, data, env)
+        } else {
+            None
+        }
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        !(self.0.wrapper)(data) || self.0.inner.is_loading(cell, data, env)
+    }
+
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        self.0.inner.background(cell, data, env)
+    }
+}
+
+impl<T, F, DC> DataCompare<T> for LoadingWrapped<T, F, DC>
+where
+    F: Fn(&T) -> bool,
+    DC: DataCompare<T>,
+{
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self.0.inner.compare(a, b)
+    }
+}
+
+impl<T, F, EF> EditorFactory<T> for LoadingWrapped<T, F, EF>
+where
+    F: Fn(&T) -> bool,
+    EF: EditorFactory<T>,
+{
+    fn make_editor(&mut self, ctx: &CellCtx) -> Option<Box<dyn Widget<T>>> {
+        self.0.inner.make_editor(ctx)
+    }
+
+    fn can_edit(&self, ctx: &CellCtx) -> bool {
+        self.0.inner.can_edit(ctx)
+    }
+}
+
+// Most of a circle, turned by how far the cells have got through LOADING_PHASE
+fn paint_spinner(ctx: &mut PaintCtx, size: Size, env: &Env) {
+    let radius = (size.width.min(size.height) / 2. - 2.).min(6.);
+    if radius <= 0. {
+        return;
+    }
+    let turn = env.try_get(LOADING_PHASE).unwrap_or(0.);
+    let arc = Arc {
+        center: size.to_rect().center(),
+        radii: Vec2::new(radius, radius),
+        start_angle: turn * 2. * PI,
+        sweep_angle: 1.5 * PI,
+        x_rotation: 0.,
+    };
+    ctx.stroke(arc, &env.get(theme::PLACEHOLDER_COLOR), 1.5);
+}
+
+// Where None sorts, in ascending order, relative to the values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoneOrder {
+    First,
+    Last,
+}
+
+pub struct OptionalWrapped<T, I> {
+    inner: I,
+    placeholder: String,
+    placeholder_cell: TextCell,
+    none_order: NoneOrder,
+    phantom_t: PhantomData<T>,
+}
+
+impl<T, I> OptionalWrapped<T, I> {
+    fn new(inner: I) -> Self {
+        OptionalWrapped {
+            inner,
+            placeholder: String::new(),
+            placeholder_cell: TextCell::new().text_color(theme::PLACEHOLDER_COLOR),
+            none_order: NoneOrder::First,
+            phantom_t: PhantomData,
+        }
+    }
+
+    // Greyed text painted for None, eg "—". Blank by default
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn none_order(mut self, none_order: NoneOrder) -> Self {
+        self.none_order = none_order;
+        self
+    }
+}
+
+impl<T, CR> CellRender<Option<T>> for OptionalWrapped<T, CR>
+where
+    T: Data,
+    CR: CellRender<T>,
+{
+    fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        self.inner.init(ctx, env);
+        self.placeholder_cell.init(ctx, env)
+    }
+
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &Option<T>, env: &Env) {
+        match data {
+            Some(data) => self.inner.paint(ctx, cell, data, env),
+            None if !self.placeholder.is_empty() => {
+                self.placeholder_cell
+                    .paint(ctx, cell, &self.placeholder, env)
+            }
+            None => (),
+        }
+    }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &Option<T>,
+        env: &Env,
+    ) -> Option<f64> {
+        match data {
+            Some(data) => self.inner.measure(text, axis, cell, data, env),
+            None => self
+                .placeholder_cell
+                .measure(text, axis, cell, &self.placeholder, env),
+        }
+    }
+
+    // None is left blank when copied, rather than giving the placeholder
+    fn as_text(&self, cell: &CellCtx, data: &Option<T>, env: &Env) -> Option<String> {
+        self.inner.as_text(cell, data.as_ref()?, env)
+    }
+
+    fn as_number(&self, cell: &CellCtx, data: &Option<T>, env: &Env) -> Option<f64> {
+        self.inner.as_number(cell, data.as_ref()?, env)
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &Option<T>, env: &Env) -> bool {
+        data.as_ref()
+            .map_or(false, |data| self.inner.is_loading(cell, data, env))
+    }
+
+    fn background(&self, cell: &CellCtx, data: &Option<T>, env: &Env) -> Option<Color> {
+        self.inner.background(cell, data.as_ref()?, env)
+    }
+}
+
+impl<T, DC> DataCompare<Option<T>> for OptionalWrapped<T, DC>
+where
+    DC: DataCompare<T>,
+{
+    fn compare(&self, a: &Option<T>, b: &Option<T>) -> Ordering {
+        let none_first = self.none_order == NoneOrder::First;
+        match (a, b) {
+            (Some(a), Some(b)) => self.inner.compare(a, b),
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) if none_first => Ordering::Less,
+            (Some(_), None) if !none_first => Ordering::Less,
+            _ => Ordering::Greater,
+        }
+    }
+}
+
+// The inner editor works on T, so optional cells aren't editable
+impl<T, EF> EditorFactory<Option<T>> for OptionalWrapped<T, EF> {
+    fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<Option<T>>>> {
+        None
+    }
+
+    fn can_edit(&self, _ctx: &CellCtx) -> bool {
+        false
+    }
+}
+
+static MISSING_FONT_WARNING: Once = Once::new();
+
+// Set by the cells to how far loading spinners have turned, in whole turns
+pub(crate) const LOADING_PHASE: Key<f64> = Key::new("druid-table.loading-phase");
+
+// A TextAlignment can't be hashed, so cached layouts go by its position in here
+const ALIGNMENTS: [TextAlignment; 4] = [
+    TextAlignment::Start,
+    TextAlignment::End,
+    TextAlignment::Center,
+    TextAlignment::Justified,
+];
+
+// Set while painting a right to left table, flipping text to the other side of its cell
+pub(crate) const RIGHT_TO_LEFT: Key<bool> = Key::new("druid-table.right-to-left");
+
+fn alignment_key(align: TextAlignment) -> Option<u64> {
+    let pos = ALIGNMENTS.iter().position(|a| *a == align)?;
+    Some(pos as u64)
+}
+
+const ELLIPSIS: &str = "\u{2026}";
+
+// What to do with text that is wider than its cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextOverflow {
+    Clip,
+    Ellipsis,
+}
+
+#[derive(Clone)]
+pub struct TextCell {
+    text_color: KeyOrValue<Color>,
+    font_name: KeyOrValue<ArcStr>,
+    font_size: KeyOrValue<f64>,
+    text_alignment: TextAlignment,
+    wrap: bool,
+    overflow: TextOverflow,
+    natural_sort: bool,
+    ignore_case: bool,
+    cached_font: Option<FontFamily>,
+    layouts: RefCell<HashMap<LayoutKey, PietTextLayout>>, // While CACHE_TEXT_LAYOUTS is set
+}
+
+// Everything a layout depends on besides the font: the text, font size, colour, alignment and
+// wrapping width, the floats by their bits
+type LayoutKey = (String, u64, u32, u64, Option<u64>);
+
+// Each TextCell keeps about as many layouts as there are cells in view a few times over
+const LAYOUT_CACHE_LIMIT: usize = 4096;
+
+// Set while painting table cells with TableConfig::cache_text_layouts on
+pub(crate) const CACHE_TEXT_LAYOUTS: Key<bool> = Key::new("druid-table.cache-text-layouts");
+
+impl TextCell {
+    pub fn new() -> Self {
+        TextCell {
+            text_color: theme::TEXT_COLOR.into(),
+            font_name: ArcStr::from("Gill Sans").into(),
+            font_size: theme::TEXT_SIZE_NORMAL.into(),
+            text_alignment: TextAlignment::Start,
+            wrap: false,
+            overflow: TextOverflow::Clip,
+            natural_sort: false,
+            ignore_case: false,
+            cached_font: None,
+            layouts: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn text_color(mut self, text_color: impl Into<KeyOrValue<Color>>) -> TextCell {
+        self.text_color = text_color.into();
+        self
+    }
+
+    pub fn font_name(mut self, font_name: impl Into<KeyOrValue<ArcStr>>) -> TextCell {
+        self.font_name = font_name.into();
+        self
+    }
+
+    pub fn font_size(mut self, font_size: impl Into<KeyOrValue<f64>>) -> TextCell {
+        self.font_size = font_size.into();
+        self
+    }
+
+    pub fn text_alignment(mut self, text_alignment: TextAlignment) -> TextCell {
+        self.text_alignment = text_alignment;
+        self
+    }
+
+    // Flow the text onto multiple lines within the cell width.
+    // Rows will grow to fit if they are individually measured.
+    pub fn wrap(mut self, wrap: bool) -> TextCell {
+        self.wrap = wrap;
+        self
+    }
+
+    // Only applies to text that isn't wrapped
+    pub fn overflow(mut self, overflow: TextOverflow) -> TextCell {
+        self.overflow = overflow;
+        self
+    }
+
+    // Sort numbers within the text by value, so "file9" comes before "file10"
+    pub fn natural_sort(mut self, natural_sort: bool) -> TextCell {
+        self.natural_sort = natural_sort;
+        self
+    }
+
+    // Sort "apple" and "Banana" alphabetically rather than capitals first.
+    // Text differing only by case still has a stable order.
+    pub fn ignore_case(mut self, ignore_case: bool) -> TextCell {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    // The column's alignment, from TableColumn::align, takes precedence
+    fn alignment(&self, cell: &CellCtx, env: &Env) -> TextAlignment {
+        let column_align = cell.style().and_then(|style| style.align);
+        let align = column_align.unwrap_or(self.text_alignment);
+        match (align, env.try_get(RIGHT_TO_LEFT).unwrap_or(false)) {
+            (TextAlignment::Start, true) => TextAlignment::End,
+            (TextAlignment::End, true) => TextAlignment::Start,
+            _ => align,
+        }
+    }
+
+    fn resolve_font(&self, text: &mut PietText, env: &Env) -> FontFamily {
+        let font_name = self.font_name.resolve(env);
+        text.font_family(&font_name).unwrap_or_else(|| {
+            MISSING_FONT_WARNING.call_once(|| {
+                log::warn!("Font {} not found, falling back to system font", font_name)
+            });
+            FontFamily::SYSTEM_UI
+        })
+    }
+
+    fn max_width(&self, cell: &CellCtx) -> Option<f64> {
+        if self.wrap {
+            cell.size().map(|s| s.width)
+        } else {
+            None
+        }
+    }
+
+    // A colour given on the way to the cell, as by text_color_with, takes precedence
+    fn color(&self, cell: &CellCtx, env: &Env) -> Color {
+        match cell.style().and_then(|style| style.text_color.clone()) {
+            Some(color) => color,
+            None => self.text_color.resolve(env),
+        }
+    }
+
+    fn build_layout(
+        &self,
+        text: &mut PietText,
+        cell: &CellCtx,
+        data: &str,
+        env: &Env,
+        font: &FontFamily,
+        max_width: Option<f64>,
+    ) -> Option<PietTextLayout> {
+        let builder = text
+            .new_text_layout(data.to_string())
+            .font(font.clone(), self.font_size.resolve(env))
+            .text_color(self.color(cell, env))
+            .alignment(self.alignment(cell, env));
+        let builder = if let Some(width) = max_width {
+            builder.max_width(width)
+        } else {
+            builder
+        };
+        builder.build().ok()
+    }
+
+    // Reuses the layout from the last time the same text was laid out the same way, when
+    // caching is on. Building layouts is most of the cost of painting a cell.
+    fn cached_layout(
+        &self,
+        text: &mut PietText,
+        cell: &CellCtx,
+        data: &str,
+        env: &Env,
+        font: &FontFamily,
+        max_width: Option<f64>,
+    ) -> Option<PietTextLayout> {
+        if !env.try_get(CACHE_TEXT_LAYOUTS).unwrap_or(false) {
+            return self.build_layout(text, cell, data, env, font, max_width);
+        }
+        let color = self.color(cell, env);
+        let key = (
+            data.to_string(),
+            self.font_size.resolve(env).to_bits(),
+            color.as_rgba_u32(),
+            alignment_key(self.alignment(cell, env)).unwrap_or(0),
+            max_width.map(f64::to_bits),
+        );
+        if let Some(layout) = self.layouts.borrow().get(&key) {
+            return Some(layout.clone());
+        }
+        let layout = self.build_layout(text, cell, data, env, font, max_width)?;
+        let mut layouts = self.layouts.borrow_mut();
+        if layouts.len() >= LAYOUT_CACHE_LIMIT {
+            layouts.clear();
+        }
+        layouts.insert(key, layout.clone());
+        Some(layout)
+    }
+
+    fn paint_impl(
+        &self,
+        ctx: &mut PaintCtx,
+        cell: &CellCtx,
+        data: &str,
+        env: &Env,
+        font: &FontFamily,
+    ) {
+        // TODO: error handling
+        let max_width = self.max_width(cell);
+        if let Some(layout) = self.cached_layout(ctx.text(), cell, data, env, font, max_width) {
+            let layout = match cell.size() {
+                Some(size)
+                    if self.overflow == TextOverflow::Ellipsis
+                        && !self.wrap
+                        && layout.size().width > size.width =>
+                {
+                    self.truncate(ctx.text(), cell, data, env, font, &layout)
+                        .unwrap_or(layout)
+                }
+                _ => layout,
+            };
+            let x = self.x_offset(cell, &layout, env);
+            ctx.draw_text(&layout, (x, 0.0));
+        }
+    }
+
+    // Cut the text where the ellipsis would start, then back off until it fits the cell
+    fn truncate(
+        &self,
+        text: &mut PietText,
+        cell: &CellCtx,
+        data: &str,
+        env: &Env,
+        font: &FontFamily,
+        full: &PietTextLayout,
+    ) -> Option<PietTextLayout> {
+        let width = cell.size()?.width;
+        let ellipsis = self.build_layout(text, cell, ELLIPSIS, env, font, None)?;
+        let available = width - ellipsis.size().width;
+        if available <= 0. {
+            return Some(ellipsis);
+        }
+        let mut end = full
+            .hit_test_point(Point::new(available, 0.))
+            .idx
+            .min(data.len());
+        while !data.is_char_boundary(end) {
+            end -= 1;
+        }
+        loop {
+            let truncated = format!("{}{}", data[..end].trim_end(), ELLIPSIS);
+            let layout = self.build_layout(text, cell, &truncated, env, font, None)?;
+            if end == 0 || layout.size().width <= width {
+                return Some(layout);
+            }
+            end = data[..end].char_indices().last().map_or(0, |(idx, _)| idx);
+        }
+    }
+
+    // Wrapped layouts are aligned within their max width by the layout itself
+    fn x_offset(&self, cell: &CellCtx, layout: &PietTextLayout, env: &Env) -> f64 {
+        let spare = match cell.size() {
+            Some(size) if !self.wrap => size.width - layout.size().width,
+            _ => return 0.,
+        };
+        if spare <= 0. {
+            // Too wide to fit, show the start of the text
+            return 0.;
+        }
+        match self.alignment(cell, env) {
+            TextAlignment::End => spare,
+            TextAlignment::Center => spare / 2.,
+            _ => 0.,
+        }
+    }
+}
+
+impl Default for TextCell {
+    fn default() -> Self {
+        TextCell::new()
+    }
+}
+
+impl CellRender<String> for TextCell {
+    fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        if self.cached_font.is_none() {
+            let font = self.resolve_font(ctx.text(), env);
+            self.cached_font = Some(font);
+        }
+    }
+
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &String, env: &Env) {
+        if let Some(font) = &self.cached_font {
+            self.paint_impl(ctx, cell, data, env, font);
+        } else {
+            log::warn!("Font not cached, are you missing a call to init");
+            let font = self.resolve_font(ctx.text(), env);
+            ctx.stroke(
+                Line::new((0., 0.), (100., 100.)),
+                &Color::rgb8(0xff, 0, 0),
+                2.,
+            );
+            self.paint_impl(ctx, cell, data, env, &font);
+        }
+    }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &String,
+        env: &Env,
+    ) -> Option<f64> {
+        let font = match &self.cached_font {
+            Some(font) => font.clone(),
+            None => self.resolve_font(text, env),
+        };
+        match axis {
+            TableAxis::Rows if self.wrap => self
+                .cached_layout(text, cell, data, env, &font, self.max_width(cell))
+                .map(|layout| layout.size().height),
+            // The width the text would take on one line
+            TableAxis::Columns => self
+                .build_layout(text, cell, data, env, &font, None)
+                .map(|layout| layout.size().width),
+            _ => None,
+        }
+    }
+
+    fn as_text(&self, _cell: &CellCtx, data: &String, _env: &Env) -> Option<String> {
+        Some(data.clone())
+    }
+}
+
+impl EditorFactory<String> for TextCell {
+    fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<String>>> {
+        Some(Box::new(TextBox::new().expand_height()))
+    }
+
+    fn can_edit(&self, _ctx: &CellCtx) -> bool {
+        true
+    }
+}
+
+// Renders numbers through a TextCell, right aligned by default
+#[derive(Clone)]
+pub struct NumberCell {
+    decimals: usize,
+    thousands_separator: Option<char>,
+    prefix: String,
+    suffix: String,
+    placeholder: String,
+    text: TextCell,
+}
+
+impl NumberCell {
+    pub fn new() -> Self {
+        NumberCell {
+            decimals: 2,
+            thousands_separator: None,
+            prefix: String::new(),
+            suffix: String::new(),
+            placeholder: "-".to_string(),
+            text: TextCell::new().text_alignment(TextAlignment::End),
+        }
+    }
+
+    // Digits after the decimal point, ignored for integers
+    pub fn decimals(mut self, decimals: usize) -> NumberCell {
+        self.decimals = decimals;
+        self
+    }
+
+    pub fn thousands_separator(mut self, separator: char) -> NumberCell {
+        self.thousands_separator = Some(separator);
+        self
+    }
+
+    pub fn prefix(mut self, prefix: impl Into<String>) -> NumberCell {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn suffix(mut self, suffix: impl Into<String>) -> NumberCell {
+        self.suffix = suffix.into();
+        self
+    }
+
+    // Shown instead of NaN or infinite values
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> NumberCell {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn text_cell(mut self, text: TextCell) -> NumberCell {
+        self.text = text;
+        self
+    }
+
+    fn format_f64(&self, value: f64) -> String {
+        if !value.is_finite() {
+            return self.placeholder.clone();
+        }
+        let digits = format!("{:.*}", self.decimals, value.abs());
+        let (int_part, frac_part) = match digits.find('.') {
+            Some(dot) => digits.split_at(dot),
+            None => (digits.as_str(), ""),
+        };
+        // Rounding can leave "-0.00", which should not carry a sign
+        let negative = value < 0. && digits.bytes().any(|b| b.is_ascii_digit() && b != b'0');
+        self.decorate(negative, int_part, frac_part)
+    }
+
+    fn format_i64(&self, value: i64) -> String {
+        // Widen first so i64::MIN doesn't overflow
+        let digits = (value as i128).abs().to_string();
+        self.decorate(value < 0, &digits, "")
+    }
+
+    fn decorate(&self, negative: bool, int_part: &str, frac_part: &str) -> String {
+        let mut out = String::new();
+        if negative {
+            out.push('-');
+        }
+        out.push_str(&self.prefix);
+        let len = int_part.len();
+        for (i, c) in int_part.chars().enumerate() {
+            if i > 0 && (len - i) % 3 == 0 {
+                if let Some(sep) = self.thousands_separator {
+                    out.push(sep);
+                }
+            }
+            out.push(c);
+        }
+        out.push_str(frac_part);
+        out.push_str(&self.suffix);
+        out
+    }
+}
+
+impl Default for NumberCell {
+    fn default() -> Self {
+        NumberCell::new()
+    }
+}
+
+impl CellRender<f64> for NumberCell {
+    fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        self.text.init(ctx, env)
+    }
+
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &f64, env: &Env) {
+        self.text.paint(ctx, cell, &self.format_f64(*data), env)
+    }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &f64,
+        env: &Env,
+    ) -> Option<f64> {
+        self.text
+            .measure(text, axis, cell, &self.format_f64(*data), env)
+    }
+
+    fn as_text(&self, _cell: &CellCtx, data: &f64, _env: &Env) -> Option<String> {
+        Some(self.format_f64(*data))
+    }
+
+    fn as_number(&self, _cell: &CellCtx, data: &f64, _env: &Env) -> Option<f64> {
+        Some(*data)
+    }
+}
+
+impl CellRender<i64> for NumberCell {
+    fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        self.text.init(ctx, env)
+    }
+
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &i64, env: &Env) {
+        self.text.paint(ctx, cell, &self.format_i64(*data), env)
+    }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &i64,
+        env: &Env,
+    ) -> Option<f64> {
+        self.text
+            .measure(text, axis, cell, &self.format_i64(*data), env)
+    }
+
+    fn as_text(&self, _cell: &CellCtx, data: &i64, _env: &Env) -> Option<String> {
+        Some(self.format_i64(*data))
+    }
+
+    fn as_number(&self, _cell: &CellCtx, data: &i64, _env: &Env) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+impl DataCompare<f64> for NumberCell {
+    // NaN sorts after every other value
+    fn compare(&self, a: &f64, b: &f64) -> Ordering {
+        a.partial_cmp(b)
+            .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+    }
+}
+
+impl DataCompare<i64> for NumberCell {
+    fn compare(&self, a: &i64, b: &i64) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl EditorFactory<f64> for NumberCell {
+    fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<f64>>> {
+        None
+    }
+
+    fn can_edit(&self, _ctx: &CellCtx) -> bool {
+        false
+    }
+}
+
+impl EditorFactory<i64> for NumberCell {
+    fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<i64>>> {
+        None
+    }
+
+    fn can_edit(&self, _ctx: &CellCtx) -> bool {
+        false
+    }
+}
+
+// Shows the label of the current value, and offers the choices when edited
+#[derive(Clone)]
+pub struct ChoiceCell<T> {
+    choices: Vec<(String, T)>,
+    text: TextCell,
+}
+
+impl<T: Data + PartialEq> ChoiceCell<T> {
+    pub fn new(choices: Vec<(String, T)>) -> Self {
+        ChoiceCell {
+            choices,
+            text: TextCell::new(),
+        }
+    }
+
+    pub fn text_cell(mut self, text: TextCell) -> Self {
+        self.text = text;
+        self
+    }
+
+    fn position(&self, value: &T) -> Option<usize> {
+        self.choices.iter().position(|(_, v)| v == value)
+    }
+
+    fn label(&self, value: &T) -> Option<&String> {
+        self.position(value).map(|pos| &self.choices[pos].0)
+    }
+}
+
+impl<T: Data + PartialEq> CellRender<T> for ChoiceCell<T> {
+    fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        self.text.init(ctx, env)
+    }
+
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env) {
+        if let Some(label) = self.label(data) {
+            self.text.paint(ctx, cell, label, env)
+        }
+    }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        self.label(data)
+            .and_then(|label| self.text.measure(text, axis, cell, label, env))
+    }
+
+    fn as_text(&self, _cell: &CellCtx, data: &T, _env: &Env) -> Option<String> {
+        self.label(data).cloned()
+    }
+}
+
+impl<T: Data + PartialEq> DataCompare<T> for ChoiceCell<T> {
+    // Choices sort in the order they were given, unknown values last
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        let pos = |v| self.position(v).unwrap_or(usize::MAX);
+        pos(a).cmp(&pos(b))
+    }
+}
+
+impl<T: Data + PartialEq> EditorFactory<T> for ChoiceCell<T> {
+    // The cell only has room for one choice, so the editor steps through them in place
+    fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<T>>> {
+        let choices = self.choices.clone();
+        let label = Label::new(move |value: &T, _: &Env| {
+            choices
+                .iter()
+                .find(|(_, v)| v == value)
+                .map(|(name, _)| name.clone())
+                .unwrap_or_default()
+        });
+        let cycle = CycleChoices {
+            choices: self.choices.clone(),
+        };
+        Some(Box::new(
+            label.controller(cycle).background(theme::BACKGROUND_LIGHT),
+        ))
+    }
+
+    fn can_edit(&self, _ctx: &CellCtx) -> bool {
+        true
+    }
+}
+
+// Clicking moves on to the next choice, or back with shift. Once clicked the arrow keys and
+// space do the same.
+struct CycleChoices<T> {
+    choices: Vec<(String, T)>,
+}
+
+impl<T: Data + PartialEq> CycleChoices<T> {
+    // Values that aren't among the choices start again from the first
+    fn step(&self, value: &mut T, back: bool) {
+        let count = self.choices.len();
+        if count == 0 {
+            return;
+        }
+        let next = match self.choices.iter().position(|(_, v)| v == value) {
+            Some(at) if back => (at + count - 1) % count,
+            Some(at) => (at + 1) % count,
+            None => 0,
+        };
+        *value = self.choices[next].1.clone();
+    }
+}
+
+impl<T: Data + PartialEq, W: Widget<T>> Controller<T, W> for CycleChoices<T> {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let back = match event {
+            Event::MouseDown(me) if me.button.is_left() => {
+                ctx.request_focus();
+                me.mods.shift()
+            }
+            Event::KeyDown(ke) => match &ke.key {
+                KbKey::ArrowDown | KbKey::ArrowRight => false,
+                KbKey::ArrowUp | KbKey::ArrowLeft => true,
+                KbKey::Character(typed) if typed == " " => ke.mods.shift(),
+                _ => return child.event(ctx, event, data, env),
+            },
+            _ => return child.event(ctx, event, data, env),
+        };
+        self.step(data, back);
+        ctx.set_handled();
+    }
+
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &T,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            ctx.register_for_focus();
+        }
+        child.lifecycle(ctx, event, data, env)
+    }
+}
+
+pub(crate) struct HeaderCell<T, I: CellRender<T>> {
+    inner: I,
+    phantom_t: PhantomData<T>,
+}
+
+impl<T, I: CellRender<T>> HeaderCell<T, I> {
+    pub fn new(inner: I) -> Self {
+        HeaderCell {
+            inner,
+            phantom_t: Default::default(),
+        }
+    }
+}
+
+fn make_arrow(top_point: &Point, up: bool, height: f64, head_rad: f64) -> Vec<PathEl> {
+    let start_y = top_point.y;
+    let tip_y = start_y + height;
+
+    let (start_y, tip_y, mult) = if up {
+        (tip_y, start_y, -1.)
+    } else {
+        (start_y, tip_y, 1.0)
+    };
+    let head_start_y = tip_y - (head_rad * mult);
+
+    let mid_x = top_point.x;
+
+    let arrow = vec![
+        PathEl::MoveTo((mid_x, start_y).into()),
+        PathEl::LineTo((mid_x, tip_y).into()),
+        PathEl::LineTo((mid_x - head_rad, head_start_y).into()),
+        PathEl::MoveTo((mid_x, tip_y).into()),
+        PathEl::LineTo((mid_x + head_rad, head_start_y).into()),
+    ];
+    arrow
+}
+
+// Width taken from a sorted heading's content by its arrow
+fn arrow_room(size: Size) -> f64 {
+    (size.height * 0.25 + 3.) * 2.
+}
+
+// Set by the headings to the height of one line of a heading's content, so the arrow and badge
+// keep to the first line of a wrapped heading
+pub(crate) const HEADER_LINE_HEIGHT: Key<f64> = Key::new("druid-table.header-line-height");
+
+// The part of a heading's content that the arrow and badge sit beside
+fn first_line(size: Size, env: &Env) -> Size {
+    let line = env.try_get(HEADER_LINE_HEIGHT).unwrap_or(size.height);
+    Size::new(size.width, line.min(size.height))
+}
+
+// Set by the headings to how many columns the rows are sorted by
+pub(crate) const SORTED_COLUMNS: Key<u64> = Key::new("druid-table.sorted-columns");
+// The colour of the sort arrows and priorities, see TableConfig::sort_indicator_color
+pub(crate) const SORT_INDICATOR_COLOR: Key<Color> = Key::new("druid-table.sort-indicator-color");
+
+fn sort_indicator_color(env: &Env) -> Color {
+    env.try_get(SORT_INDICATOR_COLOR)
+        .unwrap_or_else(|_| env.get(theme::TEXT_COLOR))
+}
+
+// The sort's place among several, numbered from 1 and drawn beside the arrow
+fn priority_badge(
+    text: &mut PietText,
+    ss: &SortSpec,
+    size: Size,
+    env: &Env,
+) -> Option<PietTextLayout> {
+    if env.try_get(SORTED_COLUMNS).unwrap_or(0) < 2 {
+        return None;
+    }
+    text.new_text_layout((ss.idx + 1).to_string())
+        .font(FontFamily::SYSTEM_UI, size.height * 0.5)
+        .text_color(sort_indicator_color(env))
+        .build()
+        .ok()
+}
+
+impl<T, I: CellRender<T>> CellRender<T> for HeaderCell<T, I> {
+    fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        self.inner.init(ctx, env);
+    }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        match cell {
+            CellCtx::Header(header_axis, log_idx, Some(ss), size) => {
+                let line = first_line(*size, env);
+                let badge = priority_badge(text, ss, line, env);
+                let room = arrow_room(line) + badge.map_or(0., |badge| badge.size().width);
+                match axis {
+                    TableAxis::Columns => {
+                        Some(self.inner.measure(text, axis, cell, data, env)? + room)
+                    }
+                    // Wrapped, the content only gets the width the arrow and badge leave
+                    TableAxis::Rows => {
+                        let size = Size::new((size.width - room).max(0.), size.height);
+                        let inner_cell = CellCtx::Header(*header_axis, *log_idx, Some(*ss), size);
+                        self.inner.measure(text, axis, &inner_cell, data, env)
+                    }
+                }
+            }
+            _ => self.inner.measure(text, axis, cell, data, env),
+        }
+    }
+
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        self.inner.as_text(cell, data, env)
+    }
+
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        self.inner.as_number(cell, data, env)
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        self.inner.is_loading(cell, data, env)
+    }
+
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        self.inner.background(cell, data, env)
     }
 
     fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env) {
         match cell {
-            CellCtx::Header(_, _, Some(ss)) => {
-                // TODO The size should be on the CellCtx, should not be using region
-                let rect = ctx
-                    .region()
-                    .bounding_box()
-                    .with_origin(Point::ORIGIN)
-                    .inset(-3.);
-                let rad = rect.height() * 0.25;
+            CellCtx::Header(axis, log_idx, Some(ss), size) => {
+                // The size is already inside the cell padding, so the arrow sits
+                // against the same edge as the content does
+                let rect = Rect::from_origin_size(Point::ORIGIN, *size);
+                let line = first_line(*size, env);
+                let rad = line.height * 0.25;
                 let up = ss.direction == Ascending;
+                // Right to left, the arrow and badge go on the left instead
+                let rtl = env.try_get(RIGHT_TO_LEFT).unwrap_or(false);
+
+                let arrow_x = if rtl {
+                    rect.min_x() + rad
+                } else {
+                    rect.max_x() - rad
+                };
+                let arrow = make_arrow(&Point::new(arrow_x, rect.min_y()), up, line.height, rad);
+                let color = sort_indicator_color(env);
+                ctx.render_ctx.stroke(&arrow[..], &color, 1.0);
+                let mut room = arrow_room(line);
+                if let Some(badge) = priority_badge(ctx.text(), ss, line, env) {
+                    let badge_size = badge.size();
+                    let x = if rtl {
+                        room
+                    } else {
+                        rect.max_x() - room - badge_size.width
+                    };
+                    room += badge_size.width;
+                    let y = (line.height - badge_size.height) / 2.;
+                    ctx.draw_text(&badge, (x, y));
+                }
+                // The content is clipped short of the arrow and any badge
+                let rect1 = rect.with_size((rect.width() - room, rect.height()));
+                if rtl {
+                    ctx.transform(Affine::translate((room, 0.)));
+                }
+                ctx.clip(rect1);
+                let inner_cell = CellCtx::Header(*axis, *log_idx, Some(*ss), rect1.size());
+                self.inner.paint(ctx, &inner_cell, data, env);
+            }
+            _ => {
+                self.inner.paint(ctx, cell, data, env);
+            }
+        }
+    }
+}
+
+impl DataCompare<String> for TextCell {
+    fn compare(&self, a: &String, b: &String) -> Ordering {
+        if self.natural_sort {
+            natural_cmp(a, b, self.ignore_case)
+        } else if self.ignore_case {
+            a.to_lowercase()
+                .cmp(&b.to_lowercase())
+                .then_with(|| a.cmp(b))
+        } else {
+            a.cmp(b)
+        }
+    }
+}
+
+// Runs of ASCII digits compare by value, anything else (including other scripts' digits)
+// character by character. Equal values with more leading zeros go after, "a01" after "a1".
+fn natural_cmp(a: &str, b: &str, ignore_case: bool) -> Ordering {
+    let (mut a_chars, mut b_chars) = (a.chars().peekable(), b.chars().peekable());
+    let mut zeros = Ordering::Equal;
+    loop {
+        let ord = match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => break,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, y) = (digit_run(&mut a_chars), digit_run(&mut b_chars));
+                let (x_val, y_val) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                zeros = zeros.then(x.len().cmp(&y.len()));
+                x_val.len().cmp(&y_val.len()).then_with(|| x_val.cmp(y_val))
+            }
+            (Some(x), Some(y)) => {
+                a_chars.next();
+                b_chars.next();
+                if ignore_case {
+                    x.to_lowercase().cmp(y.to_lowercase())
+                } else {
+                    x.cmp(&y)
+                }
+            }
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    zeros.then_with(|| a.cmp(b))
+}
+
+fn digit_run(chars: &mut Peekable<Chars>) -> String {
+    let mut run = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        run.push(digit);
+    }
+    run
+}
+
+#[derive(Clone)]
+pub struct CheckboxCell {
+    box_size: KeyOrValue<f64>,
+    border_color: KeyOrValue<Color>,
+    check_color: KeyOrValue<Color>,
+}
+
+impl CheckboxCell {
+    pub fn new() -> Self {
+        CheckboxCell {
+            box_size: theme::BASIC_WIDGET_HEIGHT.into(),
+            border_color: theme::BORDER_LIGHT.into(),
+            check_color: theme::LABEL_COLOR.into(),
+        }
+    }
+
+    pub fn box_size(mut self, box_size: impl Into<KeyOrValue<f64>>) -> CheckboxCell {
+        self.box_size = box_size.into();
+        self
+    }
+
+    pub fn border_color(mut self, border_color: impl Into<KeyOrValue<Color>>) -> CheckboxCell {
+        self.border_color = border_color.into();
+        self
+    }
+
+    pub fn check_color(mut self, check_color: impl Into<KeyOrValue<Color>>) -> CheckboxCell {
+        self.check_color = check_color.into();
+        self
+    }
+}
+
+impl Default for CheckboxCell {
+    fn default() -> Self {
+        CheckboxCell::new()
+    }
+}
+
+impl CellRender<bool> for CheckboxCell {
+    fn init(&mut self, _ctx: &mut PaintCtx, _env: &Env) {}
+
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &bool, env: &Env) {
+        // The paint region can be just the damaged part of the cell, so only the cell's own
+        // size will do
+        let cell_size = match cell.size() {
+            Some(size) => size,
+            None => return,
+        };
+        let side = self
+            .box_size
+            .resolve(env)
+            .min(cell_size.width)
+            .min(cell_size.height)
+            - 1.;
+        let rect = Rect::from_center_size(cell_size.to_rect().center(), (side, side));
+
+        ctx.stroke(
+            RoundedRect::from_rect(rect, 2.),
+            &self.border_color.resolve(env),
+            1.,
+        );
+
+        if *data {
+            let check = vec![
+                PathEl::MoveTo((rect.x0 + side * 0.2, rect.y0 + side * 0.5).into()),
+                PathEl::LineTo((rect.x0 + side * 0.4, rect.y0 + side * 0.75).into()),
+                PathEl::LineTo((rect.x0 + side * 0.8, rect.y0 + side * 0.25).into()),
+            ];
+            ctx.stroke(&check[..], &self.check_color.resolve(env), 2.);
+        }
+    }
+}
+
+impl DataCompare<bool> for CheckboxCell {
+    fn compare(&self, a: &bool, b: &bool) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl EditorFactory<bool> for CheckboxCell {
+    fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<bool>>> {
+        Some(Box::new(Checkbox::new("").center()))
+    }
+
+    fn can_edit(&self, _ctx: &CellCtx) -> bool {
+        true
+    }
+}
+
+// What sorts a SparklineCell's series, and what it offers as the cell's number
+#[derive(Clone, Copy, Debug, Data, Eq, PartialEq)]
+pub enum SeriesValue {
+    Last,
+    Sum,
+}
+
+impl SeriesValue {
+    // None for an empty series. Values that aren't finite are left out.
+    fn of(&self, series: &Vector<f64>) -> Option<f64> {
+        let mut finite = series.iter().copied().filter(|v| v.is_finite()).peekable();
+        finite.peek()?;
+        match self {
+            SeriesValue::Last => finite.last(),
+            SeriesValue::Sum => Some(finite.sum()),
+        }
+    }
+}
+
+// A small line chart of a series, scaled to fill the cell. An empty series is left blank.
+#[derive(Clone)]
+pub struct SparklineCell {
+    line_color: KeyOrValue<Color>,
+    fill_color: Option<KeyOrValue<Color>>,
+    line_width: f64,
+    extremes: bool,
+    value: SeriesValue,
+}
+
+impl SparklineCell {
+    pub fn new() -> Self {
+        SparklineCell {
+            line_color: theme::PRIMARY_LIGHT.into(),
+            fill_color: None,
+            line_width: 1.,
+            extremes: false,
+            value: SeriesValue::Last,
+        }
+    }
+
+    pub fn line_color(mut self, line_color: impl Into<KeyOrValue<Color>>) -> SparklineCell {
+        self.line_color = line_color.into();
+        self
+    }
+
+    // Fills the area under the line
+    pub fn fill_color(mut self, fill_color: impl Into<KeyOrValue<Color>>) -> SparklineCell {
+        self.fill_color = Some(fill_color.into());
+        self
+    }
+
+    pub fn line_width(mut self, line_width: f64) -> SparklineCell {
+        self.line_width = line_width;
+        self
+    }
+
+    // Marks the lowest and highest values with dots
+    pub fn extremes(mut self, extremes: bool) -> SparklineCell {
+        self.extremes = extremes;
+        self
+    }
 
-                let arrow = make_arrow(
-                    &Point::new(rect.max_x() - rad, rect.min_y()),
-                    up,
-                    rect.height(),
-                    rad,
-                );
-                ctx.render_ctx.stroke(&arrow[..], &Color::WHITE, 1.0);
-                let rect1 = ctx.region().bounding_box();
-                let rect1 = rect1
-                    .with_origin(Point::ORIGIN)
-                    .with_size((rect1.width() - (rad + 3.) * 2., rect1.height()));
-                ctx.clip(rect1);
-                self.inner.paint(ctx, cell, data, env);
+    pub fn value(mut self, value: SeriesValue) -> SparklineCell {
+        self.value = value;
+        self
+    }
+
+    // Evenly spaced along the width, with the lowest value at the bottom and the highest at
+    // the top. Values that aren't finite leave gaps, so have no point.
+    fn points(&self, series: &Vector<f64>, size: Size) -> Vec<Option<Point>> {
+        let finite = || series.iter().copied().filter(|v| v.is_finite());
+        let min = finite().fold(f64::INFINITY, f64::min);
+        let max = finite().fold(f64::NEG_INFINITY, f64::max);
+        let inset = if self.extremes {
+            EXTREME_RADIUS
+        } else {
+            self.line_width / 2.
+        };
+        let width = (size.width - inset * 2.).max(0.);
+        let height = (size.height - inset * 2.).max(0.);
+        // A lone value sits in the middle, as does a flat series
+        let step = width / (series.len().max(2) - 1) as f64;
+        let offset = if series.len() > 1 { 0. } else { width / 2. };
+        let span = max - min;
+        series
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let scaled = if span > 0. { (v - min) / span } else { 0.5 };
+                let x = offset + step * i as f64;
+                v.is_finite()
+                    .then(|| Point::new(inset + x, inset + height * (1. - scaled)))
+            })
+            .collect()
+    }
+}
+
+impl Default for SparklineCell {
+    fn default() -> Self {
+        SparklineCell::new()
+    }
+}
+
+const EXTREME_RADIUS: f64 = 2.;
+
+impl CellRender<Vector<f64>> for SparklineCell {
+    fn init(&mut self, _ctx: &mut PaintCtx, _env: &Env) {}
+
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &Vector<f64>, env: &Env) {
+        let size = match cell.size() {
+            Some(size) => size,
+            None => return,
+        };
+        let points = self.points(data, size);
+        let line_color = self.line_color.resolve(env);
+        // Each run of points between gaps is its own line
+        for run in points.split(Option::is_none).filter(|run| !run.is_empty()) {
+            let run: Vec<Point> = run.iter().flatten().copied().collect();
+            if let Some(fill_color) = &self.fill_color {
+                let bottom = size.height;
+                let mut area = vec![PathEl::MoveTo(Point::new(run[0].x, bottom))];
+                area.extend(run.iter().map(|point| PathEl::LineTo(*point)));
+                area.push(PathEl::LineTo(Point::new(run[run.len() - 1].x, bottom)));
+                area.push(PathEl::ClosePath);
+                ctx.fill(&area[..], &fill_color.resolve(env));
             }
-            _ => {
-                self.inner.paint(ctx, cell, data, env);
+            if run.len() == 1 {
+                ctx.fill(Circle::new(run[0], self.line_width), &line_color);
+            } else {
+                let mut line = vec![PathEl::MoveTo(run[0])];
+                line.extend(run[1..].iter().map(|point| PathEl::LineTo(*point)));
+                ctx.stroke(&line[..], &line_color, self.line_width);
+            }
+        }
+
+        if self.extremes {
+            let lowest = points.iter().flatten().max_by(|a, b| a.y.total_cmp(&b.y));
+            let highest = points.iter().flatten().min_by(|a, b| a.y.total_cmp(&b.y));
+            for point in lowest.into_iter().chain(highest) {
+                ctx.fill(Circle::new(*point, EXTREME_RADIUS), &line_color);
             }
         }
     }
+
+    fn as_text(&self, _cell: &CellCtx, data: &Vector<f64>, _env: &Env) -> Option<String> {
+        let values: Vec<String> = data.iter().map(|v| v.to_string()).collect();
+        Some(values.join(", "))
+    }
+
+    fn as_number(&self, _cell: &CellCtx, data: &Vector<f64>, _env: &Env) -> Option<f64> {
+        self.value.of(data)
+    }
 }
 
-impl DataCompare<String> for TextCell {
-    fn compare(&self, a: &String, b: &String) -> Ordering {
-        a.cmp(b)
+impl DataCompare<Vector<f64>> for SparklineCell {
+    // Empty series sort first
+    fn compare(&self, a: &Vector<f64>, b: &Vector<f64>) -> Ordering {
+        let (a, b) = (self.value.of(a), self.value.of(b));
+        a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl EditorFactory<Vector<f64>> for SparklineCell {
+    fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<Vector<f64>>>> {
+        None
+    }
+
+    fn can_edit(&self, _ctx: &CellCtx) -> bool {
+        false
+    }
+}
+
+// A summary of a column's shown rows, for the footer
+#[derive(Clone, Copy, Debug, Data, Eq, PartialEq)]
+pub enum Aggregate {
+    Sum,
+    Average,
+    Count,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    // The values are None for cells that aren't numeric. Apart from Count, which just counts
+    // rows, any of those give None, as do no values at all.
+    pub fn compute(&self, values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+        let values: Vec<Option<f64>> = values.collect();
+        if values.is_empty() {
+            return None;
+        } else if let Aggregate::Count = self {
+            return Some(values.len() as f64);
+        }
+        let values: Vec<f64> = values.into_iter().collect::<Option<_>>()?;
+        Some(match self {
+            Aggregate::Sum => values.iter().sum(),
+            Aggregate::Average => values.iter().sum::<f64>() / values.len() as f64,
+            Aggregate::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Aggregate::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Aggregate::Count => unreachable!(),
+        })
     }
+
+    // As the footer shows it, where counting or summing no rows gives 0 rather than a blank
+    pub(crate) fn apply(&self, values: impl Iterator<Item = Option<f64>>) -> Option<String> {
+        let mut values = values.peekable();
+        let result = match self {
+            Aggregate::Sum | Aggregate::Count if values.peek().is_none() => 0.,
+            _ => self.compute(values)?,
+        };
+        // Rounded so float error doesn't show, dropping the zeros that leaves
+        let text = format!("{:.6}", result);
+        Some(text.trim_end_matches('0').trim_end_matches('.').to_string())
+    }
+}
+
+// Aggregates a column outside of the footer, taking each row's number from `value`. Given
+// the rows the table shows, rows filtered out are left out as well; None takes every row.
+pub fn aggregate_column<TableData: IndexedData<Idx = LogIdx>>(
+    data: &TableData,
+    rows: Option<&Remap>,
+    value: impl Fn(&TableData::Item) -> Option<f64>,
+    aggregate: Aggregate,
+) -> Option<f64> {
+    let log_idxs: Vec<LogIdx> = match rows {
+        Some(rows) => (0..rows.vis_len(data.idx_len()))
+            .filter_map(|vis| rows.get_log_idx(VisIdx(vis)))
+            .collect(),
+        None => (0..data.idx_len()).map(LogIdx).collect(),
+    };
+    let values = log_idxs
+        .into_iter()
+        .filter_map(|log_idx| data.with(log_idx, &value));
+    aggregate.compute(values)
 }
 
 pub struct TableColumn<T: Data, CD: CellDelegate<T>> {
@@ -415,6 +2285,15 @@ pub struct TableColumn<T: Data, CD: CellDelegate<T>> {
     pub(crate) sort_order: Option<usize>,
     pub(crate) sort_fixed: bool,
     pub(crate) sort_dir: Option<SortDirection>,
+    pub(crate) footer: Option<Aggregate>,
+    pub(crate) group: Option<String>,
+    pub(crate) header_tooltip: Option<String>,
+    pub(crate) col_span: Option<Box<dyn Fn(&T) -> usize>>,
+    pub(crate) merge_equal: bool,
+    pub(crate) on_click: Option<Selector<LogIdx>>,
+    pub(crate) align: Option<TextAlignment>,
+    pub(crate) padding: Option<Insets>,
+    pub(crate) fill: Option<Box<dyn Fn(&T, &mut T)>>,
     phantom_: PhantomData<T>,
 }
 
@@ -428,16 +2307,34 @@ impl<T: Data, CD: CellDelegate<T>> Debug for TableColumn<T, CD> {
 
 pub struct TableColumnWidth {
     initial: Option<KeyOrValue<f64>>,
-    _min: Option<KeyOrValue<f64>>,
-    _max: Option<KeyOrValue<f64>>,
+    min: Option<KeyOrValue<f64>>,
+    max: Option<KeyOrValue<f64>>,
+    flex: Option<f64>,
+}
+
+impl TableColumnWidth {
+    // The range interactive resizing is clamped to
+    pub(crate) fn limits(&self, env: &Env) -> (f64, f64) {
+        (
+            self.min.as_ref().map_or(0., |min| min.resolve(env)),
+            self.max
+                .as_ref()
+                .map_or(f64::INFINITY, |max| max.resolve(env)),
+        )
+    }
+
+    pub(crate) fn initial(&self, env: &Env) -> Option<f64> {
+        self.initial.as_ref().map(|initial| initial.resolve(env))
+    }
 }
 
 impl Default for TableColumnWidth {
     fn default() -> Self {
         TableColumnWidth {
             initial: Some(50.0.into()), // Could be in a 'theme' I guess.
-            _min: Some(20.0.into()),
-            _max: None,
+            min: Some(20.0.into()),
+            max: None,
+            flex: None,
         }
     }
 }
@@ -457,8 +2354,9 @@ where
     fn from((initial, min, max): (T1, T2, T3)) -> Self {
         TableColumnWidth {
             initial: Some(initial.into()),
-            _min: Some(min.into()),
-            _max: Some(max.into()),
+            min: Some(min.into()),
+            max: Some(max.into()),
+            flex: None,
         }
     }
 }
@@ -478,6 +2376,15 @@ impl<T: Data, CD: CellDelegate<T>> TableColumn<T, CD> {
             sort_order: Default::default(),
             sort_fixed: false,
             sort_dir: None,
+            footer: None,
+            group: None,
+            header_tooltip: None,
+            col_span: None,
+            merge_equal: false,
+            on_click: None,
+            align: None,
+            padding: None,
+            fill: None,
             width: Default::default(),
             phantom_: PhantomData,
         }
@@ -488,6 +2395,13 @@ impl<T: Data, CD: CellDelegate<T>> TableColumn<T, CD> {
         self
     }
 
+    // Share the width left over by other columns, in proportion to the weight.
+    // Still clamped to the min and max width
+    pub fn flex(mut self, weight: f64) -> Self {
+        self.width.flex = Some(weight);
+        self
+    }
+
     pub fn sort<S: Into<SortDirection>>(mut self, sort: S) -> Self {
         self.sort_dir = Some(sort.into());
         self
@@ -497,6 +2411,65 @@ impl<T: Data, CD: CellDelegate<T>> TableColumn<T, CD> {
         self.sort_fixed = true;
         self
     }
+
+    // Shown in a row below the cells, over the rows the filters leave
+    pub fn footer(mut self, aggregate: Aggregate) -> Self {
+        self.footer = Some(aggregate);
+        self
+    }
+
+    // Neighbouring columns with the same group share a heading spanning them
+    pub fn group(mut self, label: impl Into<String>) -> Self {
+        self.group = Some(label.into());
+        self
+    }
+
+    // Shown on hovering over the heading. Without one, a heading too long to fit shows itself.
+    pub fn header_tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.header_tooltip = Some(tooltip.into());
+        self
+    }
+
+    // How many columns this column's cell covers in each row, eg for section separators
+    pub fn col_span(mut self, span: impl Fn(&T) -> usize + 'static) -> Self {
+        self.col_span = Some(Box::new(span));
+        self
+    }
+
+    // Show runs of rows with equal values in this column, as its cells compare them to sort,
+    // as one cell with the value at the top. The runs are of the rows as shown, so they change
+    // as the table is sorted or filtered, and read best when sorted by this column.
+    pub fn merge_equal(mut self) -> Self {
+        self.merge_equal = true;
+        self
+    }
+
+    // Submit the row's logical index when one of this column's cells is clicked and released,
+    // eg for a column of delete buttons. Plain clicks don't select the cell or open an editor,
+    // while ones with modifiers select as usual.
+    pub fn on_click(mut self, selector: Selector<LogIdx>) -> Self {
+        self.on_click = Some(selector);
+        self
+    }
+
+    // Lines up the text of this column's cells, over the alignment of a shared TextCell
+    pub fn align(mut self, align: TextAlignment) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    // Space around this column's cell content, in place of the table's cell padding
+    pub fn cell_padding(mut self, padding: impl Into<Insets>) -> Self {
+        self.padding = Some(padding.into());
+        self
+    }
+
+    // Lets the fill handle copy this column's value from one row to others, by writing the
+    // relevant field of the first row into the second
+    pub fn fillable(mut self, fill: impl Fn(&T, &mut T) + 'static) -> Self {
+        self.fill = Some(Box::new(fill));
+        self
+    }
 }
 
 impl<T: Data, CR: CellDelegate<T>> CellRender<T> for TableColumn<T, CR> {
@@ -505,7 +2478,40 @@ impl<T: Data, CR: CellDelegate<T>> CellRender<T> for TableColumn<T, CR> {
     }
 
     fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &T, env: &Env) {
-        self.cell_delegate.paint(ctx, cell, data, env)
+        match self.align {
+            Some(align) => {
+                let cell = cell.with_alignment(align);
+                self.cell_delegate.paint(ctx, &cell, data, env)
+            }
+            None => self.cell_delegate.paint(ctx, cell, data, env),
+        }
+    }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &T,
+        env: &Env,
+    ) -> Option<f64> {
+        self.cell_delegate.measure(text, axis, cell, data, env)
+    }
+
+    fn as_text(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<String> {
+        self.cell_delegate.as_text(cell, data, env)
+    }
+
+    fn as_number(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<f64> {
+        self.cell_delegate.as_number(cell, data, env)
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &T, env: &Env) -> bool {
+        self.cell_delegate.is_loading(cell, data, env)
+    }
+
+    fn background(&self, cell: &CellCtx, data: &T, env: &Env) -> Option<Color> {
+        self.cell_delegate.background(cell, data, env)
     }
 }
 
@@ -519,6 +2525,10 @@ impl<T: Data, CR: CellDelegate<T>> EditorFactory<T> for TableColumn<T, CR> {
     fn make_editor(&mut self, ctx: &CellCtx) -> Option<Box<dyn Widget<T>>> {
         self.cell_delegate.make_editor(ctx)
     }
+
+    fn can_edit(&self, ctx: &CellCtx) -> bool {
+        self.cell_delegate.can_edit(ctx)
+    }
 }
 
 pub struct ProvidedColumns<TableData: IndexedData, ColumnType: CellDelegate<TableData::Item>>
@@ -557,11 +2567,15 @@ where
         // Put the columns in sort order
         let mut in_order: Vec<(usize, &TableColumn<TableData::Item, ColumnType>)> =
             self.cols.iter().enumerate().collect();
-        in_order.sort_by(|(_, a), (_, b)| match (a.sort_order, b.sort_order) {
-            (Some(a), Some(b)) => a.cmp(&b),
-            (Some(_), _) => Ordering::Greater,
-            (_, Some(_)) => Ordering::Less,
-            _ => Ordering::Equal,
+        // Fixed sorts come first, so the others only sort within them
+        in_order.sort_by(|(_, a), (_, b)| {
+            let by_order = match (a.sort_order, b.sort_order) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), _) => Ordering::Greater,
+                (_, Some(_)) => Ordering::Less,
+                _ => Ordering::Equal,
+            };
+            b.sort_fixed.cmp(&a.sort_fixed).then(by_order)
         });
 
         // Then add the ones which have a
@@ -574,32 +2588,154 @@ where
         spec
     }
 
-    fn remap_items(&self, table_data: &TableData, remap_spec: &RemapSpec) -> Remap {
-        if remap_spec.is_empty() {
-            Remap::new() // Todo: preserve moves
+    fn remap_items(
+        &self,
+        table_data: &TableData,
+        remap_spec: &RemapSpec,
+        prior: &Remap,
+        env: &Env,
+    ) -> Remap {
+        if remap_spec.is_empty() && remap_spec.moved_order.is_none() {
+            Remap::new()
         } else {
-            //Todo: Filter
-            let mut idxs: Vector<LogIdx> = (0usize..table_data.idx_len()).map(LogIdx).collect(); //TODO Give up if too big?
-            idxs.sort_by(|a, b| {
+            // Sorting is stable, so this is also the order within ties
+            let mut idxs: Vector<LogIdx> = remap_spec
+                .row_order(table_data.idx_len())
+                .into_iter()
+                .filter(|log_idx| self.shows_row(table_data, remap_spec, *log_idx, env))
+                .collect(); //TODO Give up if too big?
+            if !remap_spec.sort_by.is_empty() {
+                // Rows tied on every sort column stay in the order they are shown in now
+                let ranks = prior.vis_ranks(table_data.idx_len());
+                idxs.sort_by(|a, b| {
+                    self.compare_rows(table_data, remap_spec, *a, *b)
+                        .then_with(|| ranks[a.0].cmp(&ranks[b.0]))
+                });
+            }
+            match remap_spec.group_by {
+                Some(group_col) => self.grouped(table_data, group_col, idxs),
+                None => Remap::selected(RemapDetails::Full(idxs)),
+            }
+        }
+    }
+
+    fn remap_item_changed(
+        &self,
+        table_data: &TableData,
+        remap_spec: &RemapSpec,
+        prior: &Remap,
+        log_idx: LogIdx,
+        env: &Env,
+    ) -> Option<Remap> {
+        let mut order = match prior {
+            Remap::Selected(RemapDetails::Full(order), _) if !remap_spec.sort_by.is_empty() => {
+                order.clone()
+            }
+            _ => return None,
+        };
+        let was_at = prior.get_vis_idx(log_idx).map(|vis| vis.0);
+        if let Some(pos) = was_at {
+            order.remove(pos);
+        }
+        if self.shows_row(table_data, remap_spec, log_idx, env) {
+            let cmp = |a: LogIdx, b: LogIdx| self.compare_rows(table_data, remap_spec, a, b);
+            // Stay put if still in order with the neighbours, so ties don't move
+            let in_place = was_at.filter(|pos| {
+                let after_prev = *pos == 0 || cmp(order[*pos - 1], log_idx) != Ordering::Greater;
+                let before_next = order
+                    .get(*pos)
+                    .map_or(true, |next| cmp(log_idx, *next) != Ordering::Greater);
+                after_prev && before_next
+            });
+            // Otherwise go after any rows it ties with
+            let at = in_place.unwrap_or_else(|| {
+                match order.binary_search_by(|probe| cmp(*probe, log_idx).then(Ordering::Less)) {
+                    Ok(pos) | Err(pos) => pos,
+                }
+            });
+            order.insert(at, log_idx);
+        }
+        Some(Remap::selected(RemapDetails::Full(order)))
+    }
+}
+
+impl<TableData: IndexedData<Idx = LogIdx>, ColumnType: CellDelegate<TableData::Item>>
+    ProvidedColumns<TableData, ColumnType>
+where
+    TableData::Item: Data,
+{
+    fn shows_row(
+        &self,
+        table_data: &TableData,
+        remap_spec: &RemapSpec,
+        log_idx: LogIdx,
+        env: &Env,
+    ) -> bool {
+        table_data
+            .with(log_idx, |row| {
+                remap_spec.filters.iter().all(|filter| {
+                    let text = self
+                        .cols
+                        .get(filter.idx)
+                        .and_then(|col| col.as_text(&CellCtx::Absent, row, env));
+                    filter.matches(text)
+                })
+            })
+            .unwrap_or(false)
+    }
+
+    // Clusters rows with equal values in `group_col`, each group keeping the order given and
+    // starting with a header
+    fn grouped(&self, table_data: &TableData, group_col: usize, mut idxs: Vector<LogIdx>) -> Remap {
+        let cmp = |a: LogIdx, b: LogIdx| self.compare_column(table_data, group_col, a, b);
+        idxs.sort_by(|a, b| cmp(*a, *b));
+        let mut grouped = Vector::new();
+        let mut prev: Option<LogIdx> = None;
+        for log_idx in idxs {
+            if prev.map_or(true, |prev| cmp(prev, log_idx) != Ordering::Equal) {
+                grouped.push_back(None);
+            }
+            grouped.push_back(Some(log_idx));
+            prev = Some(log_idx);
+        }
+        Remap::selected(RemapDetails::Grouped(grouped))
+    }
+
+    fn compare_column(&self, table_data: &TableData, col: usize, a: LogIdx, b: LogIdx) -> Ordering {
+        self.cols
+            .get(col)
+            .and_then(|col| {
+                table_data
+                    .with(a, |a| table_data.with(b, |b| col.compare(a, b)))
+                    .flatten()
+            })
+            .unwrap_or(Ordering::Equal)
+    }
+
+    // By the sort columns alone
+    fn compare_rows(
+        &self,
+        table_data: &TableData,
+        remap_spec: &RemapSpec,
+        a: LogIdx,
+        b: LogIdx,
+    ) -> Ordering {
+        table_data
+            .with(a, |a| {
                 table_data
-                    .with(*a, |a| {
-                        table_data
-                            .with(*b, |b| {
-                                for SortSpec { idx, direction } in &remap_spec.sort_by {
-                                    let col = self.cols.get(*idx).unwrap();
-                                    let ord = col.compare(a, b);
-                                    if ord != Ordering::Equal {
-                                        return direction.apply(ord);
-                                    }
-                                }
-                                Ordering::Equal
-                            })
-                            .unwrap()
+                    .with(b, |b| {
+                        for SortSpec { idx, direction } in &remap_spec.sort_by {
+                            let col = self.cols.get(*idx).unwrap();
+                            let ord = col.compare(a, b);
+                            if ord != Ordering::Equal {
+                                return direction.apply(ord);
+                            }
+                        }
+                        Ordering::Equal
                     })
                     .unwrap()
-            });
-            Remap::Selected(RemapDetails::Full(idxs))
-        }
+            })
+            .unwrap()
     }
 }
 
@@ -615,6 +2751,33 @@ where
     fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &TableData::Item, env: &Env) {
         self.cols.paint(ctx, cell, data, env);
     }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &TableData::Item,
+        env: &Env,
+    ) -> Option<f64> {
+        self.cols.measure(text, axis, cell, data, env)
+    }
+
+    fn as_text(&self, cell: &CellCtx, data: &TableData::Item, env: &Env) -> Option<String> {
+        self.cols.as_text(cell, data, env)
+    }
+
+    fn as_number(&self, cell: &CellCtx, data: &TableData::Item, env: &Env) -> Option<f64> {
+        self.cols.as_number(cell, data, env)
+    }
+
+    fn is_loading(&self, cell: &CellCtx, data: &TableData::Item, env: &Env) -> bool {
+        self.cols.is_loading(cell, data, env)
+    }
+
+    fn background(&self, cell: &CellCtx, data: &TableData::Item, env: &Env) -> Option<Color> {
+        self.cols.background(cell, data, env)
+    }
 }
 
 impl<TableData: IndexedData<Idx = LogIdx>, ColumnType: CellDelegate<TableData::Item>>
@@ -628,6 +2791,10 @@ where
     ) -> Option<Box<dyn Widget<<TableData as IndexedItems>::Item>>> {
         self.cols.make_editor(ctx)
     }
+
+    fn can_edit(&self, ctx: &CellCtx) -> bool {
+        self.cols.can_edit(ctx)
+    }
 }
 
 impl<TableData: IndexedData<Idx = LogIdx>, ColumnType: CellDelegate<TableData::Item>>
@@ -638,4 +2805,715 @@ where
     fn number_of_columns_in_data(&self, _data: &TableData) -> usize {
         self.cols.len()
     }
+
+    fn column_width_limits(&self, env: &Env) -> Vec<(f64, f64)> {
+        self.cols.iter().map(|col| col.width.limits(env)).collect()
+    }
+
+    fn column_initial_widths(&self, env: &Env) -> Vec<Option<f64>> {
+        self.cols.iter().map(|col| col.width.initial(env)).collect()
+    }
+
+    fn column_flex_weights(&self) -> Vec<f64> {
+        self.cols
+            .iter()
+            .map(|col| col.width.flex.unwrap_or(0.))
+            .collect()
+    }
+
+    fn col_span(&self, row: &TableData::Item, col: LogIdx) -> usize {
+        let column = self.cols.get(col.0);
+        match column.and_then(|column| column.col_span.as_ref()) {
+            Some(span) => span(row),
+            None => 1,
+        }
+    }
+
+    fn spans_columns(&self) -> bool {
+        self.cols.iter().any(|col| col.col_span.is_some())
+    }
+
+    fn merges_equal(&self, col: LogIdx) -> bool {
+        self.cols
+            .get(col.0)
+            .map_or(false, |column| column.merge_equal)
+    }
+
+    fn on_click(&self, col: LogIdx) -> Option<Selector<LogIdx>> {
+        self.cols.get(col.0).and_then(|column| column.on_click)
+    }
+
+    fn equal_in_column(&self, col: LogIdx, a: &TableData::Item, b: &TableData::Item) -> bool {
+        self.cols
+            .get(col.0)
+            .map_or(false, |column| column.compare(a, b) == Ordering::Equal)
+    }
+
+    fn column_padding(&self, col: LogIdx) -> Option<Insets> {
+        self.cols.get(col.0)?.padding
+    }
+
+    fn can_fill(&self, col: LogIdx) -> bool {
+        let column = self.cols.get(col.0);
+        column.map_or(false, |column| column.fill.is_some())
+    }
+
+    fn fill(&self, col: LogIdx, from: &TableData::Item, to: &mut TableData::Item) {
+        if let Some(fill) = self.cols.get(col.0).and_then(|column| column.fill.as_ref()) {
+            fill(from, to)
+        }
+    }
+
+    fn column_header(&self, col: LogIdx) -> Option<String> {
+        self.cols.get(col.0).map(|column| column.header.clone())
+    }
+
+    fn rename_column(&mut self, col: LogIdx, header: String) {
+        if let Some(column) = self.cols.get_mut(col.0) {
+            column.header = header;
+        }
+    }
+
+    fn has_footer(&self) -> bool {
+        self.cols.iter().any(|col| col.footer.is_some())
+    }
+
+    fn footer_value(
+        &self,
+        col: LogIdx,
+        data: &TableData,
+        rows: &Remap,
+        env: &Env,
+    ) -> Option<String> {
+        let column = self.cols.get(col.0)?;
+        let aggregate = column.footer?;
+        let values = (0..rows.vis_len(data.idx_len()))
+            .filter_map(|vis| rows.get_log_idx(VisIdx(vis)))
+            .map(|log_idx| {
+                data.with(log_idx, |row| column.as_number(&CellCtx::Absent, row, env))
+                    .flatten()
+            });
+        aggregate.apply(values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::axis_measure::{AxisPair, LogIdx, VisIdx};
+    use crate::columns::{
+        aggregate_column, alignment_key, column, Aggregate, CycleChoices, NoneOrder,
+        ProvidedColumns, CACHE_TEXT_LAYOUTS, RIGHT_TO_LEFT,
+    };
+    use crate::data::{RemapDetails, SortDirection};
+    use crate::selection::SingleCell;
+    use crate::{AxisMeasure, AxisMeasurementType, SeriesValue, SparklineCell};
+    use crate::{CellCtx, CellRender, CellRenderExt, CellsDelegate, DataCompare, NumberCell};
+    use crate::{FilterSpec, IndexedItems, Remap, RemapSpec, Remapper, TableAxis, TextCell};
+    use druid::im::Vector;
+    use druid::piet::{Device, FontFamily, RenderContext, TextAlignment, TextLayout};
+    use druid::{lens, ArcStr, Color, Env, Insets, Key, Point, Selector, Size};
+    use std::cmp::Ordering;
+
+    #[test]
+    fn missing_font_falls_back() {
+        let cell = TextCell::new().font_name(ArcStr::from("No Such Font Anywhere"));
+        let mut device = Device::new().expect("device");
+        let mut target = device.bitmap_target(10, 10, 1.0).expect("target");
+        let mut piet = target.render_context();
+
+        let font = cell.resolve_font(piet.text(), &Env::default());
+        assert!(font == FontFamily::SYSTEM_UI);
+        piet.finish().expect("finish");
+    }
+
+    #[test]
+    fn configured_fonts_reach_the_layout() {
+        let name = FontFamily::MONOSPACE.name();
+        let cell = TextCell::new().font_name(ArcStr::from(name));
+        let mut device = Device::new().expect("device");
+        let mut target = device.bitmap_target(10, 10, 1.0).expect("target");
+        let mut piet = target.render_context();
+        let env = Env::default();
+
+        let font = cell.resolve_font(piet.text(), &env);
+        assert_eq!(font.name(), name);
+
+        let single = SingleCell::new(
+            AxisPair::new(VisIdx(0), VisIdx(0)),
+            AxisPair::new(LogIdx(0), LogIdx(0)),
+        );
+        let ctx = CellCtx::Cell(&single, Default::default(), Default::default());
+        let data = "iiiiii".to_string();
+        let measured = cell.measure(piet.text(), TableAxis::Columns, &ctx, &data, &env);
+        let direct = cell
+            .build_layout(piet.text(), &ctx, &data, &env, &font, None)
+            .map(|layout| layout.size().width);
+        assert_eq!(measured, direct);
+        piet.finish().expect("finish");
+    }
+
+    #[test]
+    fn wrapped_text_colors_win_over_the_cells_own() {
+        let single = SingleCell::new(
+            AxisPair::new(VisIdx(0), VisIdx(0)),
+            AxisPair::new(LogIdx(0), LogIdx(0)),
+        );
+        let plain = CellCtx::Cell(&single, Size::ZERO, Default::default());
+        let cell = TextCell::new().text_color(Color::BLACK);
+        let env = Env::default();
+        let color = |ctx: &CellCtx| cell.color(ctx, &env).as_rgba_u32();
+        let black = Color::BLACK.as_rgba_u32();
+        assert_eq!(color(&plain), black);
+
+        let red = Color::rgb8(0xff, 0, 0);
+        let styled = plain.with_text_color(red.clone());
+        assert_eq!(color(&styled), red.as_rgba_u32());
+        // Only cells carry a style
+        assert_eq!(color(&CellCtx::Absent.with_text_color(red)), black);
+    }
+
+    #[test]
+    fn layouts_are_cached_only_when_asked() {
+        let cell = TextCell::new().font_size(12.);
+        let mut device = Device::new().expect("device");
+        let mut target = device.bitmap_target(10, 10, 1.0).expect("target");
+        let mut piet = target.render_context();
+        let font = FontFamily::SYSTEM_UI;
+
+        let env = Env::default();
+        cell.cached_layout(piet.text(), &CellCtx::Absent, "one", &env, &font, None);
+        assert!(cell.layouts.borrow().is_empty());
+
+        let env = env.adding(CACHE_TEXT_LAYOUTS, true);
+        cell.cached_layout(piet.text(), &CellCtx::Absent, "one", &env, &font, None);
+        cell.cached_layout(piet.text(), &CellCtx::Absent, "one", &env, &font, None);
+        cell.cached_layout(piet.text(), &CellCtx::Absent, "one", &env, &font, Some(30.));
+        cell.cached_layout(piet.text(), &CellCtx::Absent, "two", &env, &font, None);
+        assert_eq!(cell.layouts.borrow().len(), 3);
+        piet.finish().expect("finish");
+    }
+
+    #[test]
+    fn number_formatting() {
+        let cell = NumberCell::new().thousands_separator(',').prefix("$");
+        assert_eq!(cell.format_f64(1234567.891), "$1,234,567.89");
+        assert_eq!(cell.format_f64(-999.5), "-$999.50");
+        assert_eq!(cell.format_f64(-0.001), "$0.00");
+        assert_eq!(cell.format_f64(f64::NAN), "-");
+        assert_eq!(cell.format_i64(i64::MIN), "-$9,223,372,036,854,775,808");
+
+        let cell = NumberCell::new().decimals(0).suffix("%").placeholder("n/a");
+        assert_eq!(cell.format_f64(42.4), "42%");
+        assert_eq!(cell.format_f64(f64::INFINITY), "n/a");
+    }
+
+    #[test]
+    fn numbers_compare_numerically() {
+        let cell = NumberCell::new();
+        assert_eq!(
+            DataCompare::<f64>::compare(&cell, &9., &10.),
+            Ordering::Less
+        );
+        assert_eq!(
+            DataCompare::<f64>::compare(&cell, &f64::NAN, &1.),
+            Ordering::Greater
+        );
+        assert_eq!(
+            DataCompare::<i64>::compare(&cell, &10, &9),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn sparklines_fill_the_cell_and_sort_by_their_value() {
+        let cell = SparklineCell::new();
+        let series: Vector<f64> = vec![1., 3., f64::NAN, 2.].into();
+        let points = cell.points(&series, Size::new(31., 11.));
+        assert_eq!(points[0], Some(Point::new(0.5, 10.5)));
+        assert_eq!(points[1], Some(Point::new(10.5, 0.5)));
+        assert_eq!(points[2], None);
+        assert_eq!(points[3], Some(Point::new(30.5, 5.5)));
+
+        let empty = Vector::new();
+        assert_eq!(SeriesValue::Last.of(&series), Some(2.));
+        assert_eq!(SeriesValue::Sum.of(&series), Some(6.));
+        assert_eq!(SeriesValue::Sum.of(&empty), None);
+        assert_eq!(cell.compare(&empty, &series), Ordering::Less);
+        let by_sum = SparklineCell::new().value(SeriesValue::Sum);
+        let late_dip: Vector<f64> = vec![9., 1.].into();
+        assert_eq!(cell.compare(&late_dip, &series), Ordering::Less);
+        assert_eq!(by_sum.compare(&late_dip, &series), Ordering::Greater);
+    }
+
+    #[test]
+    fn blanks_sort_to_the_chosen_end() {
+        let first = TextCell::new().optional();
+        let last = TextCell::new().optional().none_order(NoneOrder::Last);
+        let (none, some) = (None, Some("a".to_string()));
+        assert_eq!(first.compare(&none, &some), Ordering::Less);
+        assert_eq!(last.compare(&none, &some), Ordering::Greater);
+        assert_eq!(last.compare(&none, &none), Ordering::Equal);
+
+        let env = Env::default();
+        assert_eq!(first.as_text(&CellCtx::Absent, &none, &env), None);
+        assert_eq!(first.as_text(&CellCtx::Absent, &some, &env), some);
+    }
+
+    #[test]
+    fn loading_cells_have_no_value_until_ready() {
+        let cell = NumberCell::new().loading(|num: &f64| !num.is_nan());
+        let env = Env::default();
+        assert!(cell.is_loading(&CellCtx::Absent, &f64::NAN, &env));
+        assert_eq!(cell.as_number(&CellCtx::Absent, &f64::NAN, &env), None);
+
+        assert!(!cell.is_loading(&CellCtx::Absent, &2., &env));
+        assert_eq!(cell.as_number(&CellCtx::Absent, &2., &env), Some(2.));
+    }
+
+    #[test]
+    fn choices_are_stepped_through_in_place() {
+        let choices = vec![("Low".to_string(), 1), ("High".to_string(), 2)];
+        let cycle = CycleChoices { choices };
+        let mut value = 1;
+        cycle.step(&mut value, false);
+        assert_eq!(value, 2);
+        cycle.step(&mut value, false);
+        assert_eq!(value, 1);
+        cycle.step(&mut value, true);
+        assert_eq!(value, 2);
+
+        let mut unknown = 7;
+        cycle.step(&mut unknown, true);
+        assert_eq!(unknown, 1);
+    }
+
+    #[test]
+    fn backgrounds_reach_through_wrappers() {
+        let red = Color::rgb8(0xff, 0, 0);
+        let positive = move |v: &f64, _: &Env| if *v > 0. { Some(red.clone()) } else { None };
+        let cell = NumberCell::new().with_bg(positive).optional();
+        let env = Env::default();
+        let background = |value| {
+            cell.background(&CellCtx::Absent, &value, &env)
+                .map(|color| color.as_rgba_u32())
+        };
+        let red = Color::rgb8(0xff, 0, 0).as_rgba_u32();
+        assert_eq!(background(Some(2.)), Some(red));
+        assert_eq!(background(Some(-2.)), None);
+        assert_eq!(background(None), None);
+    }
+
+    #[test]
+    fn copied_text_comes_from_the_cells_column() {
+        let cols = vec![NumberCell::new(), NumberCell::new().decimals(0)];
+        let cell = SingleCell::new(
+            AxisPair::new(VisIdx(0), VisIdx(0)),
+            AxisPair::new(LogIdx(3), LogIdx(1)),
+        );
+        let env = Env::default();
+        let ctx = CellCtx::Cell(&cell, Default::default(), Default::default());
+        let text = cols.as_text(&ctx, &2.7f64, &env);
+        assert_eq!(text.as_deref(), Some("3"));
+        assert_eq!(cols.as_text(&CellCtx::Absent, &2.7f64, &env), None);
+    }
+
+    #[test]
+    fn filters_hide_rows() {
+        let cols: ProvidedColumns<Vector<String>, _> =
+            ProvidedColumns::new(vec![column("Fruit", TextCell::new())]);
+        let data: Vector<String> = vec!["apple", "banana", "cherry"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let env = Env::default();
+
+        let mut spec = RemapSpec::default();
+        spec.set_filter(FilterSpec::new(0, |text| text.contains('a')));
+        let remap = cols.remap_items(&data, &spec, &Remap::Pristine, &env);
+        assert_eq!(remap.vis_len(data.idx_len()), 2);
+        assert_eq!(remap.get_log_idx(VisIdx(1)), Some(LogIdx(1)));
+
+        spec.clear_filters();
+        let remap = cols.remap_items(&data, &spec, &Remap::Pristine, &env);
+        assert_eq!(remap.vis_len(data.idx_len()), 3);
+    }
+
+    #[test]
+    fn grouped_rows_follow_their_header() {
+        let cols: ProvidedColumns<Vector<String>, _> =
+            ProvidedColumns::new(vec![column("Fruit", TextCell::new())]);
+        let data: Vector<String> = vec!["pear", "fig", "pear", "fig", "kiwi"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let env = Env::default();
+
+        let mut spec = RemapSpec::default();
+        spec.set_group_by(Some(0));
+        let remap = cols.remap_items(&data, &spec, &Remap::Pristine, &env);
+        assert_eq!(remap.vis_len(data.idx_len()), 8);
+        assert_eq!(shown(&remap, 8), vec![1, 3, 4, 0, 2]);
+        let header = |vis: usize| Some((vis, remap.group_header_at(VisIdx(vis))?.0));
+        let headers: Vec<_> = (0..8).filter_map(header).collect();
+        assert_eq!(headers, vec![(0, 1), (3, 4), (5, 0)]);
+    }
+
+    #[test]
+    fn footer_aggregates_the_shown_rows() {
+        let cols: ProvidedColumns<Vector<f64>, _> = ProvidedColumns::new(vec![
+            column("Sum", NumberCell::new()).footer(Aggregate::Sum),
+            column("Average", NumberCell::new()).footer(Aggregate::Average),
+            column("None", NumberCell::new()),
+        ]);
+        let data: Vector<f64> = vec![1.5, 2., 4.].into();
+        let env = Env::default();
+        let footer = |col, rows: &Remap| cols.footer_value(LogIdx(col), &data, rows, &env);
+
+        let filtered = Remap::selected(RemapDetails::Full(vec![LogIdx(0), LogIdx(2)].into()));
+        assert_eq!(footer(0, &filtered), Some("5.5".to_string()));
+        assert_eq!(footer(1, &Remap::Pristine), Some("2.5".to_string()));
+        assert_eq!(footer(2, &Remap::Pristine), None);
+    }
+
+    #[test]
+    fn spans_come_from_their_column() {
+        let cols: ProvidedColumns<Vector<f64>, _> = ProvidedColumns::new(vec![
+            column("Note", NumberCell::new()).col_span(|n: &f64| if *n < 0. { 2 } else { 1 }),
+            column("Plain", NumberCell::new()),
+        ]);
+        assert_eq!(cols.col_span(&-1., LogIdx(0)), 2);
+        assert_eq!(cols.col_span(&1., LogIdx(0)), 1);
+        assert_eq!(cols.col_span(&-1., LogIdx(1)), 1);
+    }
+
+    #[test]
+    fn merged_columns_compare_as_they_sort() {
+        let cols: ProvidedColumns<Vector<String>, _> = ProvidedColumns::new(vec![
+            column("Merged", TextCell::new()).merge_equal(),
+            column("Plain", TextCell::new()),
+        ]);
+        assert!(cols.merges_equal(LogIdx(0)));
+        assert!(!cols.merges_equal(LogIdx(1)));
+        let (a, b) = ("Apple".to_string(), "Apple".to_string());
+        assert!(cols.equal_in_column(LogIdx(0), &a, &b));
+        assert!(!cols.equal_in_column(LogIdx(0), &a, &"Pear".to_string()));
+    }
+
+    #[test]
+    fn clicks_go_to_their_columns_command() {
+        const DELETE: Selector<LogIdx> = Selector::new("test.delete-row");
+        let cols: ProvidedColumns<Vector<String>, _> = ProvidedColumns::new(vec![
+            column("Name", TextCell::new()),
+            column("Delete", TextCell::new()).on_click(DELETE),
+        ]);
+        assert!(cols.on_click(LogIdx(0)).is_none());
+        assert_eq!(cols.on_click(LogIdx(1)), Some(DELETE));
+    }
+
+    #[test]
+    fn renaming_changes_the_columns_header() {
+        let mut cols: ProvidedColumns<Vector<String>, _> =
+            ProvidedColumns::new(vec![column("Name", TextCell::new())]);
+        cols.rename_column(LogIdx(0), "Full name".to_string());
+        cols.rename_column(LogIdx(1), "Missing".to_string());
+        assert_eq!(cols.cols[0].header, "Full name");
+    }
+
+    #[test]
+    fn widths_follow_the_env() {
+        const DENSE_WIDTH: Key<f64> = Key::new("test.dense-width");
+        let cols: ProvidedColumns<Vector<String>, _> = ProvidedColumns::new(vec![
+            column("Name", TextCell::new()).width((DENSE_WIDTH, 20., f64::INFINITY)),
+            column("Notes", TextCell::new()).width((50., DENSE_WIDTH, f64::INFINITY)),
+            column("Tags", TextCell::new()).width((DENSE_WIDTH, 20., f64::INFINITY)),
+        ]);
+        let mut measure = AxisMeasure::new(AxisMeasurementType::Individual, 50.);
+        measure.set_axis_properties(0., 3, &Remap::Pristine);
+        let apply = |measure: &mut AxisMeasure, env: &Env| {
+            measure.set_log_limits(cols.column_width_limits(env));
+            measure.set_log_initial(cols.column_initial_widths(env));
+            (0..3)
+                .map(|idx| measure.pixels_length_for_log(LogIdx(idx)).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let comfortable = Env::default().adding(DENSE_WIDTH, 120.);
+        assert_eq!(apply(&mut measure, &comfortable), vec![120., 120., 120.]);
+
+        // A compact theme narrows them again, unless resized
+        measure.set_pixel_length_for_log(LogIdx(2), 150.);
+        let compact = comfortable.adding(DENSE_WIDTH, 80.);
+        assert_eq!(apply(&mut measure, &compact), vec![80., 80., 150.]);
+        assert_eq!(apply(&mut measure, &comfortable), vec![120., 120., 150.]);
+    }
+
+    #[test]
+    fn only_fillable_columns_fill() {
+        let cols: ProvidedColumns<Vector<f64>, _> = ProvidedColumns::new(vec![
+            column("Filled", NumberCell::new()).fillable(|from: &f64, to: &mut f64| *to = *from),
+            column("Plain", NumberCell::new()),
+        ]);
+        assert!(cols.can_fill(LogIdx(0)));
+        assert!(!cols.can_fill(LogIdx(1)));
+
+        let mut row = 1.;
+        cols.fill(LogIdx(1), &5., &mut row);
+        assert_eq!(row, 1.);
+        cols.fill(LogIdx(0), &5., &mut row);
+        assert_eq!(row, 5.);
+    }
+
+    #[test]
+    fn padding_is_kept_to_its_column() {
+        let cols: ProvidedColumns<Vector<f64>, _> = ProvidedColumns::new(vec![
+            column("Padded", NumberCell::new()).cell_padding((8., 2.)),
+            column("Plain", NumberCell::new()),
+        ]);
+        let padded = Some(Insets::uniform_xy(8., 2.));
+        assert_eq!(cols.column_padding(LogIdx(0)), padded);
+        assert_eq!(cols.column_padding(LogIdx(1)), None); // The table's padding is used
+        assert_eq!(alignment_key(TextAlignment::Center), Some(2));
+    }
+
+    #[test]
+    fn column_alignment_reaches_the_text_cell() {
+        let single = SingleCell::new(
+            AxisPair::new(VisIdx(0), VisIdx(0)),
+            AxisPair::new(LogIdx(0), LogIdx(0)),
+        );
+        let plain = CellCtx::Cell(&single, Size::ZERO, Default::default());
+        let aligned = plain.with_alignment(TextAlignment::Center);
+        let cell = TextCell::new().text_alignment(TextAlignment::End);
+        let env = Env::default();
+        assert_eq!(cell.alignment(&plain, &env), TextAlignment::End);
+        assert_eq!(cell.alignment(&aligned, &env), TextAlignment::Center);
+
+        // Right to left still flips the column's alignment
+        let env = env.adding(RIGHT_TO_LEFT, true);
+        let start = plain.with_alignment(TextAlignment::Start);
+        assert_eq!(cell.alignment(&start, &env), TextAlignment::End);
+    }
+
+    #[test]
+    fn aggregates_leave_out_filtered_rows() {
+        let data: Vector<f64> = Vector::from(vec![4., 1., 7.]);
+        let agg = |rows: Option<&Remap>, aggregate| {
+            aggregate_column(&data, rows, |n: &f64| Some(*n), aggregate)
+        };
+        assert_eq!(agg(None, Aggregate::Sum), Some(12.));
+        let shown = Remap::selected(RemapDetails::Full(Vector::from(vec![LogIdx(2), LogIdx(1)])));
+        assert_eq!(agg(Some(&shown), Aggregate::Min), Some(1.));
+        assert_eq!(agg(Some(&shown), Aggregate::Average), Some(4.));
+        let empty = Remap::selected(RemapDetails::Full(Vector::new()));
+        assert_eq!(agg(Some(&empty), Aggregate::Count), None);
+    }
+
+    #[test]
+    fn text_columns_only_count() {
+        let words = || vec![None, None].into_iter();
+        assert_eq!(Aggregate::Count.apply(words()), Some("2".to_string()));
+        assert_eq!(Aggregate::Sum.apply(words()), None);
+        assert_eq!(Aggregate::Max.apply(std::iter::empty()), None);
+    }
+
+    fn shown(remap: &Remap, len: usize) -> Vec<usize> {
+        (0..len)
+            .filter_map(|vis| remap.get_log_idx(VisIdx(vis)))
+            .map(|log_idx| log_idx.0)
+            .collect()
+    }
+
+    #[test]
+    fn moved_rows_outlive_the_sort_until_a_new_one() {
+        let cols: ProvidedColumns<Vector<String>, _> =
+            ProvidedColumns::new(vec![column("Fruit", TextCell::new())]);
+        let data: Vector<String> = vec!["apple", "banana", "cherry"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let env = Env::default();
+
+        let mut spec = RemapSpec::default();
+        spec.toggle_sort(LogIdx(0), false, &[]);
+        spec.toggle_sort(LogIdx(0), false, &[]);
+        let remap = cols.remap_items(&data, &spec, &Remap::Pristine, &env);
+        assert_eq!(shown(&remap, 3), vec![2, 1, 0]);
+
+        // Moving apple to the top keeps the rest in their sorted places
+        spec.move_rows(remap.explicit_order(3), &[LogIdx(0)], VisIdx(0));
+        let remap = cols.remap_items(&data, &spec, &Remap::Pristine, &env);
+        assert_eq!(shown(&remap, 3), vec![0, 2, 1]);
+
+        spec.toggle_sort(LogIdx(0), false, &[]);
+        let remap = cols.remap_items(&data, &spec, &Remap::Pristine, &env);
+        assert_eq!(shown(&remap, 3), vec![0, 1, 2]);
+        assert!(spec.moved_order.is_none());
+    }
+
+    #[test]
+    fn sorts_cycle_through_unsorted() {
+        let cols: ProvidedColumns<Vector<String>, _> =
+            ProvidedColumns::new(vec![column("Fruit", TextCell::new())]);
+        let data: Vector<String> = vec!["banana", "cherry", "apple"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let env = Env::default();
+        let mut spec = RemapSpec::default();
+        let mut click = |fixed: &[bool]| {
+            spec.toggle_sort(LogIdx(0), false, fixed);
+            let remap = cols.remap_items(&data, &spec, &Remap::Pristine, &env);
+            shown(&remap, 3)
+        };
+
+        assert_eq!(click(&[]), vec![2, 0, 1]);
+        assert_eq!(click(&[]), vec![1, 0, 2]);
+        assert_eq!(click(&[]), vec![0, 1, 2]);
+        // A fixed sort turns back around instead
+        assert_eq!(click(&[true]), vec![2, 0, 1]);
+        assert_eq!(click(&[true]), vec![1, 0, 2]);
+        assert_eq!(click(&[true]), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn extending_keeps_the_other_sorts() {
+        let mut spec = RemapSpec::default();
+        spec.toggle_sort(LogIdx(0), false, &[]);
+        spec.toggle_sort(LogIdx(1), true, &[]);
+        spec.toggle_sort(LogIdx(0), true, &[]);
+        let sorts = |spec: &RemapSpec| {
+            let sorts = spec.sort_by.iter().map(|s| (s.idx, s.direction));
+            sorts.collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sorts(&spec),
+            vec![(0, SortDirection::Descending), (1, SortDirection::Ascending)]
+        );
+
+        spec.toggle_sort(LogIdx(1), false, &[]);
+        assert_eq!(sorts(&spec), vec![(1, SortDirection::Descending)]);
+    }
+
+    #[test]
+    fn fixed_sorts_stay_under_clicked_ones() {
+        let cols: ProvidedColumns<Vector<(String, String)>, _> = ProvidedColumns::new(vec![
+            column("Name", TextCell::new().lens(lens!((String, String), 1))),
+            column("Kind", TextCell::new().lens(lens!((String, String), 0)))
+                .sort(SortDirection::Ascending)
+                .fix_sort(),
+        ]);
+        let data: Vector<(String, String)> = vec![
+            ("veg", "carrot"),
+            ("fruit", "cherry"),
+            ("veg", "apple"),
+            ("fruit", "banana"),
+        ]
+        .into_iter()
+        .map(|(kind, name)| (kind.to_string(), name.to_string()))
+        .collect();
+        let env = Env::default();
+        let fixed = [false, true];
+        let sorts = |spec: &RemapSpec| {
+            let sorts = spec.sort_by.iter().map(|s| (s.idx, s.direction));
+            sorts.collect::<Vec<_>>()
+        };
+
+        // Sorting by name alone still keeps the kinds apart
+        let mut spec = cols.initial_spec();
+        spec.toggle_sort(LogIdx(0), false, &fixed);
+        assert_eq!(
+            sorts(&spec),
+            vec![(1, SortDirection::Ascending), (0, SortDirection::Ascending)]
+        );
+        let remap = cols.remap_items(&data, &spec, &Remap::Pristine, &env);
+        assert_eq!(shown(&remap, 4), vec![3, 1, 2, 0]);
+
+        // Turning the kind around keeps it first, and the name sort comes off on its own
+        spec.toggle_sort(LogIdx(1), true, &fixed);
+        spec.toggle_sort(LogIdx(0), false, &fixed);
+        spec.toggle_sort(LogIdx(0), false, &fixed);
+        assert_eq!(sorts(&spec), vec![(1, SortDirection::Descending)]);
+        spec.toggle_sort(LogIdx(1), false, &fixed);
+        spec.toggle_sort(LogIdx(1), false, &fixed);
+        assert_eq!(sorts(&spec), vec![(1, SortDirection::Descending)]);
+    }
+
+    #[test]
+    fn sort_ties_keep_the_shown_order() {
+        let cols: ProvidedColumns<Vector<String>, _> =
+            ProvidedColumns::new(vec![column("Letter", TextCell::new())]);
+        let data: Vector<String> = vec!["b", "a", "b", "a"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let env = Env::default();
+        let mut spec = RemapSpec::default();
+        spec.toggle_sort(LogIdx(0), false, &[]);
+
+        let remap = cols.remap_items(&data, &spec, &Remap::Pristine, &env);
+        assert_eq!(shown(&remap, 4), vec![1, 3, 0, 2]);
+
+        let order = vec![2, 0, 3, 1].into_iter().map(LogIdx).collect();
+        let prior = Remap::selected(RemapDetails::Full(order));
+        let remap = cols.remap_items(&data, &spec, &prior, &env);
+        assert_eq!(shown(&remap, 4), vec![3, 1, 2, 0]);
+    }
+
+    #[test]
+    fn one_changed_row_is_moved_into_place() {
+        let cols: ProvidedColumns<Vector<String>, _> =
+            ProvidedColumns::new(vec![column("Letter", TextCell::new())]);
+        let mut data: Vector<String> = vec!["b", "a", "c", "a"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let env = Env::default();
+        let mut spec = RemapSpec::default();
+        spec.toggle_sort(LogIdx(0), false, &[]);
+        let prior = cols.remap_items(&data, &spec, &Remap::Pristine, &env);
+        assert_eq!(shown(&prior, 4), vec![1, 3, 0, 2]);
+
+        data[1] = "d".to_string();
+        let remap = cols
+            .remap_item_changed(&data, &spec, &prior, LogIdx(1), &env)
+            .expect("incremental");
+        assert_eq!(shown(&remap, 4), vec![3, 0, 2, 1]);
+        let full = cols.remap_items(&data, &spec, &prior, &env);
+        assert_eq!(shown(&full, 4), shown(&remap, 4));
+
+        // Still in order where it is, so it stays ahead of the row it now ties with
+        data[2] = "d".to_string();
+        let remap = cols
+            .remap_item_changed(&data, &spec, &remap, LogIdx(2), &env)
+            .expect("incremental");
+        assert_eq!(shown(&remap, 4), vec![3, 0, 2, 1]);
+    }
+
+    #[test]
+    fn natural_sort_orders_numbers_by_value() {
+        let sorted = |cell: TextCell, items: &[&str]| {
+            let mut items: Vec<String> = items.iter().map(|s| s.to_string()).collect();
+            items.sort_by(|a, b| cell.compare(a, b));
+            items
+        };
+        let files = ["file10", "file9", "file1", "file010", "File2"];
+        assert_eq!(
+            sorted(TextCell::new(), &files),
+            vec!["File2", "file010", "file1", "file10", "file9"]
+        );
+        assert_eq!(
+            sorted(TextCell::new().natural_sort(true), &files),
+            vec!["File2", "file1", "file9", "file10", "file010"]
+        );
+        assert_eq!(
+            sorted(TextCell::new().natural_sort(true).ignore_case(true), &files),
+            vec!["file1", "File2", "file9", "file10", "file010"]
+        );
+        // Only ASCII digits are read as numbers
+        let items = ["Item 11", "Item 2", "Item ٣"];
+        assert_eq!(
+            sorted(TextCell::new().natural_sort(true), &items),
+            vec!["Item 2", "Item 11", "Item ٣"]
+        );
+    }
 }
@@ -1,21 +1,125 @@
-use crate::axis_measure::TableAxis;
-use druid::{theme, Color, Env, KeyOrValue};
+use crate::axis_measure::{AxisPair, LogIdx, TableAxis, VisIdx};
+use crate::selection::SelectionMode;
+use crate::TableSelection;
+use druid::{theme, Color, Data, Env, EventCtx, KeyOrValue, Point, Selector};
+use std::rc::Rc;
+use std::sync::Once;
+use std::time::Duration;
 
 pub(crate) const DEFAULT_COL_HEADER_HEIGHT: f64 = 25.0;
 pub(crate) const DEFAULT_ROW_HEADER_WIDTH: f64 = 100.0;
 
+// Shows the menu for a right clicked cell at a point in the window, see TableBuilder::context_menu
+pub type ContextMenuFn = Rc<dyn Fn(&mut EventCtx, &AxisPair<LogIdx>, Point)>;
+
+// Which of the lines between cells get drawn
+#[derive(Debug, Data, Clone, Copy, PartialEq, Eq)]
+pub enum Gridlines {
+    Both,
+    Horizontal,
+    Vertical,
+    None,
+}
+
+impl Gridlines {
+    pub(crate) fn horizontal(&self) -> bool {
+        matches!(self, Gridlines::Both | Gridlines::Horizontal)
+    }
+
+    pub(crate) fn vertical(&self) -> bool {
+        matches!(self, Gridlines::Both | Gridlines::Vertical)
+    }
+}
+
 #[derive(Clone)]
 pub struct TableConfig {
     pub col_header_height: KeyOrValue<f64>,
+    // Let the column headings grow taller than col_header_height to fit headers wrapped to
+    // their column's width. The header renderer has to wrap them, see TableBuilder::wrap_headers
+    pub wrap_headers: bool,
+    // Double clicking a column heading renames it, instead of sorting by it. See RENAME_COLUMN.
+    pub editable_headers: bool,
     pub row_header_width: KeyOrValue<f64>,
     pub header_background: KeyOrValue<Color>,
     pub header_selected_background: KeyOrValue<Color>,
     pub cells_background: KeyOrValue<Color>,
+    // Alternate rows in the order shown, over the cells background
+    pub even_row_background: KeyOrValue<Color>,
+    pub odd_row_background: KeyOrValue<Color>,
     pub cells_border: KeyOrValue<Color>,
     pub cell_border_thickness: KeyOrValue<f64>,
+    // The frozen divider is drawn regardless
+    pub gridlines: Gridlines,
     pub cell_padding: KeyOrValue<f64>,
     pub selection_color: KeyOrValue<Color>,
+    // How strongly selected cells are tinted with the selection colour
+    pub selection_opacity: KeyOrValue<f64>,
+    // Outlines and tints whole selected rows and columns
+    pub slice_selection_color: KeyOrValue<Color>,
     pub focus_color: KeyOrValue<Color>,
+    // Defaults to a little over the cell border thickness
+    pub focus_border_width: Option<KeyOrValue<f64>>,
+    // The sort arrows and priorities drawn in the headings
+    pub sort_indicator_color: KeyOrValue<Color>,
+    // Fills the cells matching the search, under the selection
+    pub search_highlight: KeyOrValue<Color>,
+    pub selection_mode: SelectionMode,
+    // Ease the focus between cells and rows to their fitted height, rather than jumping
+    pub animate: bool,
+    pub animation_duration: Duration,
+    // How long the mouse rests on a heading or clipped cell before its tooltip shows
+    pub tooltip_delay: Duration,
+    // The least a row can be dragged to by its border in the row headings
+    pub min_row_height: KeyOrValue<f64>,
+    // Rows examined when auto sizing a column, None for all of them
+    pub auto_size_row_limit: Option<usize>,
+    // Lay the columns out from the right, with text aligned to the right and sort arrows on
+    // the left. Frozen columns aren't supported this way yet, so frozen_columns is ignored.
+    pub right_to_left: bool,
+    // Text cells keep the layouts they paint, so scrolling back over them doesn't lay them out
+    // again. Costs memory for each text renderer.
+    pub cache_text_layouts: bool,
+    // Columns are scaled, within their limits, to fill the table's width in the proportions
+    // they have, over any flex weights. When their minimums don't fit, the table scrolls.
+    pub fit_width: bool,
+    // Leading columns that stay in view when scrolling horizontally. Ignored right to left,
+    // with a warning, until they can be kept at the right edge.
+    pub frozen_columns: usize,
+    // Leading rows that stay in view when scrolling vertically
+    pub frozen_rows: usize,
+    pub frozen_divider: KeyOrValue<Color>,
+    // A box in the corner between the headings that selects every row, or none
+    pub select_all_box: bool,
+    // Number the row headings from 1 in the order shown, instead of their own headers. They
+    // widen to fit the largest number.
+    pub row_numbers: bool,
+    // Typing jumps the focus to the next row whose sort column starts with what was typed,
+    // instead of editing the cell. F2 still edits.
+    pub type_ahead: bool,
+    // Appended rows keep a view that was at the bottom scrolled to the last row. Scrolling up
+    // pauses this until the view is back at the bottom. See SCROLL_TO_BOTTOM.
+    pub stick_to_bottom: bool,
+    // Copying every row of some columns, or the whole table, puts the column headings first.
+    // Ctrl+Shift+C copies them with any selection.
+    pub copy_headers: bool,
+    // Submitted with the new selection whenever it changes
+    pub on_selection: Option<Selector<TableSelection>>,
+    // Submitted with the first and last rows on screen when scrolling or resizing changes
+    // them, eg to fetch more rows as they come into view
+    pub on_visible_rows: Option<Selector<(VisIdx, VisIdx)>>,
+    // Submitted with the logical rows on screen that the data has yet to load, see
+    // IndexedItems::with. They are asked for again each time the rows are checked until
+    // they arrive.
+    pub on_missing_rows: Option<Selector<Vec<LogIdx>>>,
+    // The group label spanning each logical column, see TableColumn::group
+    pub column_groups: Vec<Option<String>>,
+    // Shown on hovering over each logical column's heading, see TableColumn::header_tooltip
+    pub header_tooltips: Vec<Option<String>>,
+    // By logical column, sorts that clicking a heading turns around but never removes
+    pub fixed_sorts: Vec<bool>,
+    // Shown in place of the rows while there are none, eg when filtered out
+    pub empty_message: Option<String>,
+    pub context_menu: Option<ContextMenuFn>,
 }
 
 pub struct ResolvedTableConfig {
@@ -24,11 +128,28 @@ pub struct ResolvedTableConfig {
     pub(crate) header_background: Color,
     pub(crate) header_selected_background: Color,
     pub(crate) cells_background: Color,
+    pub(crate) even_row_background: Color,
+    pub(crate) odd_row_background: Color,
     pub(crate) cells_border: Color,
     pub(crate) cell_border_thickness: f64,
+    pub(crate) gridlines: Gridlines,
     pub(crate) cell_padding: f64,
     pub(crate) selection_color: Color,
+    pub(crate) selection_opacity: f64,
+    pub(crate) slice_selection_color: Color,
+    pub(crate) sort_indicator_color: Color,
     pub(crate) focus_color: Color,
+    pub(crate) focus_border_width: f64,
+    pub(crate) search_highlight: Color,
+    pub(crate) selection_mode: SelectionMode,
+    pub(crate) animate: bool,
+    pub(crate) animation_duration: Duration,
+    pub(crate) min_row_height: f64,
+    pub(crate) auto_size_row_limit: Option<usize>,
+    pub(crate) right_to_left: bool,
+    pub(crate) frozen_columns: usize,
+    pub(crate) frozen_rows: usize,
+    pub(crate) frozen_divider: Color,
 }
 
 impl ResolvedTableConfig {
@@ -50,30 +171,165 @@ impl TableConfig {
     pub fn new() -> TableConfig {
         TableConfig {
             col_header_height: DEFAULT_COL_HEADER_HEIGHT.into(),
+            wrap_headers: false,
+            editable_headers: false,
             row_header_width: DEFAULT_ROW_HEADER_WIDTH.into(),
             header_background: theme::BACKGROUND_DARK.into(),
             header_selected_background: theme::PLACEHOLDER_COLOR.into(),
-            cells_background: theme::TEXT_COLOR.into(),
+            cells_background: theme::BACKGROUND_LIGHT.into(),
+            even_row_background: Color::TRANSPARENT.into(),
+            odd_row_background: Color::TRANSPARENT.into(),
             cells_border: theme::BORDER_LIGHT.into(),
             cell_border_thickness: 0.5.into(),
+            gridlines: Gridlines::Both,
             cell_padding: 2.0.into(),
             selection_color: Color::rgb8(0xB0, 0xEE, 0xFF).into(),
+            selection_opacity: 0.2.into(),
+            slice_selection_color: theme::PRIMARY_LIGHT.into(),
             focus_color: Color::rgb8(0x4D, 0x58, 0xD8).into(),
+            focus_border_width: None,
+            sort_indicator_color: theme::TEXT_COLOR.into(),
+            search_highlight: Color::rgba8(0xFF, 0xD5, 0x4F, 0x90).into(),
+            selection_mode: SelectionMode::Cell,
+            animate: true,
+            animation_duration: Duration::from_millis(120),
+            tooltip_delay: Duration::from_millis(600),
+            min_row_height: 10.0.into(),
+            auto_size_row_limit: Some(1000),
+            right_to_left: false,
+            cache_text_layouts: true,
+            fit_width: false,
+            frozen_columns: 0,
+            frozen_rows: 0,
+            frozen_divider: theme::BORDER_DARK.into(),
+            select_all_box: false,
+            row_numbers: false,
+            type_ahead: false,
+            stick_to_bottom: false,
+            copy_headers: false,
+            on_selection: None,
+            on_visible_rows: None,
+            on_missing_rows: None,
+            column_groups: Vec::new(),
+            header_tooltips: Vec::new(),
+            fixed_sorts: Vec::new(),
+            empty_message: None,
+            context_menu: None,
         }
     }
 
+    // Column groups get a band of their own above the column headings
+    pub(crate) fn has_column_groups(&self) -> bool {
+        self.column_groups.iter().any(Option::is_some)
+    }
+
     pub(crate) fn resolve(&self, env: &Env) -> ResolvedTableConfig {
+        let cell_border_thickness = self.cell_border_thickness.resolve(env);
         ResolvedTableConfig {
             row_header_width: self.row_header_width.resolve(env),
             col_header_height: self.col_header_height.resolve(env),
             header_background: self.header_background.resolve(env),
             header_selected_background: self.header_selected_background.resolve(env),
             cells_background: self.cells_background.resolve(env),
+            even_row_background: self.even_row_background.resolve(env),
+            odd_row_background: self.odd_row_background.resolve(env),
             cells_border: self.cells_border.resolve(env),
-            cell_border_thickness: self.cell_border_thickness.resolve(env),
+            cell_border_thickness,
+            gridlines: self.gridlines,
             cell_padding: self.cell_padding.resolve(env),
             selection_color: self.selection_color.resolve(env),
+            selection_opacity: self.selection_opacity.resolve(env),
+            slice_selection_color: self.slice_selection_color.resolve(env),
+            sort_indicator_color: self.sort_indicator_color.resolve(env),
             focus_color: self.focus_color.resolve(env),
+            focus_border_width: self.focus_border_width.as_ref().map_or_else(
+                || (cell_border_thickness * 1.5).min(2.),
+                |width| width.resolve(env),
+            ),
+            search_highlight: self.search_highlight.resolve(env),
+            selection_mode: self.selection_mode,
+            animate: self.animate,
+            animation_duration: self.animation_duration,
+            min_row_height: self.min_row_height.resolve(env),
+            auto_size_row_limit: self.auto_size_row_limit,
+            right_to_left: self.right_to_left,
+            frozen_columns: self.resolve_frozen_columns(),
+            frozen_rows: self.frozen_rows,
+            frozen_divider: self.frozen_divider.resolve(env),
+        }
+    }
+
+    // Right to left, frozen columns would be frozen at the wrong edge
+    fn resolve_frozen_columns(&self) -> usize {
+        if self.right_to_left && self.frozen_columns > 0 {
+            FROZEN_RIGHT_TO_LEFT_WARNING.call_once(|| {
+                log::warn!(
+                    "Frozen columns aren't supported right to left, so {} won't be frozen",
+                    self.frozen_columns
+                )
+            });
+            0
+        } else {
+            self.frozen_columns
         }
     }
 }
+
+static FROZEN_RIGHT_TO_LEFT_WARNING: Once = Once::new();
+
+#[cfg(test)]
+mod test {
+    use crate::TableConfig;
+    use druid::{theme, Color, Env};
+
+    fn themed(background: Color, text: Color) -> Env {
+        Env::default()
+            .adding(theme::BACKGROUND_DARK, background.clone())
+            .adding(theme::BACKGROUND_LIGHT, background.clone())
+            .adding(theme::PLACEHOLDER_COLOR, background.clone())
+            .adding(theme::BORDER_LIGHT, background.clone())
+            .adding(theme::BORDER_DARK, background)
+            .adding(theme::TEXT_COLOR, text)
+            .adding(theme::PRIMARY_LIGHT, Color::rgb8(0x5C, 0xC4, 0xFF))
+    }
+
+    #[test]
+    fn default_colors_follow_the_theme() {
+        let dark = TableConfig::new().resolve(&themed(Color::BLACK, Color::WHITE));
+        let light = TableConfig::new().resolve(&themed(Color::WHITE, Color::BLACK));
+        assert_eq!(dark.cells_background, Color::BLACK);
+        assert_eq!(light.cells_background, Color::WHITE);
+        assert_eq!(dark.sort_indicator_color, Color::WHITE);
+        assert_eq!(light.sort_indicator_color, Color::BLACK);
+    }
+
+    #[test]
+    fn selection_colors_can_be_overridden() {
+        let env = themed(Color::WHITE, Color::BLACK);
+        let default = TableConfig::new().resolve(&env);
+        assert_eq!(default.focus_border_width, 0.75); // Half again the border thickness
+
+        let mut config = TableConfig::new();
+        config.selection_color = Color::rgb8(0x10, 0x20, 0x30).into();
+        config.selection_opacity = 0.5.into();
+        config.slice_selection_color = theme::TEXT_COLOR.into();
+        config.focus_color = Color::rgb8(0xFF, 0, 0).into();
+        config.focus_border_width = Some(3.0.into());
+        let resolved = config.resolve(&env);
+        assert_eq!(resolved.selection_color, Color::rgb8(0x10, 0x20, 0x30));
+        assert_eq!(resolved.selection_opacity, 0.5);
+        assert_eq!(resolved.slice_selection_color, Color::BLACK);
+        assert_eq!(resolved.focus_color, Color::rgb8(0xFF, 0, 0));
+        assert_eq!(resolved.focus_border_width, 3.0);
+    }
+
+    #[test]
+    fn frozen_columns_are_ignored_right_to_left() {
+        let env = themed(Color::WHITE, Color::BLACK);
+        let mut config = TableConfig::new();
+        config.frozen_columns = 2;
+        assert_eq!(config.resolve(&env).frozen_columns, 2);
+        config.right_to_left = true;
+        assert_eq!(config.resolve(&env).frozen_columns, 0);
+    }
+}
@@ -0,0 +1,226 @@
+use druid::kurbo::{Line, PathEl, RoundedRect};
+use druid::widget::prelude::*;
+use druid::{
+    theme, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, Rect, Size, UpdateCtx, Widget,
+};
+
+use crate::axis_measure::{TableAxis, VisIdx};
+use crate::config::{ResolvedTableConfig, TableConfig};
+use crate::data::IndexedItems;
+use crate::headings::row_headings_width;
+use crate::selection::TableSelection;
+use crate::table::TableState;
+
+const BOX_SIZE: f64 = 14.;
+
+// How much of the rows the selection covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowsSelected {
+    None,
+    Some,
+    All,
+}
+
+impl RowsSelected {
+    fn of(selection: &TableSelection, row_count: usize) -> Self {
+        match selection.fully_selected_on_axis(TableAxis::Rows) {
+            Some(rows) if row_count > 0 && rows.len() == row_count => RowsSelected::All,
+            _ if !selection.has_focus() => RowsSelected::None,
+            _ => RowsSelected::Some,
+        }
+    }
+}
+
+// The corner between the headings, as wide as the row headings and as tall as the column ones.
+// With TableConfig::select_all_box it has a box that selects every row, or clears the
+// selection if they all are. It shows a tick when all rows are selected and a dash when
+// only some cells are.
+pub(crate) struct Corner {
+    config: TableConfig,
+    resolved_config: Option<ResolvedTableConfig>,
+}
+
+impl Corner {
+    pub(crate) fn new(config: TableConfig) -> Self {
+        Corner {
+            config,
+            resolved_config: None,
+        }
+    }
+
+    // Beside the band that column groups are labelled in
+    fn band(&self, rtc: &ResolvedTableConfig) -> f64 {
+        if self.config.has_column_groups() {
+            rtc.col_header_height
+        } else {
+            0.
+        }
+    }
+
+    fn select_all_rows<TableData: IndexedItems + Data>(
+        data: &TableState<TableData>,
+    ) -> TableSelection {
+        let rows = &data.remaps[TableAxis::Rows];
+        let count = rows.vis_len(data.data.idx_len());
+        // A group header has no row of its own to anchor the slice on
+        let first = (0..count)
+            .map(VisIdx)
+            .find(|vis| rows.get_log_idx(*vis).is_some());
+        let mut selection = TableSelection::NoSelection;
+        if let Some(first) = first {
+            selection.select_in_axis(TableAxis::Rows, first, &data.remaps);
+            selection.extend_in_axis(TableAxis::Rows, VisIdx(count - 1), &data.remaps);
+        }
+        selection
+    }
+}
+
+impl<TableData: IndexedItems + Data> Widget<TableState<TableData>> for Corner {
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut TableState<TableData>,
+        _env: &Env,
+    ) {
+        if !self.config.select_all_box {
+            return;
+        }
+        match event {
+            Event::MouseDown(me) if me.button.is_left() => {
+                ctx.set_active(true);
+                ctx.set_handled();
+            }
+            Event::MouseUp(me) if ctx.is_active() => {
+                ctx.set_active(false);
+                if ctx.is_hot() && me.button.is_left() {
+                    let count = data.remaps[TableAxis::Rows].vis_len(data.data.idx_len());
+                    data.selection = match RowsSelected::of(&data.selection, count) {
+                        RowsSelected::All => TableSelection::NoSelection,
+                        _ => Self::select_all_rows(data),
+                    };
+                }
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        _data: &TableState<TableData>,
+        env: &Env,
+    ) {
+        match event {
+            LifeCycle::WidgetAdded => self.resolved_config = Some(self.config.resolve(env)),
+            LifeCycle::HotChanged(_) => ctx.request_paint(),
+            _ => (),
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: &TableState<TableData>,
+        data: &TableState<TableData>,
+        _env: &Env,
+    ) {
+        if !old_data.selection.same(&data.selection) || !old_data.remaps.same(&data.remaps) {
+            ctx.request_paint();
+        }
+        // Stays as wide as the row numbers below
+        if self.config.row_numbers && old_data.data.idx_len() != data.data.idx_len() {
+            ctx.request_layout();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &TableState<TableData>,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Corner");
+        let size = match &self.resolved_config {
+            Some(rtc) => {
+                let rows = data.data.idx_len();
+                let width = row_headings_width(ctx.text(), &self.config, rows, env);
+                Size::new(width, rtc.col_header_height + self.band(rtc))
+            }
+            None => Size::new(0., TableAxis::Columns.default_header_cross()),
+        };
+        bc.constrain(size)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &TableState<TableData>, env: &Env) {
+        let rtc = match &self.resolved_config {
+            Some(rtc) if self.config.select_all_box => rtc,
+            _ => return,
+        };
+        // The box goes below the column groups
+        let size = ctx.size();
+        let area = Rect::new(0., self.band(rtc).min(size.height), size.width, size.height);
+        ctx.fill(area, &rtc.header_background);
+
+        let side = BOX_SIZE.min(area.width()).min(area.height()) - 1.;
+        let rect = Rect::from_center_size(area.center(), (side, side));
+        let border = if ctx.is_hot() {
+            &rtc.focus_color
+        } else {
+            &rtc.cells_border
+        };
+        ctx.stroke(RoundedRect::from_rect(rect, 2.), border, 1.);
+
+        let text_color = env.get(theme::TEXT_COLOR);
+        let count = data.remaps[TableAxis::Rows].vis_len(data.data.idx_len());
+        match RowsSelected::of(&data.selection, count) {
+            RowsSelected::All => {
+                let check = vec![
+                    PathEl::MoveTo((rect.x0 + side * 0.2, rect.y0 + side * 0.5).into()),
+                    PathEl::LineTo((rect.x0 + side * 0.4, rect.y0 + side * 0.75).into()),
+                    PathEl::LineTo((rect.x0 + side * 0.8, rect.y0 + side * 0.25).into()),
+                ];
+                ctx.stroke(&check[..], &text_color, 2.);
+            }
+            RowsSelected::Some => {
+                let y = rect.center().y;
+                let dash = Line::new((rect.x0 + side * 0.25, y), (rect.x1 - side * 0.25, y));
+                ctx.stroke(dash, &text_color, 2.);
+            }
+            RowsSelected::None => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::axis_measure::{AxisPair, LogIdx, TableAxis, VisIdx};
+    use crate::corner::RowsSelected;
+    use crate::selection::{SingleCell, TableSelection};
+    use crate::Remap;
+
+    #[test]
+    fn box_reflects_how_many_rows_are_selected() {
+        let demap = AxisPair::new(Remap::Pristine, Remap::Pristine);
+        let none = TableSelection::NoSelection;
+        assert_eq!(RowsSelected::of(&none, 3), RowsSelected::None);
+
+        let cell = SingleCell::new(
+            AxisPair::new(VisIdx(1), VisIdx(0)),
+            AxisPair::new(LogIdx(1), LogIdx(0)),
+        );
+        let cell: TableSelection = cell.into();
+        assert_eq!(RowsSelected::of(&cell, 3), RowsSelected::Some);
+
+        let mut rows = TableSelection::NoSelection;
+        rows.select_in_axis(TableAxis::Rows, VisIdx(0), &demap);
+        rows.extend_in_axis(TableAxis::Rows, VisIdx(1), &demap);
+        assert_eq!(RowsSelected::of(&rows, 3), RowsSelected::Some);
+        rows.extend_in_axis(TableAxis::Rows, VisIdx(2), &demap);
+        assert_eq!(RowsSelected::of(&rows, 3), RowsSelected::All);
+    }
+}
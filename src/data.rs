@@ -2,9 +2,12 @@ use crate::axis_measure::{LogIdx, VisIdx, VisOffset};
 use crate::data::SortDirection::Descending;
 use druid::im::HashMap;
 use druid::im::Vector;
-use druid::Data;
+use druid::{Data, Env};
 use std::cmp::Ordering;
 use std::cmp::Reverse;
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+use std::sync::Arc;
 
 // This ended up sort of similar to Lens,
 // so I've named the methods similarly.
@@ -15,6 +18,10 @@ pub trait IndexedItems {
                           // This takes a callback so it can work
                           // the same way for concrete and virtual data sources
                           // but still provide a reference.
+                          // A virtual source can return None for items it has yet to load,
+                          // counting them in idx_len so the scroll extent is right. The table
+                          // shows those as placeholders, though sorting, filtering or grouping
+                          // leaves them out until they load.
     fn with<V>(&self, idx: Self::Idx, f: impl FnOnce(&Self::Item) -> V) -> Option<V>;
 
     fn with_mut<V>(&mut self, idx: Self::Idx, f: impl FnOnce(&mut Self::Item) -> V) -> Option<V>;
@@ -73,17 +80,83 @@ impl<RowData> IndexedItems for Vec<RowData> {
     }
 }
 
+// A std Vec isn't Data, so to be table data it goes behind an Arc, compared by pointer.
+// Unlike im::Vector nothing is shared, so the first edit while the Arc is shared (as it is
+// during an event) copies the whole Vec. Rows that an edit leaves the same are not written
+// back, so they don't cause a copy or a repaint.
+impl<RowData: Data> IndexedItems for Arc<Vec<RowData>> {
+    type Item = RowData;
+    type Idx = LogIdx;
+    fn with<V>(&self, idx: LogIdx, f: impl FnOnce(&RowData) -> V) -> Option<V> {
+        self.get(idx.0).map(f)
+    }
+
+    fn with_mut<V>(&mut self, idx: Self::Idx, f: impl FnOnce(&mut Self::Item) -> V) -> Option<V> {
+        let mut row = self.get(idx.0)?.clone();
+        let res = f(&mut row);
+        if !row.same(&self[idx.0]) {
+            Arc::make_mut(self)[idx.0] = row;
+        }
+        Some(res)
+    }
+
+    fn idx_len(&self) -> usize {
+        self.len()
+    }
+}
+
 #[derive(Clone, Data, Debug)]
 pub enum RemapDetails {
     Full(Vector<LogIdx>), // Could do versioning for sameness if Vector is an issue
+    Grouped(Vector<Option<LogIdx>>), // None is a group header, followed by its items
 }
 
 impl RemapDetails {
     fn get_log_idx(&self, idx: VisIdx) -> Option<&LogIdx> {
         match self {
             RemapDetails::Full(v) => v.get(idx.0),
+            RemapDetails::Grouped(v) => v.get(idx.0).and_then(|o| o.as_ref()),
         }
     }
+
+    fn len(&self) -> usize {
+        match self {
+            RemapDetails::Full(v) => v.len(),
+            RemapDetails::Grouped(v) => v.len(),
+        }
+    }
+
+    // Just the items, leaving out any group headers
+    fn items(&self) -> Vector<LogIdx> {
+        match self {
+            RemapDetails::Full(v) => v.clone(),
+            RemapDetails::Grouped(v) => v.iter().flatten().copied().collect(),
+        }
+    }
+
+    fn vis_index(&self) -> VisIndex {
+        let mut index = Vec::new();
+        for vis in 0..self.len() {
+            if let Some(log_idx) = self.get_log_idx(VisIdx(vis)) {
+                if index.len() <= log_idx.0 {
+                    index.resize(log_idx.0 + 1, None);
+                }
+                index[log_idx.0] = Some(VisIdx(vis));
+            }
+        }
+        VisIndex(Arc::new(index))
+    }
+}
+
+// Where each logical item is shown, built along with a remap so looking one up doesn't scan it
+#[derive(Clone, Debug)]
+pub struct VisIndex(Arc<Vec<Option<VisIdx>>>);
+
+impl Data for VisIndex {
+    // It follows from the details it is built with, which are compared
+    fn same(&self, _other: &Self) -> bool {
+        true
+    }
 }
 
 impl Remap {
@@ -91,15 +164,71 @@ impl Remap {
         Remap::Pristine
     }
 
+    pub fn selected(details: RemapDetails) -> Remap {
+        let index = details.vis_index();
+        Remap::Selected(details, index)
+    }
+
     pub fn is_pristine(&self) -> bool {
         matches!(self, Remap::Pristine)
     }
 
     pub fn max_vis_idx(&self, len: usize) -> VisIdx {
-        if let Remap::Selected(RemapDetails::Full(v)) = self {
-            VisIdx(v.len()) + VisOffset(-1)
+        VisIdx(self.vis_len(len)) + VisOffset(-1)
+    }
+
+    // The shown order where it is explicit, otherwise the identity for the first `min_len`
+    pub(crate) fn explicit_order(&self, min_len: usize) -> Vector<LogIdx> {
+        match self {
+            Remap::Selected(details, _) => details.items(),
+            _ => (0..min_len).map(LogIdx).collect(),
+        }
+    }
+
+    // Where each of `len` items is shown. Ones that aren't come after, in their own order.
+    pub(crate) fn vis_ranks(&self, len: usize) -> Vec<usize> {
+        match self {
+            Remap::Selected(details, _) => {
+                let mut ranks: Vec<usize> = (len..len * 2).collect();
+                for (vis, log_idx) in details.items().iter().enumerate() {
+                    if log_idx.0 < len {
+                        ranks[log_idx.0] = vis;
+                    }
+                }
+                ranks
+            }
+            _ => (0..len).collect(),
+        }
+    }
+
+    // How many of the `len` items are shown
+    pub fn vis_len(&self, len: usize) -> usize {
+        if let Remap::Selected(details, _) = self {
+            details.len()
         } else {
-            VisIdx(len) + VisOffset(-1)
+            len
+        }
+    }
+
+    // Where an item comes among the shown ones, counting from 1 and skipping group headers
+    pub(crate) fn row_number(&self, vis_idx: VisIdx) -> Option<usize> {
+        match self {
+            Remap::Selected(RemapDetails::Grouped(v), _) => match v.get(vis_idx.0) {
+                Some(Some(_)) => Some(v.iter().take(vis_idx.0 + 1).flatten().count()),
+                _ => None,
+            },
+            _ => Some(vis_idx.0 + 1),
+        }
+    }
+
+    // For a group header, the first item of its group
+    pub fn group_header_at(&self, vis_idx: VisIdx) -> Option<LogIdx> {
+        match self {
+            Remap::Selected(RemapDetails::Grouped(v), _) => match v.get(vis_idx.0) {
+                Some(None) => v.get(vis_idx.0 + 1).copied().flatten(),
+                _ => None,
+            },
+            _ => None,
         }
     }
 }
@@ -109,7 +238,7 @@ impl Remap {
 pub enum Remap {
     #[default]
     Pristine,
-    Selected(RemapDetails),
+    Selected(RemapDetails, VisIndex), // Made with Remap::selected
     Internal, // This indicates that the source data has done the remapping, ie no wrapper required. Eg sort in db.
               //  need some token to give back to the table rows
 }
@@ -117,15 +246,24 @@ pub enum Remap {
 impl Remap {
     pub fn get_log_idx(&self, vis_idx: VisIdx) -> Option<LogIdx> {
         match self {
-            Remap::Selected(v) => v.get_log_idx(vis_idx).cloned(),
+            Remap::Selected(v, _) => v.get_log_idx(vis_idx).cloned(),
             _ => Some(LogIdx(vis_idx.0)), // Dunno if right for internal
         }
     }
+
+    // Where a logical item is shown, None if it is filtered out
+    pub fn get_vis_idx(&self, log_idx: LogIdx) -> Option<VisIdx> {
+        match self {
+            Remap::Selected(_, VisIndex(index)) => index.get(log_idx.0).copied().flatten(),
+            _ => Some(VisIdx(log_idx.0)),
+        }
+    }
 }
 
 
 
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Debug, Copy, Data)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SortDirection {
     Ascending,
     Descending,
@@ -157,12 +295,94 @@ impl SortSpec {
     }
 }
 
+// Keeps the rows whose text in a column passes the predicate
+#[derive(Clone)]
+pub struct FilterSpec {
+    pub(crate) idx: usize,
+    pub(crate) predicate: Rc<dyn Fn(&str) -> bool>,
+}
+
+impl FilterSpec {
+    pub fn new(idx: usize, predicate: impl Fn(&str) -> bool + 'static) -> Self {
+        FilterSpec {
+            idx,
+            predicate: Rc::new(predicate),
+        }
+    }
+
+    pub(crate) fn matches(&self, text: Option<String>) -> bool {
+        (self.predicate)(text.as_deref().unwrap_or(""))
+    }
+}
+
+impl Data for FilterSpec {
+    fn same(&self, other: &Self) -> bool {
+        self.idx == other.idx && Rc::ptr_eq(&self.predicate, &other.predicate)
+    }
+}
+
+impl Debug for FilterSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FilterSpec")
+            .field("idx", &self.idx)
+            .finish()
+    }
+}
+
+// Cells whose text contains the query are highlighted, see SET_SEARCH
+#[derive(Clone, Debug)]
+pub struct SearchSpec {
+    pub(crate) query: String,
+    lowered: String, // The query to look for when ignoring case
+    pub(crate) case_sensitive: bool,
+    pub(crate) jump: bool,
+}
+
+impl SearchSpec {
+    // Ignores case unless told otherwise
+    pub fn new(query: impl Into<String>) -> Self {
+        let query = query.into();
+        SearchSpec {
+            lowered: query.to_lowercase(),
+            query,
+            case_sensitive: false,
+            jump: false,
+        }
+    }
+
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    // Move the focus to the first match from it as soon as the search is set. The focus stays
+    // put if nothing matches.
+    pub fn jump_to_match(mut self, jump: bool) -> Self {
+        self.jump = jump;
+        self
+    }
+
+    // An empty query matches nothing, rather than every cell
+    pub(crate) fn matches(&self, text: &str) -> bool {
+        if self.query.is_empty() {
+            false
+        } else if self.case_sensitive {
+            text.contains(&self.query)
+        } else {
+            text.to_lowercase().contains(&self.lowered)
+        }
+    }
+}
+
 #[derive(Clone, Debug, Data)]
 #[derive(Default)]
 pub struct RemapSpec {
-    pub(crate) sort_by: Vector<SortSpec>, // columns sorted
-    // filters
+    pub(crate) sort_by: Vector<SortSpec>,           // columns sorted
+    pub(crate) filters: Vector<FilterSpec>,         // all must pass for a row to show
+    pub(crate) moved_order: Option<Vector<LogIdx>>, // Rows as the user last moved them
+    pub(crate) group_by: Option<usize>,             // column whose values group the rows
     pub(crate) placements: im::HashMap<LogIdx, (VisIdx, usize)>, // Explicit moves
+    pub(crate) hidden: im::HashSet<LogIdx>,                      // Left out of the remap
 }
 
 impl RemapSpec {
@@ -171,48 +391,135 @@ impl RemapSpec {
     }
 
     pub(crate) fn is_empty(&self) -> bool {
-        self.sort_by.is_empty()
+        self.sort_by.is_empty() && self.filters.is_empty() && self.group_by.is_none()
     }
 
-    pub(crate) fn toggle_sort(&mut self, log_idx: LogIdx, extend: bool) -> bool {
-        let sort_by = &mut self.sort_by;
-        let log_idx = log_idx.0;
+    // Rows sharing a value in this column are shown together under a header
+    pub fn set_group_by(&mut self, group_by: Option<usize>) {
+        self.group_by = group_by
+    }
 
-        match sort_by.last() {
-            Some(SortSpec { idx, direction }) if log_idx == *idx => {
-                let dir = *direction;
-                sort_by.pop_back();
-                if dir == SortDirection::Ascending {
-                    sort_by.push_back(SortSpec::new(log_idx, SortDirection::Descending));
-                }
+    // Replaces any filter already on the same column
+    pub fn set_filter(&mut self, filter: FilterSpec) {
+        self.filters.retain(|f| f.idx != filter.idx);
+        self.filters.push_back(filter)
+    }
+
+    pub fn clear_filters(&mut self) {
+        self.filters.clear()
+    }
+
+    // Ascending, then descending, then not sorted by the column at all unless it is fixed.
+    // Extending keeps the other columns' sorts, otherwise the column is sorted by alone.
+    // Sorts on the columns `fixed` by logical column are kept either way, ahead of the
+    // others, so the rows are only ever sorted within them.
+    pub(crate) fn toggle_sort(&mut self, log_idx: LogIdx, extend: bool, fixed: &[bool]) -> bool {
+        // A new sort replaces any rows moved by hand
+        self.moved_order = None;
+        let log_idx = log_idx.0;
+        let is_fixed = |idx: usize| fixed.get(idx) == Some(&true);
+        let position = |sort_by: &Vector<SortSpec>| sort_by.iter().position(|s| s.idx == log_idx);
+
+        let current = position(&self.sort_by).map(|pos| self.sort_by[pos].direction);
+        let next = match current {
+            None => Some(SortDirection::Ascending),
+            Some(SortDirection::Ascending) => Some(SortDirection::Descending),
+            Some(SortDirection::Descending) if is_fixed(log_idx) => Some(SortDirection::Ascending),
+            Some(SortDirection::Descending) => None,
+        };
+        if !extend {
+            self.sort_by.retain(|s| s.idx == log_idx || is_fixed(s.idx));
+        }
+        match (position(&self.sort_by), next) {
+            (Some(pos), Some(direction)) => self.sort_by[pos].direction = direction,
+            (Some(pos), None) => {
+                self.sort_by.remove(pos);
             }
-            _ => {
-                if !extend {
-                    sort_by.clear();
+            (None, Some(direction)) => {
+                let sort = SortSpec::new(log_idx, direction);
+                if is_fixed(log_idx) {
+                    let after_fixed = self.sort_by.iter().take_while(|s| is_fixed(s.idx)).count();
+                    self.sort_by.insert(after_fixed, sort)
+                } else {
+                    self.sort_by.push_back(sort)
                 }
-                sort_by.push_back(SortSpec::new(log_idx, SortDirection::Ascending));
             }
+            (None, None) => (),
         }
         // Handle sorting disabled for a column
         true
     }
 
+    // Moves rows within the order they are shown in now. That order outlives the sort it came
+    // from, which is cleared as it no longer describes the rows.
+    pub(crate) fn move_rows(&mut self, shown: Vector<LogIdx>, moved: &[LogIdx], to: VisIdx) {
+        let mut order: Vector<LogIdx> = shown
+            .into_iter()
+            .filter(|log_idx| !moved.contains(log_idx))
+            .collect();
+        let at = to.0.min(order.len());
+        for (offset, log_idx) in moved.iter().enumerate() {
+            order.insert(at + offset, *log_idx);
+        }
+        self.moved_order = Some(order);
+        self.sort_by.clear();
+    }
+
+    // All `len` rows, in the moved order if there is one. Rows it doesn't mention, like
+    // ones added since or hidden by a filter at the time, follow in their own order.
+    pub(crate) fn row_order(&self, len: usize) -> Vector<LogIdx> {
+        match &self.moved_order {
+            Some(moved) => {
+                let mut seen = vec![false; len];
+                let mut order: Vector<LogIdx> = Vector::new();
+                for log_idx in moved {
+                    if log_idx.0 < len && !seen[log_idx.0] {
+                        seen[log_idx.0] = true;
+                        order.push_back(*log_idx);
+                    }
+                }
+                order.extend((0..len).filter(|idx| !seen[*idx]).map(LogIdx));
+                order
+            }
+            None => (0..len).map(LogIdx).collect(),
+        }
+    }
+
     pub(crate) fn place(&mut self, log_idx: LogIdx, vis_idx: VisIdx) {
         self.placements
             .insert(log_idx, (vis_idx, self.placements.len()));
         log::info!("Placing {:?} at {:?}", log_idx, vis_idx)
     }
 
+    // Hidden items keep any placement, so they come back where they were
+    pub fn hide(&mut self, log_idx: LogIdx) {
+        self.hidden.insert(log_idx);
+    }
+
+    pub fn show(&mut self, log_idx: LogIdx) {
+        self.hidden.remove(&log_idx);
+    }
+
+    pub fn is_hidden(&self, log_idx: LogIdx) -> bool {
+        self.hidden.contains(&log_idx)
+    }
+
+    // Placements are where items were dropped among those shown, so hidden items are left out
+    // before placing the rest
     pub(crate) fn remap_placements(&self, max_log_idx: LogIdx) -> Remap {
-        if self.placements.is_empty() {
+        if self.placements.is_empty() && self.hidden.is_empty() {
             Remap::new()
         } else {
             let mut all: Vector<LogIdx> = Vector::new();
             let unplaced_log: Vector<LogIdx> = (0..=max_log_idx.0)
-                .filter(|li| !self.placements.contains_key(&LogIdx(*li)))
                 .map(LogIdx)
+                .filter(|li| !self.placements.contains_key(li) && !self.hidden.contains(li))
+                .collect();
+            let mut s_placements: Vec<_> = self
+                .placements
+                .iter()
+                .filter(|(log, _)| !self.hidden.contains(log))
                 .collect();
-            let mut s_placements: Vec<_> = self.placements.iter().collect();
             s_placements.sort_by_key(|(_, (_, o))| Reverse(*o));
             let mut placed_by_vis: HashMap<VisIdx, LogIdx> = HashMap::new();
 
@@ -230,10 +537,10 @@ impl RemapSpec {
                 }
                 all.push_back(log);
             }
-            for place in placed_by_vis.values() {
-                all.push_back(*place)
-            }
-            Remap::Selected(RemapDetails::Full(all))
+            let mut rest: Vec<_> = placed_by_vis.into_iter().collect();
+            rest.sort_by_key(|(vis, _)| *vis);
+            all.extend(rest.into_iter().map(|(_, log)| log));
+            Remap::selected(RemapDetails::Full(all))
         }
     }
 }
@@ -247,5 +554,112 @@ where
     // This takes our normal data and a spec, and returns a remapped view of it if required
     fn sort_fixed(&self, idx: usize) -> bool;
     fn initial_spec(&self) -> RemapSpec;
-    fn remap_items(&self, table_data: &TableData, remap_spec: &RemapSpec) -> Remap;
+    // The prior remap is what is shown now, which can settle ties in the new order
+    fn remap_items(
+        &self,
+        table_data: &TableData,
+        remap_spec: &RemapSpec,
+        prior: &Remap,
+        env: &Env,
+    ) -> Remap;
+
+    // A cheaper remap for when only the given row has changed. None means use remap_items.
+    fn remap_item_changed(
+        &self,
+        _table_data: &TableData,
+        _remap_spec: &RemapSpec,
+        _prior: &Remap,
+        _log_idx: LogIdx,
+        _env: &Env,
+    ) -> Option<Remap> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::RemapDetails;
+    use crate::{IndexedItems, LogIdx, Remap, RemapSpec, SearchSpec, VisIdx};
+    use std::sync::Arc;
+
+    #[test]
+    fn arc_vec_only_copies_on_change() {
+        let mut data = Arc::new(vec![1u32, 2, 3]);
+        let held = data.clone();
+
+        data.with_mut(LogIdx(1), |row| *row = 2);
+        assert!(Arc::ptr_eq(&data, &held));
+
+        data.with_mut(LogIdx(1), |row| *row = 5);
+        assert!(!Arc::ptr_eq(&data, &held));
+        assert_eq!(data.with(LogIdx(1), |row| *row), Some(5));
+        assert_eq!(held[1], 2);
+    }
+
+    #[test]
+    fn row_numbers_skip_group_headers() {
+        let groups = [None, Some(2), Some(0), None, Some(1)];
+        let groups = groups.iter().map(|g| g.map(LogIdx)).collect();
+        let remap = Remap::selected(RemapDetails::Grouped(groups));
+        let numbers: Vec<_> = (0..5).map(|vis| remap.row_number(VisIdx(vis))).collect();
+        assert_eq!(numbers, vec![None, Some(1), Some(2), None, Some(3)]);
+        assert_eq!(Remap::Pristine.row_number(VisIdx(4)), Some(5));
+    }
+
+    #[test]
+    fn shown_places_are_looked_up_from_the_index() {
+        let groups = [None, Some(2), Some(0), None, Some(1)];
+        let groups = groups.iter().map(|g| g.map(LogIdx)).collect();
+        let remap = Remap::selected(RemapDetails::Grouped(groups));
+        let place = |log| remap.get_vis_idx(LogIdx(log)).map(|vis| vis.0);
+        let places: Vec<_> = (0..4).map(place).collect();
+        assert_eq!(places, vec![Some(2), Some(4), Some(1), None]);
+
+        let filtered = Remap::selected(RemapDetails::Full(vec![LogIdx(3), LogIdx(1)].into()));
+        assert_eq!(filtered.get_vis_idx(LogIdx(1)), Some(VisIdx(1)));
+        assert_eq!(filtered.get_vis_idx(LogIdx(0)), None);
+        assert_eq!(Remap::Pristine.get_vis_idx(LogIdx(7)), Some(VisIdx(7)));
+    }
+
+    #[test]
+    fn hidden_columns_leave_the_remap() {
+        let mut spec = RemapSpec::default();
+        spec.place(LogIdx(3), VisIdx(0));
+        spec.hide(LogIdx(1));
+        let remap = spec.remap_placements(LogIdx(3));
+        let shown: Vec<_> = (0..3)
+            .filter_map(|vis| remap.get_log_idx(VisIdx(vis)))
+            .collect();
+        assert_eq!(shown, vec![LogIdx(3), LogIdx(0), LogIdx(2)]);
+        assert_eq!(remap.vis_len(4), 3);
+
+        spec.show(LogIdx(1));
+        assert_eq!(spec.remap_placements(LogIdx(3)).vis_len(4), 4);
+    }
+
+    #[test]
+    fn placements_count_only_the_shown_columns() {
+        let mut spec = RemapSpec::default();
+        spec.hide(LogIdx(1));
+        // Dragged to be the third of the shown columns, 0 2 3
+        spec.place(LogIdx(0), VisIdx(2));
+        let remap = spec.remap_placements(LogIdx(3));
+        let shown: Vec<_> = (0..3)
+            .filter_map(|vis| remap.get_log_idx(VisIdx(vis)))
+            .collect();
+        assert_eq!(shown, vec![LogIdx(2), LogIdx(3), LogIdx(0)]);
+    }
+
+    #[test]
+    fn search_ignores_case_unless_asked() {
+        let search = SearchSpec::new("lon");
+        assert!(search.matches("London"));
+        assert!(search.matches("BARCELONA"));
+        assert!(!search.matches("Paris"));
+
+        let search = search.case_sensitive(true);
+        assert!(!search.matches("London"));
+        assert!(search.matches("Barcelona"));
+        assert!(!SearchSpec::new("").matches("London"));
+    }
 }
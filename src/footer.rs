@@ -0,0 +1,159 @@
+use druid::kurbo::Line;
+use druid::widget::prelude::*;
+use druid::{
+    Affine, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, Rect, Size, UpdateCtx, Widget,
+};
+
+use crate::axis_measure::{TableAxis, VisIdx};
+use crate::columns::{CellCtx, CellRender, RIGHT_TO_LEFT};
+use crate::config::{ResolvedTableConfig, TableConfig};
+use crate::render_ext::RenderContextExt;
+use crate::table::TableState;
+
+// The row below the cells showing each column's footer value. It follows the column measure
+// like the column headings do, so it stays aligned when columns are resized or frozen.
+pub struct Footer<Render: CellRender<String>> {
+    config: TableConfig,
+    resolved_config: Option<ResolvedTableConfig>,
+    render: Render,
+}
+
+impl<Render: CellRender<String>> Footer<Render> {
+    pub fn new(config: TableConfig, render: Render) -> Self {
+        Footer {
+            config,
+            resolved_config: None,
+            render,
+        }
+    }
+
+    fn paint_value<TableData: Data>(
+        &self,
+        ctx: &mut PaintCtx,
+        data: &TableState<TableData>,
+        env: &Env,
+        vis_idx: VisIdx,
+    ) -> Option<()> {
+        let rtc = self.resolved_config.as_ref()?;
+        let measure = &data.measures[TableAxis::Columns];
+        let x = measure.first_pixel_from_vis(vis_idx)?;
+        let width = measure.pixels_length_for_vis(vis_idx)?;
+        let cell_rect = Rect::new(x, 0., x + width, rtc.col_header_height);
+
+        let padded_rect = cell_rect.inset(-rtc.cell_padding);
+        let log_idx = data.remaps[TableAxis::Columns].get_log_idx(vis_idx)?;
+        if let Some(Some(value)) = data.footer.get(log_idx.0) {
+            let cell = CellCtx::Header(&TableAxis::Columns, log_idx, None, padded_rect.size());
+            let render = &self.render;
+            ctx.with_save(|ctx| {
+                ctx.clip(padded_rect);
+                ctx.transform(Affine::translate(padded_rect.origin().to_vec2()));
+                ctx.with_child_ctx(padded_rect, |ctxt| {
+                    render.paint(ctxt, &cell, value, env);
+                });
+            });
+        }
+        let thickness = rtc.cell_border_thickness;
+        ctx.stroke_bottom_left_border(&cell_rect, rtc.gridlines, &rtc.cells_border, thickness);
+        Some(())
+    }
+}
+
+impl<TableData: Data, Render: CellRender<String>> Widget<TableState<TableData>> for Footer<Render> {
+    fn event(
+        &mut self,
+        _ctx: &mut EventCtx,
+        _event: &Event,
+        _data: &mut TableState<TableData>,
+        _env: &Env,
+    ) {
+    }
+
+    fn lifecycle(
+        &mut self,
+        _ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        _data: &TableState<TableData>,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.resolved_config = Some(self.config.resolve(env));
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: &TableState<TableData>,
+        data: &TableState<TableData>,
+        _env: &Env,
+    ) {
+        if !old_data.same(data) {
+            ctx.request_layout();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &TableState<TableData>,
+        _env: &Env,
+    ) -> Size {
+        bc.debug_check("Footer");
+        let height = self
+            .resolved_config
+            .as_ref()
+            .map_or(TableAxis::Columns.default_header_cross(), |rtc| {
+                rtc.col_header_height
+            });
+        bc.constrain(Size::new(
+            data.measures[TableAxis::Columns].total_pixel_length(),
+            height,
+        ))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &TableState<TableData>, env: &Env) {
+        let rtc = match &self.resolved_config {
+            Some(rtc) => rtc,
+            None => return,
+        };
+        let env = &env.clone().adding(RIGHT_TO_LEFT, rtc.right_to_left);
+        self.render.init(ctx, env);
+        let rect = ctx.region().bounding_box();
+        ctx.fill(rect, &rtc.header_background);
+
+        let height = rtc.col_header_height;
+        let header_background = rtc.header_background.clone();
+        let divider = rtc.frozen_divider.clone();
+        let thickness = (rtc.cell_border_thickness * 2.).max(1.);
+        let frozen = rtc.frozen_columns;
+
+        let measure = &data.measures[TableAxis::Columns];
+        let (start, end) = measure.vis_range_from_pixels(rect.x0, rect.x1);
+        for vis_idx in VisIdx::range_inc_iter(start, end) {
+            self.paint_value(ctx, data, env, vis_idx);
+        }
+
+        // Frozen columns are drawn again over the scrolled ones, as in the headings
+        if let Some((shift, width)) = data.frozen_band(TableAxis::Columns, frozen) {
+            if shift > 0. {
+                ctx.with_save(|ctx| {
+                    ctx.transform(Affine::translate((shift, 0.)));
+                    let band = Rect::new(0., 0., width, height);
+                    ctx.clip(band);
+                    ctx.fill(band, &header_background);
+                    for vis_idx in VisIdx::range_inc_iter(VisIdx(0), VisIdx(frozen - 1)) {
+                        self.paint_value(ctx, data, env, vis_idx);
+                    }
+                });
+            }
+            ctx.stroke(
+                Line::new((shift + width, 0.), (shift + width, height)),
+                &divider,
+                thickness,
+            );
+        }
+    }
+}
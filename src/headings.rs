@@ -1,19 +1,29 @@
 use std::marker::PhantomData;
 
+use druid::commands::CLOSE_WINDOW;
+use druid::kurbo::Line;
+use druid::piet::{FontFamily, PietText, Text, TextAlignment, TextLayout, TextLayoutBuilder};
 use druid::widget::prelude::*;
+use druid::widget::TextBox;
 use druid::{
-    Affine, BoxConstraints, Cursor, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
-    PaintCtx, Rect, Size, UpdateCtx, Widget,
+    theme, Affine, BoxConstraints, Command, Data, Env, Event, EventCtx, InternalLifeCycle, KbKey,
+    LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, Point, Rect, Selector, Size, UpdateCtx, Widget,
+    WidgetExt, WidgetId, WidgetPod, WindowId,
 };
 
 use crate::axis_measure::{AxisMeasure, LogIdx, TableAxis, VisIdx, VisOffset};
-use crate::columns::{CellCtx, CellRender};
+use crate::cells::{AUTO_SIZE_COLUMN, RENAME_COLUMN};
+use crate::columns::{
+    CellCtx, CellRender, TextCell, HEADER_LINE_HEIGHT, RIGHT_TO_LEFT, SORTED_COLUMNS,
+    SORT_INDICATOR_COLOR,
+};
 use crate::config::{ResolvedTableConfig, TableConfig};
-use crate::data::{IndexedItems, SortSpec};
+use crate::data::{IndexedItems, Remap, RemapSpec, SortSpec};
 use crate::headings::HeaderMovement::{Disallowed, Permitted};
 use crate::numbers_table::LogIdxTable;
 use crate::render_ext::RenderContextExt;
-use crate::table::TableState;
+use crate::table::{MeasuredDemap, TableState};
+use crate::tooltip::{show_tooltip, Tooltip};
 use crate::IndicesSelection;
 use std::collections::HashMap;
 use druid_bindings::{BindableAccess, bindable_self_body};
@@ -23,20 +33,43 @@ pub trait HeadersFromData {
     type Header: Data;
     type Headers: IndexedItems<Item = Self::Header, Idx = LogIdx>;
     fn get_headers(&self, table_data: &Self::TableData) -> Self::Headers;
+
+    // Gives a header the name the user typed, see TableConfig::editable_headers. False if
+    // it can't be renamed.
+    fn rename(&mut self, _idx: LogIdx, _name: &str) -> bool {
+        false
+    }
 }
 
-pub struct SuppliedHeaders<Headers, TableData> {
+pub struct SuppliedHeaders<Headers: IndexedItems, TableData> {
     headers: Headers,
+    rename: Option<Box<dyn Fn(&mut Headers::Item, &str)>>,
     phantom_td: PhantomData<TableData>,
 }
 
-impl<Headers, TableData> SuppliedHeaders<Headers, TableData> {
+impl<Headers: IndexedItems, TableData> SuppliedHeaders<Headers, TableData> {
     pub fn new(headers: Headers) -> Self {
         SuppliedHeaders {
             headers,
+            rename: None,
             phantom_td: Default::default(),
         }
     }
+
+    // Lets the user rename headers, see TableConfig::editable_headers, by giving a header the
+    // name they typed
+    pub fn renamed_with(mut self, rename: impl Fn(&mut Headers::Item, &str) + 'static) -> Self {
+        self.rename = Some(Box::new(rename));
+        self
+    }
+
+    // Lets the user rename headers that can be made from text, like String or ArcStr
+    pub fn renamable(self) -> Self
+    where
+        Headers::Item: for<'a> From<&'a str>,
+    {
+        self.renamed_with(|header, name| *header = name.into())
+    }
 }
 
 impl<Headers: IndexedItems<Idx = LogIdx> + Clone, TableData: Data> HeadersFromData
@@ -50,6 +83,16 @@ where
     fn get_headers(&self, _table_data: &Self::TableData) -> Headers {
         self.headers.clone()
     }
+
+    fn rename(&mut self, idx: LogIdx, name: &str) -> bool {
+        let rename = match &self.rename {
+            Some(rename) => rename,
+            None => return false,
+        };
+        self.headers
+            .with_mut(idx, |header| rename(header, name))
+            .is_some()
+    }
 }
 
 #[derive(Clone)]
@@ -90,7 +133,41 @@ impl<TableData: IndexedItems + Data> HeadersFromData for HeadersFromIndices<Tabl
 enum HeaderMovement {
     Disallowed,
     Permitted,
-    Moving(VisIdx),
+    Moving(VisIdx, VisIdx), // Picked up from, and where it would drop
+}
+
+// A column heading being renamed in a text box over it
+struct Renaming {
+    idx: LogIdx,
+    original: String,
+    text: String,
+    editor: WidgetPod<String, Box<dyn Widget<String>>>,
+}
+
+// Sent by the headings to themselves once the text box is added, and when it loses focus
+const FOCUS_RENAMING: Selector<WidgetId> = Selector::new("druid-builtin.table.focus-renaming");
+const FINISH_RENAMING: Selector<()> = Selector::new("druid-builtin.table.finish-renaming");
+
+// The configured width of the row headings, widened to fit the largest of `rows` numbers
+// if they are shown
+pub(crate) fn row_headings_width(
+    text: &mut PietText,
+    config: &TableConfig,
+    rows: usize,
+    env: &Env,
+) -> f64 {
+    let width = config.row_header_width.resolve(env);
+    if !config.row_numbers {
+        return width;
+    }
+    let widest = "8".repeat(rows.max(1).to_string().len());
+    text.new_text_layout(widest)
+        .font(FontFamily::SYSTEM_UI, env.get(theme::TEXT_SIZE_NORMAL))
+        .build()
+        .map_or(width, |layout| {
+            let padding = config.cell_padding.resolve(env);
+            width.max(layout.size().width.ceil() + padding * 4.)
+        })
 }
 
 pub struct Headings<HeadersSource, Render>
@@ -104,9 +181,23 @@ where
     headers_source: HeadersSource,
     headers: Option<HeadersSource::Headers>,
     header_render: Render,
+    group_render: TextCell,
+    number_render: TextCell,
     header_movement: HeaderMovement,
     resize_dragging: Option<VisIdx>,
     selection_dragging: bool,
+    tooltip: Tooltip<VisIdx>,
+    cross_length: Option<f64>, // Across the headings, not counting any group band, once laid out
+    renaming: Option<Renaming>,
+    wrapped: Option<WrappedHeight>,
+}
+
+// The height wrap_headers needed, with the widths, order and sorts it was measured at
+struct WrappedHeight {
+    measure: AxisMeasure,
+    remap: Remap,
+    sorts: RemapSpec,
+    height: f64,
 }
 
 impl<HeadersSource, Render> Headings<HeadersSource, Render>
@@ -128,24 +219,450 @@ where
             headers_source,
             headers: None,
             header_render,
+            group_render: TextCell::new().text_color(theme::TEXT_COLOR),
+            number_render: TextCell::new()
+                .text_color(theme::TEXT_COLOR)
+                .text_alignment(TextAlignment::End),
             header_movement: if allow_moves { Permitted } else { Disallowed },
             resize_dragging: None,
             selection_dragging: false,
+            tooltip: Tooltip::Idle,
+            cross_length: None,
+            renaming: None,
+            wrapped: None,
+        }
+    }
+
+    fn start_renaming(&mut self, ctx: &mut EventCtx, log_idx: LogIdx, env: &Env) {
+        let cell = CellCtx::Header(&self.axis, log_idx, None, Size::ZERO);
+        let render = &self.header_render;
+        let text = self
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.with(log_idx, |header| render.as_text(&cell, header, env)))
+            .flatten();
+        if let Some(text) = text {
+            let id = WidgetId::next();
+            self.renaming = Some(Renaming {
+                idx: log_idx,
+                original: text.clone(),
+                text,
+                editor: WidgetPod::new(Box::new(TextBox::new().with_id(id))),
+            });
+            ctx.children_changed();
+            ctx.request_layout();
+            ctx.submit_command(Command::new(FOCUS_RENAMING, id, ctx.widget_id()));
+        }
+    }
+
+    // Keeps the new name if `commit` is set and the headers can take it
+    fn finish_renaming(
+        &mut self,
+        ctx: &mut EventCtx,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        commit: bool,
+    ) {
+        let renaming = match self.renaming.take() {
+            Some(renaming) => renaming,
+            None => return,
+        };
+        let renamed = commit && renaming.text != renaming.original;
+        if renamed && self.headers_source.rename(renaming.idx, &renaming.text) {
+            self.headers = Some(self.headers_source.get_headers(&data.data));
+            self.wrapped = None;
+            ctx.submit_command(RENAME_COLUMN.with((renaming.idx, renaming.text)));
+        }
+        ctx.children_changed();
+        ctx.request_layout();
+    }
+
+    // Gives an event to the heading being renamed first. True if nothing else should see it.
+    fn rename_event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        env: &Env,
+    ) -> bool {
+        let renaming = match &mut self.renaming {
+            Some(renaming) => renaming,
+            None => return false,
+        };
+        match event {
+            Event::KeyDown(ke) if matches!(ke.key, KbKey::Enter | KbKey::Escape) => {
+                self.finish_renaming(ctx, data, ke.key == KbKey::Enter);
+                ctx.set_handled();
+                true
+            }
+            Event::Command(cmd) if cmd.is(FINISH_RENAMING) => {
+                self.finish_renaming(ctx, data, true);
+                true
+            }
+            Event::Command(cmd) if cmd.is(FOCUS_RENAMING) => {
+                if let Some(id) = cmd.get(FOCUS_RENAMING) {
+                    ctx.set_focus(*id);
+                }
+                true
+            }
+            // Clicking another heading keeps the name, then goes on to do what it does
+            Event::MouseDown(me) if !renaming.editor.layout_rect().contains(me.pos) => {
+                self.finish_renaming(ctx, data, true);
+                false
+            }
+            _ => {
+                let before = renaming.text.clone();
+                renaming.editor.event(ctx, event, &mut renaming.text, env);
+                if renaming.text != before {
+                    ctx.request_update();
+                }
+                ctx.is_handled()
+            }
         }
     }
 
     fn set_pix_length_for_axis(
         &mut self,
-        measure: &mut AxisMeasure,
+        data: &mut TableState<<HeadersSource as HeadersFromData>::TableData>,
         ctx: &mut EventCtx,
         vis_idx: VisIdx,
         pixel: f64,
     ) {
+        let measure = &mut data.measures[self.axis];
+        // Columns have their own limits in the measure. Only rows on screen can be dragged, so
+        // the ones above, and the scroll offset, stay where they are.
+        let first = measure.first_pixel_from_vis(vis_idx);
+        let pixel = match (self.axis, &self.resolved_config, first) {
+            (TableAxis::Rows, Some(rtc), Some(first)) => pixel.max(first + rtc.min_row_height),
+            _ => pixel,
+        };
+        let before = measure.total_pixel_length();
         measure.set_far_pixel_for_vis(vis_idx, pixel);
+        if measure.is_reversed() {
+            // Reversed headings grow leftwards, so keep the ones in view where they are
+            data.scroll_x = (data.scroll_x + measure.total_pixel_length() - before).max(0.);
+        }
         // TODO : this might be overkill if we knew that we are bigger that the viewport - repaint would work
         ctx.request_layout();
     }
 
+    fn renames(&self) -> bool {
+        self.axis == TableAxis::Columns && self.config.editable_headers
+    }
+
+    // The leading columns or rows on this axis that stay in view
+    fn frozen_count(&self) -> usize {
+        match (self.axis, &self.resolved_config) {
+            (TableAxis::Columns, Some(rtc)) => rtc.frozen_columns,
+            (TableAxis::Rows, Some(rtc)) => rtc.frozen_rows,
+            _ => 0,
+        }
+    }
+
+    // The main axis pixel in the measure, undoing the shift of frozen headings
+    fn pixel_in_measure(
+        &self,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        pos: &Point,
+    ) -> f64 {
+        let pixel = self.axis.main_pixel_from_point(pos);
+        let frozen = self.frozen_count();
+        match self.resize_dragging {
+            // Keep resizing against the heading being dragged, wherever the mouse goes
+            Some(idx) => pixel - data.frozen_shift(self.axis, idx, frozen),
+            None => data.unfrozen_pixel(self.axis, pixel, frozen),
+        }
+    }
+
+    // The configured tooltip, or else the heading's own text when it is too long to fit
+    fn tooltip_text(
+        &self,
+        text: &mut PietText,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        env: &Env,
+        vis_idx: VisIdx,
+    ) -> Option<String> {
+        let rtc = self.resolved_config.as_ref()?;
+        if self.axis != TableAxis::Columns {
+            return None;
+        }
+        let log_idx = data.remaps[self.axis].get_log_idx(vis_idx)?;
+        if let Some(Some(tooltip)) = self.config.header_tooltips.get(log_idx.0) {
+            return Some(tooltip.clone());
+        }
+        // Wrapped headers show all of their text already
+        if self.config.wrap_headers {
+            return None;
+        }
+        let padding = 2. * rtc.cell_padding;
+        let width = data.measures[self.axis].pixels_length_for_vis(vis_idx)? - padding;
+        let size = Size::new(width, rtc.col_header_height - padding);
+        let cell = CellCtx::Header(&self.axis, log_idx, None, size);
+        let header_render = &self.header_render;
+        self.headers
+            .as_ref()?
+            .with(log_idx, |header| {
+                let wanted = header_render.measure(text, self.axis, &cell, header, env)?;
+                if wanted > width {
+                    header_render.as_text(&cell, header, env)
+                } else {
+                    None
+                }
+            })
+            .flatten()
+    }
+
+    fn show_tooltip(
+        &self,
+        ctx: &mut EventCtx,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        env: &Env,
+        vis_idx: VisIdx,
+        window_pos: Point,
+    ) -> Option<WindowId> {
+        let tooltip = self.tooltip_text(ctx.text(), data, env, vis_idx)?;
+        Some(show_tooltip(ctx, tooltip, window_pos, env))
+    }
+
+    // Resting on a heading for a while shows its tooltip. Moving off it, pressing or
+    // starting a drag hides it again.
+    fn track_tooltip(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        env: &Env,
+    ) {
+        match event {
+            Event::MouseMove(me) => {
+                let pix_main = self.pixel_in_measure(data, &me.pos);
+                let measure = &data.measures[self.axis];
+                let dragging = self.resize_dragging.is_some()
+                    || self.selection_dragging
+                    || matches!(self.header_movement, HeaderMovement::Moving(..));
+                let hovered = measure
+                    .vis_idx_from_pixel(pix_main)
+                    .filter(|_| !dragging && measure.pixel_near_border(pix_main).is_none());
+
+                if hovered.as_ref() != self.tooltip.part() {
+                    if let Some(window) = self.tooltip.dismiss() {
+                        ctx.submit_command(CLOSE_WINDOW.to(window));
+                    }
+                    if let Some(vis_idx) = hovered {
+                        let token = ctx.request_timer(self.config.tooltip_delay);
+                        self.tooltip = Tooltip::Waiting(vis_idx, token, me.window_pos);
+                    }
+                } else if let Tooltip::Waiting(_, _, window_pos) = &mut self.tooltip {
+                    *window_pos = me.window_pos;
+                }
+            }
+            Event::MouseDown(_) | Event::Wheel(_) => {
+                if let Some(window) = self.tooltip.dismiss() {
+                    ctx.submit_command(CLOSE_WINDOW.to(window));
+                }
+            }
+            Event::Timer(fired) => {
+                if let Tooltip::Waiting(vis_idx, token, window_pos) = self.tooltip {
+                    if *fired == token {
+                        let window = self.show_tooltip(ctx, data, env, vis_idx, window_pos);
+                        self.tooltip = Tooltip::Shown(vis_idx, window);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // The height of the band above the column headings for their groups, if there are any
+    fn group_band(&self) -> f64 {
+        match (self.axis, &self.resolved_config) {
+            (TableAxis::Columns, Some(rtc)) if self.config.has_column_groups() => {
+                rtc.col_header_height
+            }
+            _ => 0.,
+        }
+    }
+
+    fn group_of(
+        &self,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        vis_idx: VisIdx,
+    ) -> Option<&String> {
+        let log_idx = data.remaps[self.axis].get_log_idx(vis_idx)?;
+        self.config.column_groups.get(log_idx.0)?.as_ref()
+    }
+
+    // The first and last of the neighbouring columns in the same group as this one
+    fn group_extent(
+        &self,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        vis_idx: VisIdx,
+    ) -> Option<(VisIdx, VisIdx)> {
+        let group = self.group_of(data, vis_idx)?;
+        let last = data.remaps[self.axis].max_vis_idx(self.config.column_groups.len());
+        let (mut start, mut end) = (vis_idx, vis_idx);
+        while start > VisIdx(0) && self.group_of(data, start - VisOffset(1)) == Some(group) {
+            start = start - VisOffset(1);
+        }
+        while end < last && self.group_of(data, end + VisOffset(1)) == Some(group) {
+            end = end + VisOffset(1);
+        }
+        Some((start, end))
+    }
+
+    // One cell spanning each run of grouped columns. Ungrouped ones get an empty cell each.
+    fn paint_groups(
+        &self,
+        ctx: &mut PaintCtx,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        env: &Env,
+        start: VisIdx,
+        end: VisIdx,
+    ) -> Option<()> {
+        let rtc = self.resolved_config.as_ref()?;
+        let measure = &data.measures[self.axis];
+        let mut vis_idx = start;
+        while vis_idx <= end {
+            let (first, last) = self
+                .group_extent(data, vis_idx)
+                .unwrap_or((vis_idx, vis_idx));
+            let (x0, x1) = measure.pixel_range(first, last)?;
+            let cell_rect = Rect::new(x0, 0., x1, self.group_band());
+
+            if let Some(group) = self.group_of(data, vis_idx) {
+                let padded_rect = cell_rect.inset(-rtc.cell_padding);
+                let log_idx = data.remaps[self.axis].get_log_idx(first)?;
+                let cell = CellCtx::Header(&self.axis, log_idx, None, padded_rect.size());
+                ctx.with_save(|ctx| {
+                    ctx.clip(padded_rect);
+                    ctx.transform(Affine::translate(padded_rect.origin().to_vec2()));
+                    ctx.with_child_ctx(padded_rect, |ctxt| {
+                        self.group_render.paint(ctxt, &cell, group, env);
+                    });
+                });
+            }
+            let thickness = rtc.cell_border_thickness;
+            ctx.stroke_bottom_left_border(&cell_rect, rtc.gridlines, &rtc.cells_border, thickness);
+            vis_idx = last + VisOffset(1);
+        }
+        Some(())
+    }
+
+    // A line on the side of the heading that dragged headings would take the place of
+    fn paint_drop_target(
+        &self,
+        ctx: &mut PaintCtx,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        rtc: &ResolvedTableConfig,
+        cross: f64,
+    ) -> Option<()> {
+        let (from, to) = match self.header_movement {
+            HeaderMovement::Moving(from, to) if from != to => (from, to),
+            _ => return None,
+        };
+        let measure = &data.measures[self.axis];
+        // Reversed, the far side of a heading is drawn on its left
+        let pixel = if (to > from) != measure.is_reversed() {
+            measure.far_pixel_from_vis(to)?
+        } else {
+            measure.first_pixel_from_vis(to)?
+        };
+        let pixel = pixel + data.frozen_shift(self.axis, to, self.frozen_count());
+        let line = match self.axis {
+            TableAxis::Columns => Line::new((pixel, 0.), (pixel, cross)),
+            TableAxis::Rows => Line::new((0., pixel), (cross, pixel)),
+        };
+        ctx.stroke(line, &rtc.focus_color, 2.);
+        Some(())
+    }
+
+    // The sorts by the heading they are on, and the env to draw and measure headers with
+    fn sorts_and_env(
+        &self,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        env: &Env,
+    ) -> (HashMap<LogIdx, SortSpec>, Env) {
+        // TODO build on change of spec
+        let cross_rem = &data.remap_specs[self.axis.cross_axis()];
+        let sort_dirs: HashMap<LogIdx, SortSpec> = cross_rem
+            .sort_by
+            .iter()
+            .enumerate()
+            .map(|(ord, x)| (LogIdx(x.idx), SortSpec::new(ord, x.direction)))
+            .collect();
+        let indicator = match &self.resolved_config {
+            Some(rtc) => rtc.sort_indicator_color.clone(),
+            None => self.config.sort_indicator_color.resolve(env),
+        };
+        let mut env = env
+            .clone()
+            .adding(SORTED_COLUMNS, sort_dirs.len() as u64)
+            .adding(SORT_INDICATOR_COLOR, indicator)
+            .adding(RIGHT_TO_LEFT, self.config.right_to_left);
+        if let (TableAxis::Columns, Some(rtc)) = (self.axis, &self.resolved_config) {
+            let line = rtc.col_header_height - 2. * rtc.cell_padding;
+            env.set(HEADER_LINE_HEIGHT, line);
+        }
+        (sort_dirs, env)
+    }
+
+    // How tall the column headings have to be for the tallest header, wrapped to its width.
+    // Only measured again once the headers or their widths, order or sorts change.
+    fn wrapped_height(
+        &mut self,
+        text: &mut PietText,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        env: &Env,
+    ) -> Option<f64> {
+        let (measure, remap) = (&data.measures[self.axis], &data.remaps[self.axis]);
+        let sorts = &data.remap_specs[self.axis.cross_axis()];
+        let unchanged = |w: &WrappedHeight| {
+            w.measure.same(measure) && w.remap.same(remap) && w.sorts.same(sorts)
+        };
+        if let Some(wrapped) = self.wrapped.as_ref().filter(|w| unchanged(w)) {
+            return Some(wrapped.height);
+        }
+        let height = self.measure_wrapped(text, data, env)?;
+        self.wrapped = Some(WrappedHeight {
+            measure: measure.clone(),
+            remap: remap.clone(),
+            sorts: sorts.clone(),
+            height,
+        });
+        Some(height)
+    }
+
+    fn measure_wrapped(
+        &self,
+        text: &mut PietText,
+        data: &TableState<<HeadersSource as HeadersFromData>::TableData>,
+        env: &Env,
+    ) -> Option<f64> {
+        let rtc = self.resolved_config.as_ref()?;
+        let headers = self.headers.as_ref()?;
+        let (sort_dirs, env) = self.sorts_and_env(data, env);
+        let measure = &data.measures[self.axis];
+        let remap = &data.remaps[self.axis];
+        let padding = 2. * rtc.cell_padding;
+        let line = rtc.col_header_height - padding;
+        let header_render = &self.header_render;
+
+        let tallest = (0..remap.vis_len(headers.idx_len()))
+            .map(VisIdx)
+            .filter_map(|vis_idx| {
+                let log_idx = remap.get_log_idx(vis_idx)?;
+                let width = measure.pixels_length_for_vis(vis_idx)? - padding;
+                let sort = sort_dirs.get(&log_idx);
+                let cell = CellCtx::Header(&self.axis, log_idx, sort, Size::new(width, line));
+                headers
+                    .with(log_idx, |header| {
+                        header_render.measure(text, TableAxis::Rows, &cell, header, &env)
+                    })
+                    .flatten()
+            })
+            .fold(line, f64::max);
+        Some(tallest + padding)
+    }
+
     fn paint_header(
         &mut self,
         ctx: &mut PaintCtx,
@@ -159,14 +676,16 @@ where
         let rtc = self.resolved_config.as_ref()?;
         let headers = self.headers.as_ref()?;
         let axis = self.axis;
+        let group_band = self.group_band();
         let header_render = &mut self.header_render;
+        let number_render = &mut self.number_render;
+        let cross = self
+            .cross_length
+            .unwrap_or_else(|| rtc.cross_axis_length(&axis));
 
         let cell_rect = Rect::from_origin_size(
-            axis.cell_origin(measure.first_pixel_from_vis(vis_main_idx)?, 0.),
-            axis.size(
-                measure.pixels_length_for_vis(vis_main_idx)?,
-                rtc.cross_axis_length(&axis),
-            ),
+            axis.cell_origin(measure.first_pixel_from_vis(vis_main_idx)?, group_band),
+            axis.size(measure.pixels_length_for_vis(vis_main_idx)?, cross),
         );
 
         if indices_selection.vis_index_selected(vis_main_idx) {
@@ -175,20 +694,36 @@ where
 
         let padded_rect = cell_rect.inset(-rtc.cell_padding);
         if let Some(log_main_idx) = data.remaps[self.axis].get_log_idx(vis_main_idx) {
-            let cell = CellCtx::Header(&axis, log_main_idx, sort_dirs.get(&log_main_idx));
+            let cell = CellCtx::Header(
+                &axis,
+                log_main_idx,
+                sort_dirs.get(&log_main_idx),
+                padded_rect.size(),
+            );
 
-            headers.with(log_main_idx, |col_name| {
-                ctx.with_save(|ctx| {
-                    let layout_origin = padded_rect.origin().to_vec2();
-                    ctx.clip(padded_rect);
-                    ctx.transform(Affine::translate(layout_origin));
-                    ctx.with_child_ctx(padded_rect, |ctxt| {
-                        header_render.paint(ctxt, &cell, col_name, env);
-                    });
+            let number = match axis {
+                TableAxis::Rows if self.config.row_numbers => {
+                    data.remaps[axis].row_number(vis_main_idx)
+                }
+                _ => None,
+            };
+
+            ctx.with_save(|ctx| {
+                let layout_origin = padded_rect.origin().to_vec2();
+                ctx.clip(padded_rect);
+                ctx.transform(Affine::translate(layout_origin));
+                ctx.with_child_ctx(padded_rect, |ctxt| match number {
+                    Some(number) => number_render.paint(ctxt, &cell, &number.to_string(), env),
+                    None => {
+                        headers.with(log_main_idx, |col_name| {
+                            header_render.paint(ctxt, &cell, col_name, env);
+                        });
+                    }
                 });
             });
 
-            ctx.stroke_bottom_left_border(&cell_rect, &rtc.cells_border, rtc.cell_border_thickness);
+            let thickness = rtc.cell_border_thickness;
+            ctx.stroke_bottom_left_border(&cell_rect, rtc.gridlines, &rtc.cells_border, thickness);
         }
         Some(())
     }
@@ -205,17 +740,52 @@ where
         ctx: &mut EventCtx,
         event: &Event,
         data: &mut TableState<HeadersSource::TableData>,
-        _env: &Env,
+        env: &Env,
     ) {
-        let measure = &mut data.measures[self.axis];
+        if self.rename_event(ctx, event, data, env) {
+            return;
+        }
+        self.track_tooltip(ctx, event, data, env);
         match event {
+            // A group heading selects all the columns in it
+            Event::MouseDown(me) if me.pos.y < self.group_band() => {
+                let pix_main = self.pixel_in_measure(data, &me.pos);
+                if let Some(vis_idx) = data.measures[self.axis].vis_idx_from_pixel(pix_main) {
+                    if let Some((first, last)) = self.group_extent(data, vis_idx) {
+                        let demap = MeasuredDemap::new(&data.remaps, &data.measures);
+                        let sel = &mut data.selection;
+                        sel.select_in_axis(self.axis, first, &demap);
+                        sel.extend_in_axis(self.axis, last, &demap);
+                    }
+                }
+                ctx.set_handled()
+            }
             Event::MouseDown(me) => {
-                let pix_main = self.axis.main_pixel_from_point(&me.pos);
-                if me.count == 2 {
-                    let extend = me.mods.ctrl() || me.mods.meta();
+                let pix_main = self.pixel_in_measure(data, &me.pos);
+                let measure = &mut data.measures[self.axis];
+                let fit_border = measure.pixel_near_border(pix_main).filter(|idx| {
+                    self.axis == TableAxis::Columns
+                        && *idx > VisIdx(0)
+                        && measure.can_resize(*idx - VisOffset(1))
+                });
+                if let (2, Some(idx)) = (me.count, fit_border) {
+                    // Fit the column left of the border to its content
+                    ctx.submit_command(AUTO_SIZE_COLUMN.with(idx - VisOffset(1)));
+                    ctx.set_handled()
+                } else if me.count == 2 && self.renames() {
+                    let vis_idx = measure.vis_idx_from_pixel(pix_main);
+                    let log_idx = vis_idx.and_then(|vis| data.remaps[self.axis].get_log_idx(vis));
+                    if let Some(log_idx) = log_idx {
+                        self.start_renaming(ctx, log_idx, env);
+                        ctx.set_handled()
+                    }
+                } else if me.count == 2 {
+                    let extend = me.mods.shift() || me.mods.ctrl() || me.mods.meta();
                     if let Some(vis_idx) = measure.vis_idx_from_pixel(pix_main) {
                         if let Some(log_idx) = data.remaps[self.axis].get_log_idx(vis_idx) {
-                            data.remap_specs[self.axis.cross_axis()].toggle_sort(log_idx, extend);
+                            let fixed = &self.config.fixed_sorts;
+                            let spec = &mut data.remap_specs[self.axis.cross_axis()];
+                            spec.toggle_sort(log_idx, extend, fixed);
                         }
                         ctx.set_handled()
                     }
@@ -230,16 +800,19 @@ where
                         }
                     } else if let Some(idx) = measure.vis_idx_from_pixel(pix_main) {
                         let sel = &mut data.selection;
-                        // Already selected so move headings:
-                        if sel.fully_selects_heading(self.axis, idx) {
-                            self.header_movement = HeaderMovement::Moving(idx);
+                        // Already selected so move headings, unless Shift extends instead:
+                        let movable = !matches!(self.header_movement, Disallowed);
+                        if movable && !me.mods.shift() && sel.fully_selects_heading(self.axis, idx)
+                        {
+                            self.header_movement = HeaderMovement::Moving(idx, idx);
                             ctx.set_active(true);
                         } else {
                             // Change the selection
+                            let demap = MeasuredDemap::new(&data.remaps, &data.measures);
                             if me.mods.shift() {
-                                sel.extend_in_axis(self.axis, idx, &data.remaps);
+                                sel.extend_in_axis(self.axis, idx, &demap);
                             } else {
-                                sel.select_in_axis(self.axis, idx, &data.remaps);
+                                sel.select_in_axis(self.axis, idx, &demap);
                             }
                             self.selection_dragging = true;
                             ctx.set_active(true);
@@ -249,9 +822,10 @@ where
                 }
             }
             Event::MouseMove(me) => {
-                let pix_main = self.axis.main_pixel_from_point(&me.pos);
+                let pix_main = self.pixel_in_measure(data, &me.pos);
+                let measure = &mut data.measures[self.axis];
                 if let Some(idx) = self.resize_dragging {
-                    self.set_pix_length_for_axis(measure, ctx, idx, pix_main);
+                    self.set_pix_length_for_axis(data, ctx, idx, pix_main);
 
                     if me.buttons.is_empty() {
                         self.resize_dragging = None;
@@ -259,37 +833,42 @@ where
                         ctx.set_cursor(self.axis.resize_cursor());
                     }
                     ctx.set_handled()
-                } else if let HeaderMovement::Moving(_idx) = self.header_movement {
-                    // Show visual indicator
-                    if let Some(_idx) = measure.vis_idx_from_pixel(pix_main) {}
+                } else if let HeaderMovement::Moving(from, _) = self.header_movement {
+                    if let Some(idx) = measure.vis_idx_from_pixel(pix_main) {
+                        self.header_movement = HeaderMovement::Moving(from, idx);
+                        ctx.request_paint();
+                    }
                     ctx.set_handled()
                 } else if self.selection_dragging {
                     if let Some(idx) = measure.vis_idx_from_pixel(pix_main) {
-                        data.selection.extend_in_axis(self.axis, idx, &data.remaps);
+                        let demap = MeasuredDemap::new(&data.remaps, &data.measures);
+                        data.selection.extend_in_axis(self.axis, idx, &demap);
                     }
                 } else if let Some(idx) = measure.pixel_near_border(pix_main) {
-                    if idx > VisIdx(0) {
-                        let cursor = if measure.can_resize(idx - VisOffset(1)) {
-                            self.axis.resize_cursor()
-                        } else {
-                            &Cursor::NotAllowed
-                        };
-                        ctx.set_cursor(cursor);
+                    // Fixed measures can't be resized, so don't offer to
+                    if idx > VisIdx(0) && measure.can_resize(idx - VisOffset(1)) {
+                        ctx.set_cursor(self.axis.resize_cursor());
                         ctx.set_handled();
                     }
                 } // TODO grabber for when header can move (ie selected)
             }
             Event::MouseUp(me) => {
-                let pix_main = self.axis.main_pixel_from_point(&me.pos);
+                let pix_main = self.pixel_in_measure(data, &me.pos);
+                let measure = &mut data.measures[self.axis];
                 if let Some(idx) = self.resize_dragging {
-                    self.set_pix_length_for_axis(measure, ctx, idx, pix_main);
+                    self.set_pix_length_for_axis(data, ctx, idx, pix_main);
                     self.resize_dragging = None;
                     ctx.set_active(false);
                     ctx.set_handled();
-                } else if let HeaderMovement::Moving(_moved_idx) = self.header_movement {
-                    if let Some(moved_to_idx) = measure.vis_idx_from_pixel(pix_main) {
-                        data.explicit_header_move(self.axis, moved_to_idx)
+                } else if let HeaderMovement::Moving(moved_idx, _) = self.header_movement {
+                    match measure.vis_idx_from_pixel(pix_main) {
+                        Some(moved_to_idx) if moved_to_idx != moved_idx => {
+                            data.explicit_header_move(self.axis, moved_to_idx)
+                        }
+                        _ => (),
                     }
+                    self.header_movement = Permitted;
+                    ctx.request_paint();
                     ctx.set_active(false);
                     ctx.set_handled()
                 } else if self.selection_dragging {
@@ -304,15 +883,32 @@ where
 
     fn lifecycle(
         &mut self,
-        _ctx: &mut LifeCycleCtx,
+        ctx: &mut LifeCycleCtx,
         event: &LifeCycle,
         data: &TableState<HeadersSource::TableData>,
         env: &Env,
     ) {
-        if let LifeCycle::WidgetAdded = event {
-            let rtc = self.config.resolve(env);
-            self.headers = Some(self.headers_source.get_headers(&data.data)); // TODO Option
-            self.resolved_config = Some(rtc);
+        if let Some(renaming) = &mut self.renaming {
+            renaming.editor.lifecycle(ctx, event, &renaming.text, env);
+            // Clicking on the cells takes the focus, which keeps the name too
+            if let LifeCycle::Internal(InternalLifeCycle::RouteFocusChanged { old, .. }) = event {
+                if *old == Some(renaming.editor.id()) {
+                    ctx.submit_command(Command::new(FINISH_RENAMING, (), ctx.widget_id()));
+                }
+            }
+        }
+        match event {
+            LifeCycle::WidgetAdded => {
+                let rtc = self.config.resolve(env);
+                self.headers = Some(self.headers_source.get_headers(&data.data)); // TODO Option
+                self.resolved_config = Some(rtc);
+            }
+            LifeCycle::HotChanged(false) => {
+                if let Some(window) = self.tooltip.dismiss() {
+                    ctx.submit_command(CLOSE_WINDOW.to(window));
+                }
+            }
+            _ => (),
         }
     }
 
@@ -321,8 +917,19 @@ where
         ctx: &mut UpdateCtx,
         old_data: &TableState<HeadersSource::TableData>,
         data: &TableState<HeadersSource::TableData>,
-        _env: &Env,
+        env: &Env,
     ) {
+        if let Some(renaming) = &mut self.renaming {
+            renaming.editor.update(ctx, &renaming.text, env);
+        }
+        if ctx.env_changed() {
+            self.resolved_config = Some(self.config.resolve(env));
+            self.wrapped = None;
+            ctx.request_layout();
+        }
+        if !old_data.data.same(&data.data) {
+            self.wrapped = None;
+        }
         if !old_data.same(data) {
             self.headers = Some(self.headers_source.get_headers(&data.data));
             ctx.request_layout(); // TODO Only relayout if actually changed
@@ -331,24 +938,47 @@ where
 
     fn layout(
         &mut self,
-        _ctx: &mut LayoutCtx,
+        ctx: &mut LayoutCtx,
         bc: &BoxConstraints,
         data: &TableState<HeadersSource::TableData>,
-        _env: &Env,
+        env: &Env,
     ) -> Size {
         bc.debug_check("ColumnHeadings");
-        let cross_axis_length = if let Some(rc) = &self.resolved_config {
-            match self.axis {
-                TableAxis::Columns => rc.col_header_height,
-                TableAxis::Rows => rc.row_header_width,
+        let header_height = self.resolved_config.as_ref().map(|rc| rc.col_header_height);
+        let cross_axis_length = match (self.axis, header_height) {
+            (_, None) => self.axis.default_header_cross(),
+            (TableAxis::Columns, Some(height)) if self.config.wrap_headers => {
+                self.wrapped_height(ctx.text(), data, env).unwrap_or(height)
+            }
+            (TableAxis::Columns, Some(height)) => height,
+            (TableAxis::Rows, Some(_)) => {
+                let rows = self.headers.as_ref().map_or(0, |h| h.idx_len());
+                row_headings_width(ctx.text(), &self.config, rows, env)
             }
-        } else {
-            self.axis.default_header_cross()
         };
+        self.cross_length = Some(cross_axis_length);
+
+        // The text box covers the heading, following it when frozen
+        let (band, frozen) = (self.group_band(), self.frozen_count());
+        if let Some(renaming) = &mut self.renaming {
+            let measure = &data.measures[self.axis];
+            let vis = data.remaps[self.axis].get_vis_idx(renaming.idx);
+            let rect = vis.and_then(|vis| {
+                let x = measure.first_pixel_from_vis(vis)?;
+                let width = measure.pixels_length_for_vis(vis)?;
+                let x = x + data.frozen_shift(self.axis, vis, frozen);
+                Some(Rect::new(x, band, x + width, band + cross_axis_length))
+            });
+            if let Some(rect) = rect {
+                let bc = BoxConstraints::tight(rect.size());
+                renaming.editor.layout(ctx, &bc, &renaming.text, env);
+                renaming.editor.set_origin(ctx, rect.origin());
+            }
+        }
 
         bc.constrain(self.axis.size(
             data.measures[self.axis].total_pixel_length(),
-            cross_axis_length,
+            cross_axis_length + self.group_band(),
         ))
     }
 
@@ -361,23 +991,27 @@ where
         let measure = &data.measures[self.axis];
         let indices_selection = data.selection.to_axis_selection(self.axis, &data.remaps);
 
-        // TODO build on change of spec
-        let cross_rem = &data.remap_specs[self.axis.cross_axis()];
-        let sort_dirs: HashMap<LogIdx, SortSpec> = cross_rem
-            .sort_by
-            .iter()
-            .enumerate()
-            .map(|(ord, x)| (LogIdx(x.idx), SortSpec::new(ord, x.direction)))
-            .collect();
+        let (sort_dirs, env) = self.sorts_and_env(data, env);
+        let env = &env;
 
         if let Some(rtc) = &self.resolved_config {
             self.header_render.init(ctx, env);
+            self.group_render.init(ctx, env);
+            self.number_render.init(ctx, env);
             let rect = ctx.region().bounding_box();
 
             ctx.fill(rect, &rtc.header_background);
 
             let (p0, p1) = self.axis.pixels_from_rect(&rect);
             let (start_main, end_main) = measure.vis_range_from_pixels(p0, p1);
+            let group_band = self.group_band();
+            let cross = self
+                .cross_length
+                .unwrap_or_else(|| rtc.cross_axis_length(&self.axis));
+            let cross = cross + group_band;
+            let header_background = rtc.header_background.clone();
+            let divider = rtc.frozen_divider.clone();
+            let thickness = (rtc.cell_border_thickness * 2.).max(1.);
 
             for vis_main_idx in VisIdx::range_inc_iter(start_main, end_main) {
                 // TODO: excessive unwrapping
@@ -391,6 +1025,42 @@ where
                     vis_main_idx,
                 );
             }
+            if group_band > 0. {
+                self.paint_groups(ctx, data, env, start_main, end_main);
+            }
+
+            let frozen = self.frozen_count();
+            if let Some((shift, width)) = data.frozen_band(self.axis, frozen) {
+                if shift > 0. {
+                    let band = Rect::from_origin_size(Point::ZERO, self.axis.size(width, cross));
+                    ctx.with_save(|ctx| {
+                        ctx.transform(Affine::translate(self.axis.coords(shift, 0.)));
+                        ctx.clip(band);
+                        ctx.fill(band, &header_background);
+                        for vis_main_idx in VisIdx::range_inc_iter(VisIdx(0), VisIdx(frozen - 1)) {
+                            self.paint_header(
+                                ctx,
+                                data,
+                                env,
+                                measure,
+                                &indices_selection,
+                                &sort_dirs,
+                                vis_main_idx,
+                            );
+                        }
+                        if group_band > 0. {
+                            self.paint_groups(ctx, data, env, VisIdx(0), VisIdx(frozen - 1));
+                        }
+                    });
+                }
+                let far = shift + width;
+                let line = Line::new(self.axis.coords(far, 0.), self.axis.coords(far, cross));
+                ctx.stroke(line, &divider, thickness);
+            }
+            self.paint_drop_target(ctx, data, rtc, cross);
+        }
+        if let Some(renaming) = &mut self.renaming {
+            renaming.editor.paint(ctx, &renaming.text, env);
         }
     }
 }
@@ -402,3 +1072,25 @@ where
 {
     bindable_self_body!();
 }
+
+#[cfg(test)]
+mod test {
+    use crate::axis_measure::LogIdx;
+    use crate::headings::{HeadersFromData, SuppliedHeaders};
+    use druid::im::Vector;
+    use druid::ArcStr;
+
+    #[test]
+    fn renaming_goes_through_the_hook() {
+        let names: Vec<ArcStr> = vec!["Name".into(), "Age".into()];
+        let mut fixed = SuppliedHeaders::<_, Vector<String>>::new(names.clone());
+        assert!(!fixed.rename(LogIdx(0), "Full name"));
+
+        let mut headers = SuppliedHeaders::<_, Vector<String>>::new(names).renamable();
+        assert!(headers.rename(LogIdx(0), "Full name"));
+        assert!(!headers.rename(LogIdx(2), "Missing"));
+        let renamed = headers.get_headers(&Vector::new());
+        assert_eq!(&*renamed[0], "Full name");
+        assert_eq!(&*renamed[1], "Age");
+    }
+}
@@ -6,27 +6,41 @@ mod builder;
 mod cells;
 mod columns;
 mod config;
+mod corner;
 mod data;
+mod footer;
 mod headings;
 mod interp;
 pub mod numbers_table;
 mod render_ext;
 mod selection;
 mod table;
+mod tooltip;
 
 pub use axis_measure::{
     AxisMeasure, AxisPair, FixedAxisMeasure, LogIdx, StoredAxisMeasure, TableAxis, VisIdx,
+    VisIdxIter,
 };
 pub use builder::{AxisMeasurementType, DefaultTableArgs, ShowHeadings, TableBuilder};
-pub use cells::{Cells, CellsDelegate};
+pub use cells::{
+    Cells, CellsDelegate, CsvExport, SelectCell, APPLY_LAYOUT, AUTO_SIZE_COLUMN, CELL_GEOMETRY,
+    CLEAR_ROW_FILTERS, ENSURE_VISIBLE, EXPORT_CSV, EXPORT_LAYOUT, FIND_NEXT, FIND_PREV,
+    GROUP_ROWS_BY, HIDE_COLUMN, RENAME_COLUMN, SCROLL_TO_BOTTOM, SCROLL_TO_ROW, SELECT_ALL,
+    SELECT_CELL, SET_ROW_FILTER, SET_SEARCH, SHOW_COLUMN, VISIBLE_ROWS,
+};
 pub use columns::{
-    column, CellCtx, CellRender, CellRenderExt, DataCompare, EditorFactory, TextCell,
+    aggregate_column, column, Aggregate, BgWrapped, CellCtx, CellRender, CellRenderExt, CellStyle,
+    CheckboxCell, ChoiceCell, CompareWrapped, DataCompare, EditorFactory, LoadingWrapped,
+    NoneOrder, NumberCell, OptionalWrapped, SeriesValue, SparklineCell, TextCell, TextColorWrapped,
+    TextOverflow,
+};
+pub use config::{ContextMenuFn, Gridlines, TableConfig};
+pub use data::{
+    FilterSpec, IndexedData, IndexedItems, Remap, RemapSpec, Remapper, SearchSpec, SortDirection,
 };
-pub use config::TableConfig;
-pub use data::{IndexedData, IndexedItems, Remap, RemapSpec, Remapper, SortDirection};
 pub use headings::{HeadersFromIndices, Headings, SuppliedHeaders};
-pub use selection::{IndicesSelection, TableSelection};
-pub use table::{HeaderBuild, Table, TableArgs};
+pub use selection::{IndicesSelection, SelectionMode, TableSelection};
+pub use table::{HeaderBuild, Table, TableArgs, TableGeometry, TableLayout};
 
 #[macro_use]
 extern crate druid;
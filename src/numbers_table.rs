@@ -1,6 +1,16 @@
-use crate::axis_measure::LogIdx;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::axis_measure::{LogIdx, TableAxis};
+use crate::columns::{CellCtx, CellRender, DataCompare, EditorFactory, TextCell};
 use crate::IndexedItems;
-use druid::{Data, Lens};
+use druid::im::Vector;
+use druid::piet::{PietText, TextAlignment};
+use druid::widget::TextBox;
+use druid::{lens, Data, Env, Lens, PaintCtx, Widget, WidgetExt};
 
 #[derive(Debug, Data, Clone, Lens)]
 pub struct LogIdxTable {
@@ -32,3 +42,608 @@ impl IndexedItems for LogIdxTable {
         self.rows
     }
 }
+
+// A grid of cells like a spreadsheet's. Each input is a number, or a formula starting with '='
+// that refers to other cells as R<row>C<col> (logical indices from 0), eg "=R0C1 * 2" or
+// "=SUM(R0C0:R0C2)". When a row is changed, the cells whose inputs changed are worked out
+// again along with the cells that depend on them.
+#[derive(Debug, Data, Clone)]
+pub struct FormulaTable {
+    rows: Vector<FormulaRow>,
+    // The formula cells referring to each cell, from the inputs
+    #[data(ignore)]
+    dependents: im::HashMap<(usize, usize), im::HashSet<(usize, usize)>>,
+}
+
+#[derive(Debug, Data, Clone, Lens)]
+pub struct FormulaRow {
+    pub inputs: Vector<String>,
+    values: Vector<FormulaValue>,
+}
+
+impl FormulaRow {
+    pub fn value(&self, col: usize) -> FormulaValue {
+        self.values.get(col).cloned().unwrap_or(FormulaValue::Blank)
+    }
+}
+
+#[derive(Debug, Data, Clone, PartialEq)]
+pub enum FormulaValue {
+    Blank,
+    Number(f64),
+    Error(FormulaError),
+}
+
+#[derive(Debug, Data, Clone, Copy, PartialEq, Eq)]
+pub enum FormulaError {
+    Value, // Not a number or a formula we understand
+    Ref,   // Refers to a cell outside the table
+    DivZero,
+    Cycle, // Depends on itself
+}
+
+impl FormulaValue {
+    pub fn number(&self) -> Option<f64> {
+        match self {
+            FormulaValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl Display for FormulaValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FormulaValue::Blank => Ok(()),
+            FormulaValue::Number(n) => write!(f, "{}", n),
+            FormulaValue::Error(FormulaError::Value) => f.write_str("#VALUE!"),
+            FormulaValue::Error(FormulaError::Ref) => f.write_str("#REF!"),
+            FormulaValue::Error(FormulaError::DivZero) => f.write_str("#DIV/0!"),
+            FormulaValue::Error(FormulaError::Cycle) => f.write_str("#CYCLE!"),
+        }
+    }
+}
+
+impl FormulaTable {
+    pub fn new(inputs: Vec<Vec<String>>) -> Self {
+        let rows = inputs
+            .into_iter()
+            .map(|inputs| FormulaRow {
+                inputs: inputs.into_iter().collect(),
+                values: Vector::new(),
+            })
+            .collect();
+        let mut table = FormulaTable {
+            rows,
+            dependents: im::HashMap::new(),
+        };
+        for (row, cells) in table.rows.clone().iter().enumerate() {
+            for (col, input) in cells.inputs.iter().enumerate() {
+                table.depend((row, col), "", input);
+            }
+        }
+        table.evaluate(None);
+        table
+    }
+
+    pub fn set_input(&mut self, row: usize, col: usize, input: impl Into<String>) {
+        let input = input.into();
+        self.with_mut(LogIdx(row), |row| {
+            if let Some(cell) = row.inputs.get_mut(col) {
+                *cell = input;
+            }
+        });
+    }
+
+    // Moves a cell's references from those of its old input to its new one
+    fn depend(&mut self, cell: (usize, usize), old: &str, new: &str) {
+        for source in references(old) {
+            if let Some(dependents) = self.dependents.get_mut(&source) {
+                dependents.remove(&cell);
+            }
+        }
+        for source in references(new) {
+            self.dependents.entry(source).or_default().insert(cell);
+        }
+    }
+
+    // The cells, and every cell that depends on them however indirectly
+    fn with_dependents(&self, cells: Vec<(usize, usize)>) -> HashSet<(usize, usize)> {
+        let mut found: HashSet<(usize, usize)> = cells.iter().copied().collect();
+        let mut next = cells;
+        while let Some(cell) = next.pop() {
+            for dependent in self.dependents.get(&cell).into_iter().flatten() {
+                if found.insert(*dependent) {
+                    next.push(*dependent);
+                }
+            }
+        }
+        found
+    }
+
+    // Works out the stale cells again, or all of them for None. The rest keep their values.
+    fn evaluate(&mut self, stale: Option<&HashSet<(usize, usize)>>) {
+        let mut evaluation = Evaluation {
+            rows: &self.rows,
+            values: HashMap::new(),
+            stale,
+        };
+        let cells: Vec<(usize, usize)> = match stale {
+            Some(stale) => stale.iter().copied().collect(),
+            None => self
+                .rows
+                .iter()
+                .enumerate()
+                .flat_map(|(row, cells)| (0..cells.inputs.len()).map(move |col| (row, col)))
+                .collect(),
+        };
+        let values: Vec<_> = cells
+            .into_iter()
+            .map(|(row, col)| (row, col, evaluation.value(row, col)))
+            .collect();
+        for (row, col, value) in values {
+            if let Some(row) = self.rows.get_mut(row).filter(|row| col < row.inputs.len()) {
+                while row.values.len() <= col {
+                    row.values.push_back(FormulaValue::Blank);
+                }
+                row.values[col] = value;
+            }
+        }
+    }
+}
+
+// The cells an input refers to, none unless it is a formula that parses
+fn references(input: &str) -> Vec<(usize, usize)> {
+    let mut refs = Vec::new();
+    let formula = input.trim().strip_prefix('=');
+    if let Some(expr) = formula.and_then(|formula| Parser::parse(formula).ok()) {
+        expr.references(&mut refs);
+    }
+    refs
+}
+
+impl IndexedItems for FormulaTable {
+    type Item = FormulaRow;
+    type Idx = LogIdx;
+
+    fn with<V>(&self, idx: LogIdx, f: impl FnOnce(&FormulaRow) -> V) -> Option<V> {
+        self.rows.get(idx.0).map(f)
+    }
+
+    fn with_mut<V>(&mut self, idx: LogIdx, f: impl FnOnce(&mut FormulaRow) -> V) -> Option<V> {
+        let before = self.rows.get(idx.0)?.inputs.clone();
+        let res = self.rows.get_mut(idx.0).map(f)?;
+        let row = &mut self.rows[idx.0];
+        row.values.truncate(row.inputs.len());
+        let after = row.inputs.clone();
+        let cols = before.len().max(after.len());
+        let changed: Vec<(usize, usize)> = (0..cols)
+            .filter(|col| before.get(*col) != after.get(*col))
+            .map(|col| (idx.0, col))
+            .collect();
+        if !changed.is_empty() {
+            for &(row, col) in &changed {
+                let input = |inputs: &Vector<String>| inputs.get(col).cloned().unwrap_or_default();
+                self.depend((row, col), &input(&before), &input(&after));
+            }
+            let stale = self.with_dependents(changed);
+            self.evaluate(Some(&stale));
+        }
+        Some(res)
+    }
+
+    fn idx_len(&self) -> usize {
+        self.rows.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Num(f64),
+    Ref(usize, usize),
+    Sum,
+    Op(char),
+    Open,
+    Close,
+    Colon,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Num(f64),
+    Ref(usize, usize),
+    Sum((usize, usize), (usize, usize)),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn references(&self, refs: &mut Vec<(usize, usize)>) {
+        match self {
+            Expr::Num(_) => (),
+            Expr::Ref(row, col) => refs.push((*row, *col)),
+            Expr::Sum((r0, c0), (r1, c1)) => {
+                for row in *r0.min(r1)..=*r0.max(r1) {
+                    refs.extend((*c0.min(c1)..=*c0.max(c1)).map(|col| (row, col)));
+                }
+            }
+            Expr::Neg(expr) => expr.references(refs),
+            Expr::Binary(_, a, b) => {
+                a.references(refs);
+                b.references(refs);
+            }
+        }
+    }
+}
+
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+        digits.push(*c);
+        chars.next();
+    }
+    digits
+}
+
+fn tokenize(formula: &str) -> Result<Vec<Token>, FormulaError> {
+    let mut tokens = Vec::new();
+    let mut chars = formula.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let num = digits(&mut chars)
+                    .parse()
+                    .map_err(|_| FormulaError::Value)?;
+                tokens.push(Token::Num(num))
+            }
+            '+' | '-' | '*' | '/' | '(' | ')' | ':' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    ':' => Token::Colon,
+                    op => Token::Op(op),
+                })
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut word = String::new();
+                while let Some(c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                    word.push(c.to_ascii_uppercase());
+                    chars.next();
+                }
+                tokens.push(match word.as_str() {
+                    "SUM" => Token::Sum,
+                    "R" => {
+                        let row = digits(&mut chars).parse().map_err(|_| FormulaError::Ref)?;
+                        if !matches!(chars.next(), Some('C') | Some('c')) {
+                            return Err(FormulaError::Ref);
+                        }
+                        let col = digits(&mut chars).parse().map_err(|_| FormulaError::Ref)?;
+                        Token::Ref(row, col)
+                    }
+                    _ => return Err(FormulaError::Value),
+                })
+            }
+            _ => return Err(FormulaError::Value),
+        }
+    }
+    Ok(tokens)
+}
+
+// Recursive descent, with * and / binding tighter than + and -
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn parse(formula: &str) -> Result<Expr, FormulaError> {
+        let mut parser = Parser {
+            tokens: tokenize(formula)?,
+            pos: 0,
+        };
+        let expr = parser.sum()?;
+        match parser.next() {
+            None => Ok(expr),
+            Some(_) => Err(FormulaError::Value),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).copied();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_op(&self, ops: &[char]) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) if ops.contains(op) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), FormulaError> {
+        match self.next() {
+            Some(t) if t == token => Ok(()),
+            _ => Err(FormulaError::Value),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, FormulaError> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.peek_op(&['+', '-']) {
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, FormulaError> {
+        let mut expr = self.factor()?;
+        while let Some(op) = self.peek_op(&['*', '/']) {
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.factor()?));
+        }
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result<Expr, FormulaError> {
+        match self.next() {
+            Some(Token::Num(num)) => Ok(Expr::Num(num)),
+            Some(Token::Ref(row, col)) => Ok(Expr::Ref(row, col)),
+            Some(Token::Op('-')) => Ok(Expr::Neg(Box::new(self.factor()?))),
+            Some(Token::Open) => {
+                let expr = self.sum()?;
+                self.expect(Token::Close)?;
+                Ok(expr)
+            }
+            Some(Token::Sum) => {
+                self.expect(Token::Open)?;
+                let from = self.cell_ref()?;
+                self.expect(Token::Colon)?;
+                let to = self.cell_ref()?;
+                self.expect(Token::Close)?;
+                Ok(Expr::Sum(from, to))
+            }
+            _ => Err(FormulaError::Value),
+        }
+    }
+
+    fn cell_ref(&mut self) -> Result<(usize, usize), FormulaError> {
+        match self.next() {
+            Some(Token::Ref(row, col)) => Ok((row, col)),
+            _ => Err(FormulaError::Ref),
+        }
+    }
+}
+
+// Values worked out so far. None marks a cell still being worked out, so reaching it again
+// means a cycle. Cells that aren't stale keep the value they have.
+struct Evaluation<'a> {
+    rows: &'a Vector<FormulaRow>,
+    values: HashMap<(usize, usize), Option<FormulaValue>>,
+    stale: Option<&'a HashSet<(usize, usize)>>,
+}
+
+impl Evaluation<'_> {
+    fn value(&mut self, row: usize, col: usize) -> FormulaValue {
+        match self.values.get(&(row, col)) {
+            Some(Some(value)) => return value.clone(),
+            Some(None) => return FormulaValue::Error(FormulaError::Cycle),
+            None => (),
+        }
+        let rows = self.rows;
+        let kept = matches!(self.stale, Some(stale) if !stale.contains(&(row, col)));
+        if kept {
+            if let Some(value) = rows.get(row).and_then(|r| r.values.get(col)) {
+                return value.clone();
+            }
+        }
+        let input = match rows.get(row).and_then(|r| r.inputs.get(col)) {
+            Some(input) => input.trim(),
+            None => return FormulaValue::Error(FormulaError::Ref),
+        };
+        self.values.insert((row, col), None);
+        let value = if input.is_empty() {
+            FormulaValue::Blank
+        } else {
+            let result = match input.strip_prefix('=') {
+                Some(formula) => Parser::parse(formula).and_then(|expr| self.eval(&expr)),
+                None => input.parse().map_err(|_| FormulaError::Value),
+            };
+            result.map_or_else(FormulaValue::Error, FormulaValue::Number)
+        };
+        self.values.insert((row, col), Some(value.clone()));
+        value
+    }
+
+    fn number(&mut self, row: usize, col: usize) -> Result<f64, FormulaError> {
+        match self.value(row, col) {
+            FormulaValue::Blank => Ok(0.),
+            FormulaValue::Number(num) => Ok(num),
+            FormulaValue::Error(err) => Err(err),
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<f64, FormulaError> {
+        Ok(match expr {
+            Expr::Num(num) => *num,
+            Expr::Ref(row, col) => self.number(*row, *col)?,
+            Expr::Sum((r0, c0), (r1, c1)) => {
+                let mut total = 0.;
+                for row in *r0.min(r1)..=*r0.max(r1) {
+                    for col in *c0.min(c1)..=*c0.max(c1) {
+                        total += self.number(row, col)?;
+                    }
+                }
+                total
+            }
+            Expr::Neg(expr) => -self.eval(expr)?,
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (self.eval(a)?, self.eval(b)?);
+                match op {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    _ if b == 0. => return Err(FormulaError::DivZero),
+                    _ => a / b,
+                }
+            }
+        })
+    }
+}
+
+// Shows a column of a FormulaTable's values, and edits its inputs
+pub struct FormulaCell {
+    col: usize,
+    text: TextCell,
+}
+
+impl FormulaCell {
+    pub fn new(col: usize) -> Self {
+        FormulaCell {
+            col,
+            text: TextCell::new().text_alignment(TextAlignment::End),
+        }
+    }
+}
+
+impl CellRender<FormulaRow> for FormulaCell {
+    fn init(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        self.text.init(ctx, env)
+    }
+
+    fn paint(&self, ctx: &mut PaintCtx, cell: &CellCtx, data: &FormulaRow, env: &Env) {
+        let value = data.value(self.col).to_string();
+        self.text.paint(ctx, cell, &value, env)
+    }
+
+    fn measure(
+        &self,
+        text: &mut PietText,
+        axis: TableAxis,
+        cell: &CellCtx,
+        data: &FormulaRow,
+        env: &Env,
+    ) -> Option<f64> {
+        let value = data.value(self.col).to_string();
+        self.text.measure(text, axis, cell, &value, env)
+    }
+
+    fn as_text(&self, _cell: &CellCtx, data: &FormulaRow, _env: &Env) -> Option<String> {
+        Some(data.value(self.col).to_string())
+    }
+
+    fn as_number(&self, _cell: &CellCtx, data: &FormulaRow, _env: &Env) -> Option<f64> {
+        data.value(self.col).number()
+    }
+}
+
+impl DataCompare<FormulaRow> for FormulaCell {
+    fn compare(&self, a: &FormulaRow, b: &FormulaRow) -> Ordering {
+        let (a, b) = (a.value(self.col).number(), b.value(self.col).number());
+        a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl EditorFactory<FormulaRow> for FormulaCell {
+    fn make_editor(&mut self, _ctx: &CellCtx) -> Option<Box<dyn Widget<FormulaRow>>> {
+        let col = self.col;
+        let input = lens::Map::new(
+            move |row: &FormulaRow| row.inputs.get(col).cloned().unwrap_or_default(),
+            move |row: &mut FormulaRow, input: String| {
+                if let Some(cell) = row.inputs.get_mut(col) {
+                    *cell = input;
+                }
+            },
+        );
+        Some(Box::new(TextBox::new().expand_height().lens(input)))
+    }
+
+    fn can_edit(&self, _ctx: &CellCtx) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::numbers_table::{FormulaError, FormulaTable, FormulaValue};
+    use crate::{IndexedItems, LogIdx};
+
+    fn table(rows: &[&[&str]]) -> FormulaTable {
+        FormulaTable::new(
+            rows.iter()
+                .map(|row| row.iter().map(|input| input.to_string()).collect())
+                .collect(),
+        )
+    }
+
+    fn value(table: &FormulaTable, row: usize, col: usize) -> FormulaValue {
+        table.with(LogIdx(row), |r| r.value(col)).expect("row")
+    }
+
+    #[test]
+    fn totals_follow_their_sources() {
+        let mut sheet = table(&[
+            &["1", "2", "3", "=SUM(R0C0:R0C2)"],
+            &["", "=R0C3 * 2 - 1", "", ""],
+        ]);
+        assert_eq!(value(&sheet, 0, 3), FormulaValue::Number(6.));
+        assert_eq!(value(&sheet, 1, 1), FormulaValue::Number(11.));
+
+        sheet.set_input(0, 1, "10");
+        assert_eq!(value(&sheet, 0, 3), FormulaValue::Number(14.));
+        assert_eq!(value(&sheet, 1, 1), FormulaValue::Number(27.));
+    }
+
+    #[test]
+    fn edits_reach_dependents_through_changed_formulas() {
+        let mut sheet = table(&[&["1", "=R0C0 + 1", "5", "=R0C3"]]);
+        assert_eq!(
+            value(&sheet, 0, 3),
+            FormulaValue::Error(FormulaError::Cycle)
+        );
+
+        // Pointed at the third cell, the second stops following the first
+        sheet.set_input(0, 1, "=R0C2 * 2");
+        sheet.set_input(0, 0, "7");
+        assert_eq!(value(&sheet, 0, 1), FormulaValue::Number(10.));
+        sheet.set_input(0, 2, "6");
+        assert_eq!(value(&sheet, 0, 1), FormulaValue::Number(12.));
+
+        // Breaking a cycle works out the cell again
+        sheet.set_input(0, 3, "=R0C1 - 2");
+        assert_eq!(value(&sheet, 0, 3), FormulaValue::Number(10.));
+    }
+
+    #[test]
+    fn bad_formulas_show_errors() {
+        let sheet = table(&[&[
+            "=R0C1",
+            "=R0C0 + 1",
+            "=1 / 0",
+            "=R5C0",
+            "=2 +",
+            "=(1 + 2) * -3",
+        ]]);
+        let cycle = FormulaValue::Error(FormulaError::Cycle);
+        assert_eq!(
+            (value(&sheet, 0, 0), value(&sheet, 0, 1)),
+            (cycle.clone(), cycle)
+        );
+        assert_eq!(
+            value(&sheet, 0, 2),
+            FormulaValue::Error(FormulaError::DivZero)
+        );
+        assert_eq!(value(&sheet, 0, 3), FormulaValue::Error(FormulaError::Ref));
+        assert_eq!(
+            value(&sheet, 0, 4),
+            FormulaValue::Error(FormulaError::Value)
+        );
+        assert_eq!(value(&sheet, 0, 5), FormulaValue::Number(-9.));
+        assert_eq!(value(&sheet, 0, 0).to_string(), "#CYCLE!");
+    }
+}
@@ -1,9 +1,10 @@
-use crate::axis_measure::{AxisPair, LogIdx, TableAxis, VisIdx, VisOffset};
+use crate::axis_measure::{AxisPair, LogIdx, TableAxis, VisIdx, VisIdxIter, VisOffset};
 use crate::AxisMeasure;
+use druid::im::Vector;
 use druid::kurbo::{Point, Rect, Size};
 use std::fmt::Debug;
-use std::iter::Map;
-use std::ops::{Add, Index, IndexMut, RangeInclusive};
+use std::iter::Rev;
+use std::ops::{Add, Index, IndexMut};
 
 // Could be the address of a cell or something else we have one of for each axis
 
@@ -60,7 +61,7 @@ impl AxisPair<f64> {
 }
 
 // For now a rect only makes sense in VisIdx - In LogIdx any list of points is possible due to remapping
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CellRect {
     pub start_row: VisIdx,
     pub end_row: VisIdx,
@@ -85,15 +86,24 @@ impl CellRect {
         CellRect::new((row, row), (col, col))
     }
 
-    // Todo impl Iterator
-    pub fn rows(&self) -> Map<RangeInclusive<usize>, fn(usize) -> VisIdx> {
-        VisIdx::range_inc_iter(self.start_row, self.end_row) // Todo work out how to support custom range
+    pub fn rows(&self) -> VisIdxIter {
+        VisIdx::range_inc_iter(self.start_row, self.end_row)
     }
 
-    pub fn cols(&self) -> Map<RangeInclusive<usize>, fn(usize) -> VisIdx> {
+    pub fn cols(&self) -> VisIdxIter {
         VisIdx::range_inc_iter(self.start_col, self.end_col)
     }
 
+    // Bottom to top, eg to paint or navigate upwards
+    pub fn rows_rev(&self) -> Rev<VisIdxIter> {
+        self.rows().rev()
+    }
+
+    // Right to left
+    pub fn cols_rev(&self) -> Rev<VisIdxIter> {
+        self.cols().rev()
+    }
+
     fn contains_cell(&self, cell_addr: &AxisPair<VisIdx>) -> bool {
         self.contains_idx(TableAxis::Columns, cell_addr.col)
             && self.contains_idx(TableAxis::Rows, cell_addr.row)
@@ -111,20 +121,25 @@ impl CellRect {
         start <= idx && end >= idx
     }
 
+    // The cells in both, None if they don't overlap
+    fn intersect(&self, other: &CellRect) -> Option<CellRect> {
+        let overlap = |axis| {
+            let ((start, end), (other_start, other_end)) = (self.range(axis), other.range(axis));
+            Some((start.max(other_start), end.min(other_end))).filter(|(s, e)| s <= e)
+        };
+        Some(CellRect::new(
+            overlap(TableAxis::Rows)?,
+            overlap(TableAxis::Columns)?,
+        ))
+    }
+
     pub fn to_pixel_rect(&self, measures: &AxisPair<AxisMeasure>) -> Option<Rect> {
         let col_measure = &measures[TableAxis::Columns];
         let row_measure = &measures[TableAxis::Rows];
 
-        Some(Rect::new(
-            col_measure.first_pixel_from_vis(self.start_col)?,
-            row_measure.first_pixel_from_vis(self.start_row)?,
-            col_measure
-                .far_pixel_from_vis(self.end_col)
-                .unwrap_or_else(|| col_measure.total_pixel_length()),
-            row_measure
-                .far_pixel_from_vis(self.end_row)
-                .unwrap_or_else(|| row_measure.total_pixel_length()),
-        ))
+        let (x0, x1) = col_measure.pixel_range(self.start_col, self.end_col)?;
+        let (y0, y1) = row_measure.pixel_range(self.start_row, self.end_row)?;
+        Some(Rect::new(x0, y0, x1, y1))
     }
 }
 
@@ -237,6 +252,7 @@ pub enum IndicesSelection {
     NoSelection,
     Single(VisIdx),
     Range { focus: VisIdx, extent: VisIdx },
+    Multi(Vec<IndicesSelection>),
     //Range(from, to)
 }
 
@@ -248,6 +264,7 @@ impl IndicesSelection {
                 let (min, max) = VisIdx::ascending(*focus, *extent);
                 vis_idx >= min && vis_idx <= max
             }
+            IndicesSelection::Multi(sels) => sels.iter().any(|s| s.vis_index_selected(vis_idx)),
             _ => false,
         }
     }
@@ -259,7 +276,9 @@ pub enum TableSelection {
     SingleCell(SingleCell),
     SingleSlice(SingleSlice),
     CellRange(CellRange),
-    SliceRange(SliceRange), //  Discontiguous
+    SliceRange(SliceRange),
+    // Discontiguous, the last one added holds the focus
+    Multi(Vector<TableSelection>),
 }
 
 impl Default for TableSelection {
@@ -268,6 +287,52 @@ impl Default for TableSelection {
     }
 }
 
+// What clicking or moving onto a cell selects
+#[derive(Data, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionMode {
+    Cell,
+    Row, // The whole row the cell is in
+}
+
+impl SelectionMode {
+    pub(crate) fn shape(&self, sel: TableSelection) -> TableSelection {
+        match (self, sel) {
+            (SelectionMode::Row, TableSelection::SingleCell(sc)) => {
+                TableSelection::SingleSlice(SingleSlice::new(TableAxis::Rows, sc))
+            }
+            (_, sel) => sel,
+        }
+    }
+}
+
+// None if either axis is empty
+fn clamp_to_lens(vis: &AxisPair<VisIdx>, lens: &AxisPair<usize>) -> Option<AxisPair<VisIdx>> {
+    if lens.row == 0 || lens.col == 0 {
+        None
+    } else {
+        Some(vis.zip_with(lens, |idx, len| VisIdx(idx.0.min(len - 1))))
+    }
+}
+
+// Steps on from `vis` past cells with nothing to focus, like group headers
+fn step_to_mapped(
+    vis: AxisPair<VisIdx>,
+    axis: TableAxis,
+    step: VisOffset,
+    lens: &AxisPair<usize>,
+    cell_demap: &impl CellDemap,
+) -> Option<AxisPair<VisIdx>> {
+    let mut vis = clamp_to_lens(&vis, lens)?;
+    while cell_demap.get_log_cell(&vis).is_none() {
+        let next = clamp_to_lens(&vis.move_by(axis, step), lens)?;
+        if next == vis {
+            return None;
+        }
+        vis = next;
+    }
+    Some(vis)
+}
+
 pub trait CellDemap {
     fn get_log_idx(&self, axis: TableAxis, vis: &VisIdx) -> Option<LogIdx>;
 
@@ -294,48 +359,72 @@ impl<F: Fn(&TableSelection) -> Option<TableSelection>> TableSelectionMod for F {
 pub struct DrawableSelections {
     pub focus: Option<AxisPair<VisIdx>>,
     pub ranges: Vec<CellRect>,
+    pub slices: Vec<CellRect>, // Whole rows or columns
+    // The cell with a handle at its bottom right corner, dragged to copy its value to others
+    pub fill_handle: Option<AxisPair<VisIdx>>,
 }
 
 impl DrawableSelections {
     pub fn new(focus: Option<AxisPair<VisIdx>>, ranges: Vec<CellRect>) -> Self {
-        DrawableSelections { focus, ranges }
+        DrawableSelections {
+            focus,
+            ranges,
+            slices: Vec::new(),
+            fill_handle: None,
+        }
+    }
+
+    fn of_slice(focus: AxisPair<VisIdx>, slice: CellRect) -> Self {
+        DrawableSelections {
+            slices: vec![slice],
+            ..DrawableSelections::new(Some(focus), Vec::new())
+        }
     }
 }
 
 impl TableSelection {
+    // Moves the focus by `amount`, staying within the visible lengths of each axis
     pub fn move_focus(
         &self,
         axis: TableAxis,
         amount: VisOffset,
+        lens: AxisPair<usize>,
         cell_demap: &impl CellDemap,
     ) -> Option<TableSelection> {
+        let step = VisOffset(amount.0.signum());
+        let moved = |vis: &AxisPair<VisIdx>| {
+            step_to_mapped(vis.move_by(axis, amount), axis, step, &lens, cell_demap)
+        };
         match self {
             Self::NoSelection => {
-                let vis_origin = AxisPair::new(VisIdx(0), VisIdx(0));
+                let origin = AxisPair::new(VisIdx(0), VisIdx(0));
+                let vis_origin =
+                    step_to_mapped(origin, TableAxis::Rows, VisOffset(1), &lens, cell_demap)?;
                 cell_demap
                     .get_log_cell(&vis_origin)
                     .map(|log| Self::SingleCell(SingleCell::new(vis_origin, log)))
             }
             Self::SingleCell(SingleCell { vis, .. }) => {
-                let new_vis = vis.move_by(axis, amount); // Should check upper bounds
+                let new_vis = moved(vis)?;
                 cell_demap
                     .get_log_cell(&new_vis)
                     .map(|log| Self::SingleCell(SingleCell::new(new_vis, log)))
             }
             Self::SingleSlice(slice) => {
-                let new_vis = slice.focus.vis.move_by(axis, amount);
+                let new_vis = moved(&slice.focus.vis)?;
                 cell_demap.get_log_cell(&new_vis).map(|log| {
                     Self::SingleSlice(SingleSlice::new(slice.axis, SingleCell::new(new_vis, log)))
                 })
             }
             Self::CellRange(CellRange { focus, .. }) => {
-                let new_vis = focus.vis.move_by(axis, amount);
+                let new_vis = moved(&focus.vis)?;
                 cell_demap
                     .get_log_cell(&new_vis)
                     .map(|log| Self::SingleCell(SingleCell::new(new_vis, log)))
             }
             Self::SliceRange(SliceRange { axis, range }) => {
                 let new_vis = range.focus.vis.move_by(*axis, amount);
+                let new_vis = step_to_mapped(new_vis, *axis, step, &lens, cell_demap)?;
                 cell_demap.get_log_cell(&new_vis).map(|log| {
                     Self::SingleSlice(SingleSlice::new(
                         *axis,
@@ -343,6 +432,67 @@ impl TableSelection {
                     ))
                 })
             }
+            // Moving collapses back to a single cell
+            Self::Multi(_) => {
+                let new_vis = moved(self.vis_focus()?)?;
+                cell_demap
+                    .get_log_cell(&new_vis)
+                    .map(|log| Self::SingleCell(SingleCell::new(new_vis, log)))
+            }
+        }
+    }
+
+    // Jumps the focus to a cell, clamped to the last one. Slices stay slices, anything else
+    // becomes a single cell.
+    pub fn move_focus_to(
+        &self,
+        target: AxisPair<VisIdx>,
+        last: AxisPair<VisIdx>,
+        cell_demap: &impl CellDemap,
+    ) -> Option<TableSelection> {
+        let vis = AxisPair::new(target.row.min(last.row), target.col.min(last.col));
+        let focus = SingleCell::new(vis, cell_demap.get_log_cell(&vis)?);
+        Some(match self {
+            Self::SingleSlice(SingleSlice { axis, .. })
+            | Self::SliceRange(SliceRange { axis, .. }) => {
+                Self::SingleSlice(SingleSlice::new(*axis, focus))
+            }
+            _ => Self::SingleCell(focus),
+        })
+    }
+
+    // Everything up to the last visible cell, or nothing if that can't be mapped
+    pub fn select_all(last: AxisPair<VisIdx>, cell_demap: &impl CellDemap) -> TableSelection {
+        let first = AxisPair::new(VisIdx(0), VisIdx(0));
+        match (
+            cell_demap.get_log_cell(&first),
+            cell_demap.get_log_cell(&last),
+        ) {
+            (Some(log_first), Some(log_last)) => Self::CellRange(CellRange::new(
+                SingleCell::new(first, log_first),
+                SingleCell::new(last, log_last),
+            )),
+            _ => Self::NoSelection,
+        }
+    }
+
+    // The block of cells covered, with slices running across to the last cell. Of a
+    // discontiguous selection, the block holding the focus.
+    pub fn to_cell_rect(&self, last: AxisPair<VisIdx>) -> Option<CellRect> {
+        match self {
+            Self::SingleCell(sc) => Some(CellRect::point(sc.vis.row, sc.vis.col)),
+            Self::SingleSlice(_) | Self::CellRange(_) | Self::SliceRange(_) => {
+                let bounding = CellRect::new((VisIdx(0), last.row), (VisIdx(0), last.col));
+                // Drawn slices overhang the bounds by a cell, so trim them back
+                let drawable = self.get_drawable_selections(&bounding);
+                let rect = drawable.ranges.into_iter().chain(drawable.slices).next()?;
+                Some(CellRect::new(
+                    (rect.start_row, rect.end_row.min(last.row)),
+                    (rect.start_col, rect.end_col.min(last.col)),
+                ))
+            }
+            Self::Multi(sels) => sels.last()?.to_cell_rect(last),
+            Self::NoSelection => None,
         }
     }
 
@@ -356,6 +506,37 @@ impl TableSelection {
             (Self::CellRange(CellRange { focus, .. }), Self::SingleCell(ext)) => {
                 Some(Self::CellRange(CellRange::new(focus.clone(), ext.clone())))
             }
+            // Slices grow along their own axis, as extend_in_axis does
+            (Self::SingleSlice(SingleSlice { axis, focus }), _) => {
+                let ext = Self::slice_extent(&sel, *axis)?;
+                Some(Self::SliceRange(SliceRange {
+                    axis: *axis,
+                    range: CellRange::new(focus.clone(), ext),
+                }))
+            }
+            (Self::SliceRange(SliceRange { axis, range }), _) => {
+                let ext = Self::slice_extent(&sel, *axis)?;
+                Some(Self::SliceRange(SliceRange {
+                    axis: *axis,
+                    range: CellRange::new(range.focus.clone(), ext),
+                }))
+            }
+            // Extend the most recently added part
+            (Self::Multi(sels), Self::SingleCell(_)) => {
+                let mut sels = sels.clone();
+                let last = sels.pop_back()?.move_extent(sel.clone())?;
+                sels.push_back(last);
+                Some(Self::Multi(sels))
+            }
+            _ => None,
+        }
+    }
+
+    // The cell a slice on `axis` should extend to
+    fn slice_extent(sel: &TableSelection, axis: TableAxis) -> Option<SingleCell> {
+        match sel {
+            Self::SingleCell(ext) => Some(ext.clone()),
+            Self::SingleSlice(ext) if ext.axis == axis => Some(ext.focus.clone()),
             _ => None,
         }
     }
@@ -403,8 +584,15 @@ impl TableSelection {
     }
 
     pub fn add_selection(&self, sel: TableSelection) -> Option<TableSelection> {
-        // Todo selection layers
-        Some(sel)
+        match self {
+            Self::NoSelection => Some(sel),
+            Self::Multi(sels) => {
+                let mut sels = sels.clone();
+                sels.push_back(sel);
+                Some(Self::Multi(sels))
+            }
+            _ => Some(Self::Multi(Vector::from(vec![self.clone(), sel]))),
+        }
     }
 
     pub fn has_focus(&self) -> bool {
@@ -421,6 +609,7 @@ impl TableSelection {
                 range: CellRange { focus, .. },
                 ..
             }) => Some(focus),
+            Self::Multi(sels) => sels.last().and_then(|sel| sel.focus()),
         }
     }
 
@@ -460,21 +649,27 @@ impl TableSelection {
                     IndicesSelection::NoSelection
                 }
             }
+            Self::Multi(sels) => IndicesSelection::Multi(
+                sels.iter()
+                    .map(|sel| sel.to_axis_selection(for_axis, _cell_demap))
+                    .collect(),
+            ),
         }
     }
 
     pub fn get_drawable_selections(&self, bounding: &CellRect) -> DrawableSelections {
         match &self {
             TableSelection::SingleCell(sc) if bounding.contains_cell(&sc.vis) => {
-                DrawableSelections::new(Some(sc.vis), Default::default())
+                DrawableSelections {
+                    fill_handle: Some(sc.vis),
+                    ..DrawableSelections::new(Some(sc.vis), Default::default())
+                }
             }
             TableSelection::SingleSlice(sl)
                 if bounding.contains_idx(sl.axis, sl.focus.vis[sl.axis]) =>
             {
-                DrawableSelections::new(
-                    Some(sl.focus.vis),
-                    vec![sl.to_cell_rect(bounding.range(sl.axis.cross_axis()))],
-                )
+                let cross = bounding.range(sl.axis.cross_axis());
+                DrawableSelections::of_slice(sl.focus.vis, sl.to_cell_rect(cross))
             }
             TableSelection::CellRange(CellRange { focus, extent }) => {
                 let row =
@@ -484,24 +679,56 @@ impl TableSelection {
                     extent.vis[TableAxis::Columns],
                 );
 
-                let cell_rect = CellRect::new(row, col);
-
-                //TODO: Intersection with bounding box
-                DrawableSelections::new(Some(focus.vis), vec![cell_rect])
+                match CellRect::new(row, col).intersect(bounding) {
+                    Some(cell_rect) => {
+                        let focus = Some(focus.vis).filter(|focus| bounding.contains_cell(focus));
+                        DrawableSelections::new(focus, vec![cell_rect])
+                    }
+                    None => DrawableSelections::new(None, Default::default()),
+                }
             }
             TableSelection::SliceRange(sr)
                 if bounding.contains_idx(sr.axis, sr.range.focus.vis[sr.axis])
                     || bounding.contains_idx(sr.axis, sr.range.extent.vis[sr.axis]) =>
             {
-                DrawableSelections::new(
-                    Some(sr.range.focus.vis),
-                    vec![sr.to_cell_rect(bounding.range(sr.axis.cross_axis()))],
-                )
+                let cross = bounding.range(sr.axis.cross_axis());
+                DrawableSelections::of_slice(sr.range.focus.vis, sr.to_cell_rect(cross))
+            }
+            TableSelection::Multi(sels) => {
+                let (mut ranges, mut slices) = (Vec::new(), Vec::new());
+                for sel in sels {
+                    let drawable = sel.get_drawable_selections(bounding);
+                    ranges.extend(drawable.ranges);
+                    slices.extend(drawable.slices);
+                    // A lone cell has no range, so give it one to show it is selected
+                    if let (TableSelection::SingleCell(_), Some(focus)) = (sel, drawable.focus) {
+                        ranges.push(CellRect::point(focus.row, focus.col));
+                    }
+                }
+                let focus = self
+                    .vis_focus()
+                    .filter(|focus| bounding.contains_cell(focus))
+                    .copied();
+                DrawableSelections {
+                    slices,
+                    ..DrawableSelections::new(focus, ranges)
+                }
             }
             _ => DrawableSelections::new(None, Default::default()),
         }
     }
 
+    // Whether the cell is selected, on its own or as part of a range
+    pub fn selects_cell(&self, vis: &AxisPair<VisIdx>) -> bool {
+        let drawable = self.get_drawable_selections(&CellRect::point(vis.row, vis.col));
+        drawable.focus.as_ref() == Some(vis)
+            || drawable
+                .ranges
+                .iter()
+                .chain(&drawable.slices)
+                .any(|range| range.contains_cell(vis))
+    }
+
     pub fn fully_selects_heading(&self, in_axis: TableAxis, idx: VisIdx) -> bool {
         match self {
             TableSelection::SingleSlice(SingleSlice { axis, focus })
@@ -514,6 +741,9 @@ impl TableSelection {
             {
                 true
             }
+            TableSelection::Multi(sels) => sels
+                .iter()
+                .any(|sel| sel.fully_selects_heading(in_axis, idx)),
             _ => false,
         }
     }
@@ -527,6 +757,20 @@ impl TableSelection {
                 let (a, b) = VisIdx::ascending(range.focus.vis[in_axis], range.extent.vis[in_axis]);
                 Some(VisIdx::range_inc_iter(a, b).collect())
             }
+            TableSelection::Multi(sels) => {
+                let mut indices: Vec<VisIdx> = sels
+                    .iter()
+                    .filter_map(|sel| sel.fully_selected_on_axis(in_axis))
+                    .flatten()
+                    .collect();
+                indices.sort();
+                indices.dedup();
+                if indices.is_empty() {
+                    None
+                } else {
+                    Some(indices)
+                }
+            }
             _ => None,
         }
     }
@@ -537,3 +781,161 @@ impl From<SingleCell> for TableSelection {
         TableSelection::SingleCell(sc)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::axis_measure::{AxisPair, LogIdx, TableAxis, VisIdx, VisOffset};
+    use crate::selection::{
+        CellRect, IndicesSelection, SelectionMode, SingleCell, SingleSlice, TableSelection,
+    };
+    use crate::Remap;
+
+    fn focused_at(row: usize, col: usize) -> TableSelection {
+        let vis = AxisPair::new(VisIdx(row), VisIdx(col));
+        TableSelection::SingleCell(SingleCell::new(vis, AxisPair::new(LogIdx(row), LogIdx(col))))
+    }
+
+    fn focus_after(sel: &TableSelection, axis: TableAxis, amount: isize) -> AxisPair<VisIdx> {
+        let demap = AxisPair::new(Remap::Pristine, Remap::Pristine);
+        let lens = AxisPair::new(3, 2);
+        let moved = sel
+            .move_focus(axis, VisOffset(amount), lens, &demap)
+            .expect("moved");
+        *moved.vis_focus().expect("focus")
+    }
+
+    #[test]
+    fn move_focus_stops_at_each_edge() {
+        let top_left = focused_at(0, 0);
+        let bottom_right = focused_at(2, 1);
+
+        let up = focus_after(&top_left, TableAxis::Rows, -1);
+        assert_eq!(up.row, VisIdx(0));
+        let left = focus_after(&top_left, TableAxis::Columns, -1);
+        assert_eq!(left.col, VisIdx(0));
+        let down = focus_after(&bottom_right, TableAxis::Rows, 1);
+        assert_eq!(down.row, VisIdx(2));
+        let right = focus_after(&bottom_right, TableAxis::Columns, 1);
+        assert_eq!(right.col, VisIdx(1));
+    }
+
+    #[test]
+    fn move_focus_in_empty_table() {
+        let demap = AxisPair::new(Remap::Pristine, Remap::Pristine);
+        let lens = AxisPair::new(0, 2);
+        let sel =
+            TableSelection::NoSelection.move_focus(TableAxis::Rows, VisOffset(1), lens, &demap);
+        assert!(sel.is_none());
+    }
+
+    #[test]
+    fn extending_a_row_slice() {
+        let slice = TableSelection::SingleSlice(SingleSlice::new(
+            TableAxis::Rows,
+            SingleCell::new(
+                AxisPair::new(VisIdx(1), VisIdx(0)),
+                AxisPair::new(LogIdx(1), LogIdx(0)),
+            ),
+        ));
+        let range = slice.move_extent(focused_at(3, 1)).expect("extended");
+        let range = range.move_extent(focused_at(4, 0)).expect("extended again");
+
+        let anchor = AxisPair::new(VisIdx(1), VisIdx(0));
+        assert_eq!(range.vis_focus(), Some(&anchor));
+        let demap = AxisPair::new(Remap::Pristine, Remap::Pristine);
+        match range.to_axis_selection(TableAxis::Rows, &demap) {
+            IndicesSelection::Range { focus, extent } => {
+                assert_eq!((focus, extent), (VisIdx(1), VisIdx(4)))
+            }
+            other => panic!("Expected a range, got {:?}", other),
+        }
+
+        let bounding = CellRect::new((VisIdx(0), VisIdx(9)), (VisIdx(0), VisIdx(2)));
+        let drawable = range.get_drawable_selections(&bounding);
+        assert!(drawable.ranges.is_empty()); // Drawn as whole rows instead
+        assert_eq!(drawable.slices.len(), 1);
+        assert_eq!(drawable.slices[0].rows().count(), 4);
+    }
+
+    #[test]
+    fn rects_iterate_either_way() {
+        let rect = CellRect::new((VisIdx(2), VisIdx(5)), (VisIdx(1), VisIdx(3)));
+        let rows: Vec<_> = rect.rows_rev().collect();
+        assert_eq!(rows, vec![VisIdx(5), VisIdx(4), VisIdx(3), VisIdx(2)]);
+        let cols: Vec<_> = rect.cols_rev().collect();
+        assert_eq!(cols, vec![VisIdx(3), VisIdx(2), VisIdx(1)]);
+        let every_other: Vec<_> = rect.rows().step_by(2).collect();
+        assert_eq!(every_other, vec![VisIdx(2), VisIdx(4)]);
+    }
+
+    #[test]
+    fn row_mode_moves_whole_rows() {
+        let row = SelectionMode::Row.shape(focused_at(1, 1));
+        let demap = AxisPair::new(Remap::Pristine, Remap::Pristine);
+        let lens = AxisPair::new(3, 2);
+        let moved = row.move_focus(TableAxis::Rows, VisOffset(1), lens, &demap);
+        match moved.map(|sel| SelectionMode::Row.shape(sel)) {
+            Some(TableSelection::SingleSlice(SingleSlice { axis, focus })) => {
+                assert_eq!(axis, TableAxis::Rows);
+                assert_eq!(focus.vis.row, VisIdx(2));
+            }
+            other => panic!("Expected a row, got {:?}", other),
+        }
+        assert!(matches!(
+            SelectionMode::Cell.shape(focused_at(1, 1)),
+            TableSelection::SingleCell(_)
+        ));
+    }
+
+    #[test]
+    fn selects_cells_in_rows_and_alone() {
+        let cell = |row, col| AxisPair::new(VisIdx(row), VisIdx(col));
+        let single = focused_at(1, 1);
+        assert!(single.selects_cell(&cell(1, 1)));
+        assert!(!single.selects_cell(&cell(1, 0)));
+
+        let row = SelectionMode::Row.shape(focused_at(1, 1));
+        assert!(row.selects_cell(&cell(1, 0)));
+        assert!(!row.selects_cell(&cell(2, 1)));
+    }
+
+    #[test]
+    fn ranges_are_drawn_within_the_bounds() {
+        let range = focused_at(2, 1)
+            .move_extent(focused_at(12, 3))
+            .expect("extended");
+        let bounding = CellRect::new((VisIdx(5), VisIdx(20)), (VisIdx(0), VisIdx(9)));
+        let drawable = range.get_drawable_selections(&bounding);
+        assert_eq!(drawable.focus, None); // Scrolled off above
+        let rect = &drawable.ranges[0];
+        assert_eq!((rect.start_row, rect.end_row), (VisIdx(5), VisIdx(12)));
+        assert_eq!((rect.start_col, rect.end_col), (VisIdx(1), VisIdx(3)));
+
+        let below = CellRect::new((VisIdx(13), VisIdx(20)), (VisIdx(0), VisIdx(9)));
+        assert!(range.get_drawable_selections(&below).ranges.is_empty());
+    }
+
+    #[test]
+    fn copied_blocks_stay_within_the_table() {
+        let last = AxisPair::new(VisIdx(9), VisIdx(2));
+        let column = TableSelection::SingleSlice(SingleSlice::new(
+            TableAxis::Columns,
+            SingleCell::new(
+                AxisPair::new(VisIdx(0), VisIdx(1)),
+                AxisPair::new(LogIdx(0), LogIdx(1)),
+            ),
+        ));
+        let rect = column.to_cell_rect(last).expect("column");
+        assert_eq!((rect.start_row, rect.end_row), (VisIdx(0), VisIdx(9)));
+        assert_eq!((rect.start_col, rect.end_col), (VisIdx(1), VisIdx(1)));
+
+        // Of several blocks, the one last added
+        let range = focused_at(4, 0)
+            .move_extent(focused_at(5, 1))
+            .expect("extended");
+        let multi = TableSelection::Multi(vec![focused_at(1, 1), range].into());
+        let rect = multi.to_cell_rect(last).expect("multi");
+        assert_eq!((rect.start_row, rect.end_row), (VisIdx(4), VisIdx(5)));
+        assert_eq!((rect.start_col, rect.end_col), (VisIdx(0), VisIdx(1)));
+    }
+}
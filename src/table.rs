@@ -1,19 +1,24 @@
 use crate::axis_measure::{AxisMeasure, AxisPair, TableAxis, VisOffset};
 use crate::cells::CellsDelegate;
-use crate::headings::HeadersFromData;
-use crate::selection::CellDemap;
+use crate::corner::Corner;
+use crate::data::SortSpec;
+use crate::footer::Footer;
+use crate::headings::{row_headings_width, HeadersFromData};
+use crate::selection::{CellDemap, CellRange, CellRect, SingleCell, SliceRange};
 use crate::{
-    CellRender, Cells, Headings, IndexedData, IndexedItems, LogIdx, Remap, RemapSpec, TableConfig,
-    TableSelection, VisIdx,
+    CellRender, Cells, Headings, IndexedData, IndexedItems, LogIdx, Remap, RemapSpec,
+    SortDirection, TableConfig, TableSelection, TextCell, VisIdx,
 };
+use druid::im::Vector;
 use druid::widget::{
     Axis, Container, CrossAxisAlignment, DefaultScopePolicy, Flex,
     Scope, Scroll,
 };
 use druid::{
-    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, Lens, LifeCycle, LifeCycleCtx, PaintCtx,
-    Size, UpdateCtx, Widget, WidgetExt, WidgetId, WidgetPod, Point,
+    theme, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, Lens, LifeCycle,
+    LifeCycleCtx, PaintCtx, Size, UpdateCtx, Widget, WidgetExt, WidgetId, WidgetPod, Point,
 };
+use druid::kurbo::{Rect, Vec2};
 use druid_bindings::*;
 
 pub struct HeaderBuild<
@@ -130,14 +135,105 @@ where
     }
 }
 
+// How the user has arranged the columns, to keep between runs. Columns are by logical index,
+// and entries for columns the table no longer has are ignored when applied.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableLayout {
+    pub column_widths: Vec<f64>,           // By logical column
+    pub column_order: Vec<usize>,          // As shown, including hidden columns
+    pub sort: Vec<(usize, SortDirection)>, // Row sort, most significant first
+    pub hidden_columns: Vec<usize>,
+}
+
+// Where the cells are, to draw over the table, see CELL_GEOMETRY. Points and rects are in
+// the cells' viewport: (0, 0) is the top left of the scrolled area that shows the cells, so
+// they move as it scrolls, and frozen rows and columns are where they are drawn. Cells are
+// single visible indices, without their column spans or merged rows.
+#[derive(Clone, Debug)]
+pub struct TableGeometry {
+    measures: AxisPair<AxisMeasure>,
+    scroll: Vec2, // How far the cells are scrolled from their origin
+    frozen: AxisPair<usize>,
+}
+
+impl TableGeometry {
+    pub fn scroll(&self) -> Vec2 {
+        self.scroll
+    }
+
+    fn scroll_on(&self, axis: TableAxis) -> f64 {
+        match axis {
+            TableAxis::Columns => self.scroll.x,
+            TableAxis::Rows => self.scroll.y,
+        }
+    }
+
+    // Where a cell starts on the axis in the viewport, and how long it is
+    fn span_on(&self, axis: TableAxis, idx: VisIdx) -> Option<(f64, f64)> {
+        let measure = &self.measures[axis];
+        let (start, length) = (
+            measure.first_pixel_from_vis(idx)?,
+            measure.pixels_length_for_vis(idx)?,
+        );
+        let scroll = self.scroll_on(axis);
+        let shift = match frozen_band(measure, scroll, self.frozen[axis]) {
+            Some((shift, _)) if idx.0 < self.frozen[axis] => shift,
+            _ => 0.,
+        };
+        Some((start + shift - scroll, length))
+    }
+
+    // None if there is no such cell, even when it is scrolled out of view
+    pub fn cell_rect(&self, cell: &AxisPair<VisIdx>) -> Option<Rect> {
+        let (x, width) = self.span_on(TableAxis::Columns, cell.col)?;
+        let (y, height) = self.span_on(TableAxis::Rows, cell.row)?;
+        Some(Rect::from_origin_size((x, y), (width, height)))
+    }
+
+    // The cell drawn at a point, with the frozen cells over those scrolled under them
+    pub fn cell_at(&self, point: Point) -> Option<AxisPair<VisIdx>> {
+        let idx_on = |axis: TableAxis, pixel: f64| {
+            let (measure, scroll) = (&self.measures[axis], self.scroll_on(axis));
+            let pixel = unfrozen_pixel(measure, scroll, pixel + scroll, self.frozen[axis]);
+            measure.vis_idx_from_pixel(pixel)
+        };
+        Some(AxisPair::new(
+            idx_on(TableAxis::Rows, point.y)?,
+            idx_on(TableAxis::Columns, point.x)?,
+        ))
+    }
+}
+
+// The first `frozen` columns, or rows, are drawn shifted along by the scroll on their axis, so
+// they stay in view. Returns the shift and the length of the frozen band.
+fn frozen_band(measure: &AxisMeasure, scroll: f64, frozen: usize) -> Option<(f64, f64)> {
+    if frozen == 0 {
+        return None;
+    }
+    let length = measure
+        .far_pixel_from_vis(VisIdx(frozen - 1))
+        .unwrap_or_else(|| measure.total_pixel_length());
+    Some((scroll.max(0.), length))
+}
+
+// Maps a pixel along the axis, from the cells' origin as drawn, back to the measure's pixel
+fn unfrozen_pixel(measure: &AxisMeasure, scroll: f64, pixel: f64, frozen: usize) -> f64 {
+    match frozen_band(measure, scroll, frozen) {
+        Some((shift, length)) if pixel - shift < length => pixel - shift,
+        _ => pixel,
+    }
+}
+
 #[derive(Data, Clone, Debug, Lens)]
 pub(crate) struct TableState<TableData: Data> {
-    scroll_x: f64,
-    scroll_y: f64,
+    pub(crate) scroll_x: f64,
+    pub(crate) scroll_y: f64,
     pub(crate) data: TableData,
     pub(crate) remap_specs: AxisPair<RemapSpec>,
     pub(crate) remaps: AxisPair<Remap>,
     pub(crate) selection: TableSelection,
+    pub(crate) footer: Vector<Option<String>>, // By logical column
     #[data(ignore)]
     pub(crate) measures: AxisPair<AxisMeasure>, // TODO
 }
@@ -151,12 +247,154 @@ impl<TableData: Data> TableState<TableData> {
             remap_specs: AxisPair::new(RemapSpec::default(), RemapSpec::default()),
             remaps: AxisPair::new(Remap::Pristine, Remap::Pristine),
             selection: TableSelection::default(),
+            footer: Vector::new(),
             measures,
         }
     }
 
-    pub fn remap_axis(&mut self, axis: TableAxis, f: impl Fn(&TableData, &RemapSpec) -> Remap) {
-        self.remaps[axis] = f(&self.data, &self.remap_specs[axis]);
+    fn scroll_on(&self, axis: TableAxis) -> f64 {
+        match axis {
+            TableAxis::Columns => self.scroll_x,
+            TableAxis::Rows => self.scroll_y,
+        }
+    }
+
+    pub(crate) fn frozen_band(&self, axis: TableAxis, frozen: usize) -> Option<(f64, f64)> {
+        frozen_band(&self.measures[axis], self.scroll_on(axis), frozen)
+    }
+
+    pub(crate) fn frozen_shift(&self, axis: TableAxis, idx: VisIdx, frozen: usize) -> f64 {
+        match self.frozen_band(axis, frozen) {
+            Some((shift, _)) if idx.0 < frozen => shift,
+            _ => 0.,
+        }
+    }
+
+    // Maps a pixel along the axis as drawn back to the measure's pixel
+    pub(crate) fn unfrozen_pixel(&self, axis: TableAxis, pixel: f64, frozen: usize) -> f64 {
+        unfrozen_pixel(&self.measures[axis], self.scroll_on(axis), pixel, frozen)
+    }
+
+    pub(crate) fn geometry(&self, frozen: AxisPair<usize>) -> TableGeometry {
+        TableGeometry {
+            measures: self.measures.clone(),
+            scroll: Vec2::new(self.scroll_x, self.scroll_y),
+            frozen,
+        }
+    }
+
+    // Scrolls as little as it takes for first..=last on the axis to be fully in a viewport
+    // `view_len` long, clear of the frozen band. Frozen cells are always in view. Something
+    // longer than the viewport is lined up with its start.
+    pub(crate) fn ensure_visible_on(
+        &mut self,
+        axis: TableAxis,
+        (first, last): (VisIdx, VisIdx),
+        view_len: f64,
+        frozen: usize,
+    ) {
+        if first.0 < frozen {
+            return;
+        }
+        let measure = &self.measures[axis];
+        let (start, end) = match measure.pixel_range(first, last) {
+            Some(range) => range,
+            None => return,
+        };
+        let band = self
+            .frozen_band(axis, frozen)
+            .map_or(0., |(_, length)| length);
+        let scroll = match axis {
+            TableAxis::Columns => &mut self.scroll_x,
+            TableAxis::Rows => &mut self.scroll_y,
+        };
+        if start < *scroll + band {
+            *scroll = (start - band).max(0.);
+        } else if end > *scroll + view_len {
+            *scroll = (end - view_len).min(start - band);
+        }
+    }
+
+    pub(crate) fn ensure_visible(
+        &mut self,
+        rect: &CellRect,
+        viewport: Size,
+        frozen: AxisPair<usize>,
+    ) {
+        let rows = (rect.start_row, rect.end_row);
+        self.ensure_visible_on(TableAxis::Rows, rows, viewport.height, frozen.row);
+        let cols = (rect.start_col, rect.end_col);
+        self.ensure_visible_on(TableAxis::Columns, cols, viewport.width, frozen.col);
+    }
+
+    pub(crate) fn export_layout(&self, columns: usize) -> TableLayout {
+        let widths = &self.measures[TableAxis::Columns];
+        let col_spec = &self.remap_specs[TableAxis::Columns];
+        // The order with the hidden columns still in it, so they come back where they were
+        let mut with_hidden = col_spec.clone();
+        with_hidden.hidden.clear();
+        let order = with_hidden.remap_placements(LogIdx(columns.saturating_sub(1)));
+        let mut hidden: Vec<usize> = col_spec.hidden.iter().map(|log_idx| log_idx.0).collect();
+        hidden.sort_unstable();
+        TableLayout {
+            column_widths: (0..columns)
+                .filter_map(|col| widths.pixels_length_for_log(LogIdx(col)))
+                .collect(),
+            column_order: (0..order.vis_len(columns))
+                .filter_map(|vis| order.get_log_idx(VisIdx(vis)))
+                .map(|log_idx| log_idx.0)
+                .collect(),
+            sort: self.remap_specs[TableAxis::Rows]
+                .sort_by
+                .iter()
+                .map(|sort| (sort.idx, sort.direction))
+                .collect(),
+            hidden_columns: hidden,
+        }
+    }
+
+    // Columns past `columns`, or mentioned twice, are skipped. Columns added since the layout
+    // was taken keep their width and go after the others.
+    pub(crate) fn apply_layout(&mut self, layout: &TableLayout, columns: usize) {
+        let widths = &mut self.measures[TableAxis::Columns];
+        for (col, width) in layout.column_widths.iter().enumerate().take(columns) {
+            if width.is_finite() && *width > 0. {
+                widths.set_pixel_length_for_log(LogIdx(col), *width);
+            }
+        }
+
+        let col_spec = &mut self.remap_specs[TableAxis::Columns];
+        col_spec.placements.clear();
+        let mut placed = vec![false; columns];
+        for &col in &layout.column_order {
+            if col < columns && !placed[col] {
+                placed[col] = true;
+                col_spec.place(LogIdx(col), VisIdx(col_spec.placements.len()));
+            }
+        }
+        col_spec.hidden = layout
+            .hidden_columns
+            .iter()
+            .filter(|col| **col < columns)
+            .map(|col| LogIdx(*col))
+            .collect();
+
+        let row_spec = &mut self.remap_specs[TableAxis::Rows];
+        row_spec.sort_by.clear();
+        for &(col, direction) in &layout.sort {
+            if col < columns && !row_spec.sort_by.iter().any(|sort| sort.idx == col) {
+                row_spec.add_sort(SortSpec::new(col, direction));
+            }
+        }
+    }
+
+    // The function also gets the remap being replaced
+    pub fn remap_axis(
+        &mut self,
+        axis: TableAxis,
+        f: impl Fn(&TableData, &RemapSpec, &Remap) -> Remap,
+    ) {
+        self.remaps[axis] = f(&self.data, &self.remap_specs[axis], &self.remaps[axis]);
     }
 
     pub fn explicit_header_move(&mut self, axis: TableAxis, moved_to_idx: VisIdx) {
@@ -168,11 +406,36 @@ impl<TableData: Data> TableState<TableData> {
         );
         let mut offset = 0;
         if let Some(headers_moved) = self.selection.fully_selected_on_axis(axis) {
-            for vis_idx in headers_moved {
-                if let Some(log_idx) = self.remaps[axis].get_log_idx(vis_idx) {
-                    self.remap_specs[axis].place(log_idx, moved_to_idx + VisOffset(offset));
-                    offset += 1;
-                }
+            if axis == TableAxis::Rows {
+                let moved: Vec<LogIdx> = headers_moved
+                    .into_iter()
+                    .filter_map(|vis_idx| self.remaps[axis].get_log_idx(vis_idx))
+                    .collect();
+                let min_len = moved
+                    .iter()
+                    .map(|log_idx| log_idx.0 + 1)
+                    .fold(moved_to_idx.0 + 1, usize::max);
+                let shown = self.remaps[axis].explicit_order(min_len);
+                self.remap_specs[axis].move_rows(shown, &moved, moved_to_idx);
+                return;
+            }
+            let moved: Vec<LogIdx> = headers_moved
+                .into_iter()
+                .filter_map(|vis_idx| self.remaps[axis].get_log_idx(vis_idx))
+                .collect();
+            for log_idx in &moved {
+                self.remap_specs[axis].place(*log_idx, moved_to_idx + VisOffset(offset));
+                offset += 1;
+            }
+            // The moved headings stay selected where they land, next to each other
+            if let (Some(first), Some(last)) = (moved.first(), moved.last()) {
+                let landed = |offset: isize, log_idx: LogIdx| {
+                    let vis_idx = moved_to_idx + VisOffset(offset);
+                    let vis = AxisPair::new_for_axis(axis, vis_idx, VisIdx(0));
+                    SingleCell::new(vis, AxisPair::new_for_axis(axis, log_idx, LogIdx(0)))
+                };
+                let range = CellRange::new(landed(0, *first), landed(offset - 1, *last));
+                self.selection = TableSelection::SliceRange(SliceRange { axis, range });
             }
         }
     }
@@ -184,17 +447,50 @@ impl CellDemap for AxisPair<Remap> {
     }
 }
 
+// The remaps, only for the items the measures have laid out. A pristine remap maps any index,
+// so this keeps selections made from the headings to cells that exist.
+pub(crate) struct MeasuredDemap<'a> {
+    remaps: &'a AxisPair<Remap>,
+    measures: &'a AxisPair<AxisMeasure>,
+}
+
+impl<'a> MeasuredDemap<'a> {
+    pub(crate) fn new(remaps: &'a AxisPair<Remap>, measures: &'a AxisPair<AxisMeasure>) -> Self {
+        MeasuredDemap { remaps, measures }
+    }
+}
+
+impl CellDemap for MeasuredDemap<'_> {
+    fn get_log_idx(&self, axis: TableAxis, vis: &VisIdx) -> Option<LogIdx> {
+        if vis.0 < self.measures[axis].vis_len() {
+            self.remaps[axis].get_log_idx(*vis)
+        } else {
+            None
+        }
+    }
+}
+
 struct TableChild<TableData: Data> {
     _ids: Ids,
     pod: WidgetPod<TableState<TableData>, Box<dyn Widget<TableState<TableData>>>>,
+    // The row headings' config, as their width is taken out of what flex columns can share
+    row_headings: Option<TableConfig>,
+    fit_width: bool, // TableConfig::fit_width
 }
 
 impl<TableData: Data> TableChild<TableData> {
     pub fn new(
         _ids: Ids,
         pod: WidgetPod<TableState<TableData>, Box<dyn Widget<TableState<TableData>>>>,
+        row_headings: Option<TableConfig>,
+        fit_width: bool,
     ) -> Self {
-        TableChild { _ids, pod }
+        TableChild {
+            _ids,
+            pod,
+            row_headings,
+            fit_width,
+        }
     }
 }
 
@@ -267,6 +563,7 @@ impl<Args: TableArgsT + 'static> Table<Args> {
         );
 
         let cells_delegate = args.cells_delegate;
+        let show_footer = cells_delegate.has_footer();
         let cells = Cells::new(table_config.clone(), cells_delegate);
 
         // These have to be added before we move Cells into scroll
@@ -280,7 +577,24 @@ impl<Args: TableArgsT + 'static> Table<Args> {
                 ),
         );
 
-        Self::add_headings(args.col_h, args.row_h, table_config, ids, cells_scroll)
+        let (col_h, row_h) = (args.col_h, args.row_h);
+        if show_footer {
+            let footer = Footer::new(
+                table_config.clone(),
+                TextCell::new().text_color(theme::TEXT_COLOR),
+            );
+            let footer_scroll = Scroll::new(footer).disable_scrollbars().binding(
+                TableState::<Args::TableData>::scroll_x
+                    .bind(ScrollToProperty::new(Axis::Horizontal)),
+            );
+            let cells_column = Flex::column()
+                .cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_flex_child(cells_scroll, 1.)
+                .with_child(footer_scroll);
+            Self::add_headings(col_h, row_h, table_config, ids, true, cells_column)
+        } else {
+            Self::add_headings(col_h, row_h, table_config, ids, false, cells_scroll)
+        }
     }
 
     fn add_headings(
@@ -288,72 +602,103 @@ impl<Args: TableArgsT + 'static> Table<Args> {
         row_h: Option<Args::RowH>,
         table_config: TableConfig,
         ids: Ids,
+        footer_needed: bool,
         widget: impl Widget<TableState<Args::TableData>> + 'static,
     ) -> TableChild<Args::TableData> {
-        if let (Some(AxisIds { headers, scroll }), Some(col_h)) = (ids.columns, col_h) {
-            let (source, render) = col_h.content();
-
-            let col_headings = Headings::new(
-                TableAxis::Columns,
-                table_config.clone(),
-                source,
-                render,
-                true,
-            );
-            let ch_scroll = Scroll::new(col_headings.with_id(headers))
-                .disable_scrollbars()
-                .with_id(scroll)
-                .binding(
-                    TableState::<Args::TableData>::scroll_x
-                        .bind(ScrollToProperty::new(Axis::Horizontal)),
+        let row_headings = Self::row_headings(&table_config, footer_needed, ids.rows, row_h);
+        let corner_needed = row_headings.is_some();
+        let rtl = table_config.right_to_left;
+        let body: Box<dyn Widget<TableState<Args::TableData>>> = match row_headings {
+            Some(rh_col) => Box::new(
+                beside(rtl, rh_col, widget).cross_axis_alignment(CrossAxisAlignment::Start),
+            ),
+            None => Box::new(widget),
+        };
+
+        let table: Box<dyn Widget<TableState<Args::TableData>>> =
+            if let (Some(AxisIds { headers, scroll }), Some(col_h)) = (ids.columns, col_h) {
+                let (source, render) = col_h.content();
+
+                let col_headings = Headings::new(
+                    TableAxis::Columns,
+                    table_config.clone(),
+                    source,
+                    render,
+                    true,
                 );
+                let ch_scroll = Scroll::new(col_headings.with_id(headers))
+                    .disable_scrollbars()
+                    .with_id(scroll)
+                    .binding(
+                        TableState::<Args::TableData>::scroll_x
+                            .bind(ScrollToProperty::new(Axis::Horizontal)),
+                    );
+
+                // The corner fills the height of the column headings, which grows to fit
+                // wrapped headers
+                let top: Box<dyn Widget<TableState<Args::TableData>>> = if corner_needed {
+                    let corner = Corner::new(table_config.clone());
+                    let top = beside(rtl, corner, ch_scroll);
+                    Box::new(top.cross_axis_alignment(CrossAxisAlignment::Fill))
+                } else {
+                    Box::new(ch_scroll)
+                };
+                let cells_column = Flex::column()
+                    .cross_axis_alignment(CrossAxisAlignment::Start)
+                    .with_child(top)
+                    .with_flex_child(body, 1.);
+                Box::new(cells_column)
+            } else {
+                body
+            };
 
-            let cells_column = Flex::column()
-                .cross_axis_alignment(CrossAxisAlignment::Start)
-                .with_child(ch_scroll)
-                .with_flex_child(widget, 1.);
-            Self::add_row_headings(table_config, true, row_h, ids, cells_column)
+        let fit_width = table_config.fit_width;
+        if corner_needed {
+            let table = Box::new(table.center());
+            TableChild::new(ids, WidgetPod::new(table), Some(table_config), fit_width)
         } else {
-            Self::add_row_headings(table_config, false, row_h, ids, widget)
+            TableChild::new(ids, WidgetPod::new(table), None, fit_width)
         }
     }
 
-    fn add_row_headings(
-        table_config: TableConfig,
-        corner_needed: bool,
+    fn row_headings(
+        table_config: &TableConfig,
+        footer_needed: bool,
+        ids: Option<AxisIds>,
         row_h: Option<Args::RowH>,
-        ids: Ids,
-        widget: impl Widget<TableState<Args::TableData>> + 'static,
-    ) -> TableChild<Args::TableData> {
-        if let (Some(AxisIds { headers, scroll }), Some(row_h)) = (ids.rows, row_h) {
-            let (source, render) = row_h.content();
-            let row_headings =
-                Headings::new(TableAxis::Rows, table_config.clone(), source, render, false);
-
-            let row_scroll = Scroll::new(row_headings.with_id(headers))
-                .disable_scrollbars()
-                .with_id(scroll)
-                .binding(
-                    TableState::<Args::TableData>::scroll_y
-                        .bind(ScrollToProperty::new(Axis::Vertical)),
-                );
-
-            let mut rh_col = Flex::column().cross_axis_alignment(CrossAxisAlignment::Start);
-            if corner_needed {
-                rh_col.add_spacer(table_config.col_header_height)
-            }
-            rh_col.add_flex_child(row_scroll, 1.);
-
-            let row = Flex::row()
-                .cross_axis_alignment(CrossAxisAlignment::Start)
-                .with_child(rh_col)
-                .with_flex_child(widget, 1.)
-                .center();
+    ) -> Option<Flex<TableState<Args::TableData>>> {
+        let (AxisIds { headers, scroll }, row_h) = (ids?, row_h?);
+        let (source, render) = row_h.content();
+        let row_headings =
+            Headings::new(TableAxis::Rows, table_config.clone(), source, render, false);
+
+        let row_scroll = Scroll::new(row_headings.with_id(headers))
+            .disable_scrollbars()
+            .with_id(scroll)
+            .binding(
+                TableState::<Args::TableData>::scroll_y.bind(ScrollToProperty::new(Axis::Vertical)),
+            );
 
-            TableChild::new(ids, WidgetPod::new(Box::new(row)))
-        } else {
-            TableChild::new(ids, WidgetPod::new(Box::new(widget)))
+        let mut rh_col = Flex::column().cross_axis_alignment(CrossAxisAlignment::Start);
+        rh_col.add_flex_child(row_scroll, 1.);
+        // Keeps the row headings the height of the cells above the footer
+        if footer_needed {
+            rh_col.add_spacer(table_config.col_header_height.clone())
         }
+        Some(rh_col)
+    }
+}
+
+// Right to left, the row headings and the corner above them sit on the right
+fn beside<T: Data>(
+    right_to_left: bool,
+    side: impl Widget<T> + 'static,
+    main: impl Widget<T> + 'static,
+) -> Flex<T> {
+    if right_to_left {
+        Flex::row().with_flex_child(main, 1.).with_child(side)
+    } else {
+        Flex::row().with_child(side).with_flex_child(main, 1.)
     }
 }
 
@@ -412,6 +757,19 @@ impl<Args: TableArgsT + 'static> Widget<TableState<Args::TableData>> for Table<A
         env: &Env,
     ) -> Size {
         if let Some(child) = self.child.as_mut() {
+            if bc.is_width_bounded() {
+                let rows = data.data.idx_len();
+                let row_header_width = child.row_headings.as_ref().map_or(0., |config| {
+                    row_headings_width(ctx.text(), config, rows, env)
+                });
+                // Redone as the width changes, eg when the window is resized
+                let available = bc.max().width - row_header_width;
+                if child.fit_width {
+                    data.measures[TableAxis::Columns].fit_all(available);
+                } else {
+                    data.measures[TableAxis::Columns].fit_flex(available);
+                }
+            }
             child
                 .pod
                 .set_origin(ctx, Point::ORIGIN);
@@ -427,3 +785,134 @@ impl<Args: TableArgsT + 'static> Widget<TableState<Args::TableData>> for Table<A
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::table::{MeasuredDemap, TableLayout, TableState};
+    use crate::{AxisMeasure, AxisMeasurementType, AxisPair, Remap, SortDirection, TableAxis};
+    use crate::{TableSelection, VisIdx};
+    use druid::kurbo::Rect;
+    use druid::Point;
+
+    #[test]
+    fn ensuring_visible_scrolls_the_least() {
+        let mut rows = AxisMeasure::new(AxisMeasurementType::Uniform, 20.);
+        rows.set_axis_properties(0., 10, &Remap::Pristine);
+        let cols = AxisMeasure::new(AxisMeasurementType::Uniform, 100.);
+        let mut state = TableState::new((), AxisPair::new(rows, cols));
+        let mut show = |row: usize, frozen: usize| {
+            let rows = (VisIdx(row), VisIdx(row));
+            state.ensure_visible_on(TableAxis::Rows, rows, 50., frozen);
+            state.scroll_y
+        };
+
+        assert_eq!(show(1, 0), 0.);
+        assert_eq!(show(4, 0), 50.); // Just brought up to the bottom
+        assert_eq!(show(3, 0), 50.);
+        assert_eq!(show(1, 0), 20.);
+        assert_eq!(show(1, 1), 0.); // Clear of the frozen first row
+        assert_eq!(show(0, 1), 0.);
+    }
+
+    #[test]
+    fn geometry_is_in_the_scrolled_viewport() {
+        let mut rows = AxisMeasure::new(AxisMeasurementType::Uniform, 20.);
+        rows.set_axis_properties(0., 10, &Remap::Pristine);
+        let mut cols = AxisMeasure::new(AxisMeasurementType::Uniform, 100.);
+        cols.set_axis_properties(0., 3, &Remap::Pristine);
+        let mut state = TableState::new((), AxisPair::new(rows, cols));
+        state.scroll_x = 30.;
+        state.scroll_y = 50.;
+        let geometry = state.geometry(AxisPair::new(1, 0));
+        let cell = |row, col| AxisPair::new(VisIdx(row), VisIdx(col));
+
+        // The frozen first row stays at the top
+        let frozen = Rect::new(-30., 0., 70., 20.);
+        assert_eq!(geometry.cell_rect(&cell(0, 0)), Some(frozen));
+        let scrolled = Rect::new(70., 10., 170., 30.);
+        assert_eq!(geometry.cell_rect(&cell(3, 1)), Some(scrolled));
+        assert_eq!(geometry.cell_rect(&cell(3, 5)), None);
+
+        assert_eq!(geometry.cell_at(Point::new(5., 5.)), Some(cell(0, 0)));
+        assert_eq!(geometry.cell_at(Point::new(75., 25.)), Some(cell(3, 1)));
+    }
+
+    #[test]
+    fn wide_tables_find_their_columns_after_scrolling() {
+        let rows = AxisMeasure::new(AxisMeasurementType::Uniform, 20.);
+        let mut cols = AxisMeasure::new(AxisMeasurementType::Individual, 50.);
+        cols.set_axis_properties(0., 500, &Remap::Pristine);
+        let mut state = TableState::new((), AxisPair::new(rows, cols));
+        state.scroll_x = 10_000.;
+        let measure = &state.measures[TableAxis::Columns];
+        assert_eq!(
+            measure.vis_range_from_pixels(10_000., 10_400.),
+            (VisIdx(200), VisIdx(208))
+        );
+
+        // Clicks are in the cells' own pixels, where the frozen column has moved along too
+        let clicked = |x| {
+            let x = state.unfrozen_pixel(TableAxis::Columns, x, 1);
+            state.measures[TableAxis::Columns].vis_idx_from_pixel(x)
+        };
+        assert_eq!(clicked(10_020.), Some(VisIdx(0)));
+        assert_eq!(clicked(10_075.), Some(VisIdx(201)));
+        assert_eq!(clicked(24_990.), Some(VisIdx(499)));
+    }
+
+    #[test]
+    fn layouts_round_trip_without_stale_columns() {
+        let mut cols = AxisMeasure::new(AxisMeasurementType::Individual, 50.);
+        cols.set_axis_properties(1., 3, &Remap::Pristine);
+        let rows = AxisMeasure::new(AxisMeasurementType::Uniform, 20.);
+        let mut state = TableState::new((), AxisPair::new(rows, cols));
+
+        let layout = TableLayout {
+            column_widths: vec![80., 40., 60., 90.],
+            column_order: vec![5, 2, 0, 2, 1],
+            sort: vec![
+                (1, SortDirection::Descending),
+                (7, SortDirection::Ascending),
+            ],
+            hidden_columns: vec![0, 9],
+        };
+        state.apply_layout(&layout, 3);
+
+        let applied = state.export_layout(3);
+        assert_eq!(applied.column_widths, vec![80., 40., 60.]);
+        assert_eq!(applied.column_order, vec![2, 0, 1]);
+        assert_eq!(applied.sort, vec![(1, SortDirection::Descending)]);
+        assert_eq!(applied.hidden_columns, vec![0]);
+        state.apply_layout(&applied, 3);
+        assert_eq!(state.export_layout(3), applied);
+    }
+
+    #[test]
+    fn heading_clicks_only_select_cells_that_exist() {
+        let remaps = AxisPair::new(Remap::Pristine, Remap::Pristine);
+        let mut rows = AxisMeasure::new(AxisMeasurementType::Uniform, 20.);
+        let mut cols = AxisMeasure::new(AxisMeasurementType::Individual, 100.);
+        cols.set_axis_properties(0., 3, &Remap::Pristine);
+        let mut measures = AxisPair::new(rows.clone(), cols);
+
+        // No rows, so a column has no cells to select
+        let mut selection = TableSelection::NoSelection;
+        let demap = MeasuredDemap::new(&remaps, &measures);
+        selection.select_in_axis(TableAxis::Columns, VisIdx(1), &demap);
+        selection.extend_in_axis(TableAxis::Columns, VisIdx(2), &demap);
+        assert!(!selection.has_focus());
+
+        rows.set_axis_properties(0., 2, &Remap::Pristine);
+        measures.row = rows;
+        let demap = MeasuredDemap::new(&remaps, &measures);
+        selection.select_in_axis(TableAxis::Columns, VisIdx(3), &demap);
+        assert!(!selection.has_focus());
+        selection.select_in_axis(TableAxis::Columns, VisIdx(1), &demap);
+        selection.extend_in_axis(TableAxis::Columns, VisIdx(5), &demap);
+        let columns = |sel: &TableSelection| sel.fully_selected_on_axis(TableAxis::Columns);
+        assert_eq!(columns(&selection), Some(vec![VisIdx(1)]));
+        // Past the last row, so the column stays selected
+        selection.select_in_axis(TableAxis::Rows, VisIdx(2), &demap);
+        assert_eq!(columns(&selection), Some(vec![VisIdx(1)]));
+    }
+}
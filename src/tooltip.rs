@@ -0,0 +1,47 @@
+use druid::kurbo::Vec2;
+use druid::widget::Label;
+use druid::{
+    Env, EventCtx, Point, TimerToken, WidgetExt, WindowConfig, WindowId, WindowLevel,
+    WindowSizePolicy,
+};
+
+// Hovering over part of a widget, eg a heading or a cell, until its tooltip shows
+pub(crate) enum Tooltip<Part> {
+    Idle,
+    Waiting(Part, TimerToken, Point), // Point is where the mouse last was in the window
+    Shown(Part, Option<WindowId>),    // None if there was nothing to show
+}
+
+impl<Part> Tooltip<Part> {
+    pub(crate) fn part(&self) -> Option<&Part> {
+        match self {
+            Tooltip::Idle => None,
+            Tooltip::Waiting(part, ..) => Some(part),
+            Tooltip::Shown(part, _) => Some(part),
+        }
+    }
+
+    // Goes back to idle, giving the tooltip window to close if one is open
+    pub(crate) fn dismiss(&mut self) -> Option<WindowId> {
+        match std::mem::replace(self, Tooltip::Idle) {
+            Tooltip::Shown(_, window) => window,
+            _ => None,
+        }
+    }
+}
+
+// A window just below the pointer, sized to the text
+pub(crate) fn show_tooltip(
+    ctx: &mut EventCtx,
+    text: String,
+    window_pos: Point,
+    env: &Env,
+) -> WindowId {
+    let position = ctx.window().get_position() + window_pos.to_vec2() + Vec2::new(0., 16.);
+    let config = WindowConfig::default()
+        .show_titlebar(false)
+        .window_size_policy(WindowSizePolicy::Content)
+        .set_level(WindowLevel::Tooltip)
+        .set_position(position);
+    ctx.new_sub_window(config, Label::new(text).padding(4.), (), env.clone())
+}